@@ -0,0 +1,90 @@
+//! Measures the records per second, and allocations per record, of the
+//! deserialization of the operations and effects fixtures.
+//!
+//! Run with `cargo bench --bench records`, set `RUNS` to change the
+//! number of runs.
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use stellar_horizon::page::Page;
+use stellar_horizon::resources::{Effect, Operation};
+
+/// Counts the allocations of the process.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Deserializes `page` as a page of `T`, returns the elapsed time, the
+/// allocations and the number of records.
+fn run<T>(page: &str) -> (Duration, usize, usize)
+where
+    T: DeserializeOwned + Serialize + Clone,
+{
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let started = Instant::now();
+    let page: Page<T> = serde_json::from_str(page).unwrap();
+    let elapsed = started.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    (elapsed, allocations, page.records.len())
+}
+
+/// Prints the median of `runs` deserializations of `page`.
+fn bench<T>(name: &str, page: &str, runs: usize)
+where
+    T: DeserializeOwned + Serialize + Clone,
+{
+    let mut results: Vec<_> = (0..runs).map(|_| run::<T>(page)).collect();
+    results.sort();
+    let (elapsed, allocations, records) = results[results.len() / 2];
+    println!(
+        "{} {} records, median of {} runs: {:.0} records/s, {:.1} allocations per record",
+        name,
+        records,
+        runs,
+        records as f64 / elapsed.as_secs_f64(),
+        allocations as f64 / records as f64,
+    );
+}
+
+fn main() {
+    let runs = env_or("RUNS", 1_000);
+    bench::<Operation>(
+        "operations",
+        include_str!("../tests/fixtures/all_operations.json"),
+        runs,
+    );
+    bench::<Effect>(
+        "effects",
+        include_str!("../tests/fixtures/all_effects.json"),
+        runs,
+    );
+}
@@ -0,0 +1,124 @@
+//! Measures the events per second, and allocations per event, of a
+//! ledgers stream served from a local server, for several sizes of the
+//! read buffer.
+//!
+//! Run with `cargo bench --bench stream`, set `EVENTS` and `RUNS` to
+//! change the number of events streamed and of runs.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use stellar_horizon::api;
+use stellar_horizon::client::{HorizonClient, HorizonHttpClient};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::stream::StreamExt;
+
+/// Counts the allocations of the process.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Returns a response streaming `events` ledgers.
+fn response(events: usize) -> Vec<u8> {
+    let ledger: serde_json::Value =
+        serde_json::from_str(include_str!("../tests/fixtures/ledger.json")).unwrap();
+    let ledger = ledger.to_string();
+    let mut response =
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n".to_vec();
+    response.extend_from_slice(b"retry: 1000\nevent: open\ndata: \"hello\"\n\n");
+    for i in 0..events {
+        response.extend_from_slice(format!("id: {}-1\ndata: {}\n\n", i, ledger).as_bytes());
+    }
+    response
+}
+
+/// Starts a server answering each connection with `response`.
+async fn serve(response: Vec<u8>) -> String {
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let _ = socket.write_all(&response).await;
+        }
+    });
+    format!("http://{}", addr)
+}
+
+/// The read buffer sizes compared, 64 KiB is the default.
+const READ_BUFFER_SIZES: [usize; 4] = [1024, 8 * 1024, 64 * 1024, 256 * 1024];
+
+/// Streams `events` ledgers reading with a buffer of `read_buffer_size`
+/// bytes, returns the elapsed time and the allocations.
+async fn run(host: &str, events: usize, read_buffer_size: usize) -> (Duration, usize) {
+    let client = HorizonHttpClient::new_from_str(host)
+        .unwrap()
+        .with_sse_read_buffer_size(read_buffer_size);
+    let mut stream = client.stream(api::ledgers::all()).unwrap().take(events);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let started = Instant::now();
+    while let Some(ledger) = stream.next().await {
+        ledger.unwrap();
+    }
+    let elapsed = started.elapsed();
+    (elapsed, ALLOCATIONS.load(Ordering::Relaxed) - allocations)
+}
+
+#[tokio::main]
+async fn main() {
+    let events = env_or("EVENTS", 100_000);
+    let runs = env_or("RUNS", 8);
+    let host = serve(response(events)).await;
+    for &read_buffer_size in READ_BUFFER_SIZES.iter() {
+        let mut results = Vec::new();
+        for _ in 0..runs {
+            results.push(run(&host, events, read_buffer_size).await);
+        }
+        results.sort();
+        let (elapsed, allocations) = results[results.len() / 2];
+        println!(
+            "stream {} ledgers with a {} KiB read buffer, median of {} runs: {:.0} events/s, {:.1} allocations per event",
+            events,
+            read_buffer_size / 1024,
+            runs,
+            events as f64 / elapsed.as_secs_f64(),
+            allocations as f64 / events as f64,
+        );
+    }
+}
@@ -9,8 +9,7 @@ use url::Url;
 
 /// Creates a request to retrieve a single account.
 pub fn single(public_key: &PublicKey) -> SingleAccountRequest {
-    let account_id = public_key.account_id();
-    SingleAccountRequest { account_id }
+    SingleAccountRequest::new(public_key)
 }
 
 /// Creates a request to retrieve all accounts.
@@ -55,11 +54,19 @@ pub struct AllAccountsRequest {
     order: Option<Order>,
 }
 
+impl SingleAccountRequest {
+    /// Creates a request to retrieve the account of `public_key`.
+    pub fn new(public_key: &PublicKey) -> SingleAccountRequest {
+        let account_id = public_key.account_id();
+        SingleAccountRequest { account_id }
+    }
+}
+
 impl Request for SingleAccountRequest {
     type Response = resources::Account;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let path = format!("/accounts/{}", self.account_id);
+        let path = format!("accounts/{}", self.account_id);
         Ok(host.join(&path)?)
     }
 }
@@ -68,7 +75,7 @@ impl Request for AllAccountsRequest {
     type Response = Page<resources::Account>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/accounts")?;
+        let mut url = host.join("accounts")?;
         if let Some(signer) = self.signer.as_ref() {
             url = url.append_query_param("signer", signer);
         }
@@ -78,7 +85,7 @@ impl Request for AllAccountsRequest {
         if let Some(sponsor) = self.sponsor.as_ref() {
             url = url.append_query_param("sponsor", sponsor);
         }
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
@@ -86,7 +93,7 @@ impl_page_request!(AllAccountsRequest);
 
 #[cfg(test)]
 mod tests {
-    use super::{all, single};
+    use super::{all, single, SingleAccountRequest};
     use crate::request::Request;
     use std::collections::HashMap;
     use stellar_base::crypto::PublicKey;
@@ -104,6 +111,21 @@ mod tests {
             "https://horizon.stellar.org/accounts/GAYOLLLUIZE4DZMBB2ZBKGBUBZLIOYU6XFLW37GBP2VZD3ABNXCW4BVA",
             uri.to_string()
         );
+        assert_eq!(uri, SingleAccountRequest::new(&pk).uri(&host).unwrap());
+    }
+
+    #[test]
+    fn test_single_request_uri_with_path_prefix() {
+        let pk =
+            PublicKey::from_account_id("GAYOLLLUIZE4DZMBB2ZBKGBUBZLIOYU6XFLW37GBP2VZD3ABNXCW4BVA")
+                .unwrap();
+        let host: Url = "https://gateway.example.com/horizon/".parse().unwrap();
+        let req = single(&pk);
+        let uri = req.uri(&host).unwrap();
+        assert_eq!(
+            "https://gateway.example.com/horizon/accounts/GAYOLLLUIZE4DZMBB2ZBKGBUBZLIOYU6XFLW37GBP2VZD3ABNXCW4BVA",
+            uri.to_string()
+        );
     }
 
     #[test]
@@ -1,7 +1,7 @@
 use crate::api::assets::credit_asset_to_string;
 use crate::error::{Error, Result};
 use crate::page::Page;
-use crate::request::{Order, Request, StreamRequest, UrlPageRequestExt};
+use crate::request::{validate_limit, Order, Request, StreamRequest, UrlPageRequestExt};
 use crate::resources;
 use chrono::{DateTime, Duration, Utc};
 use std::convert::TryInto;
@@ -127,9 +127,8 @@ pub enum Resolution {
     OneWeek,
     /// Custom duration.
     ///
-    /// Horizon only supports the value defined in this enum, a custom
-    /// duration can be used as escape hatch if horizon introduces new
-    /// values to this enum.
+    /// The duration is sent as is, without checking that Horizon
+    /// supports it. Use it for resolutions added by new Horizon versions.
     Custom(Duration),
 }
 
@@ -168,7 +167,7 @@ pub struct AllTradesRequest {
     base_asset: Asset,
     counter_asset: Asset,
     order: Option<Order>,
-    limit: Option<i64>,
+    limit: Option<u64>,
     offset: Option<Duration>,
 }
 
@@ -177,11 +176,39 @@ pub struct AllTradesRequest {
 pub struct FeeStatsRequest {}
 
 impl OrderBookRequest {
+    /// Creates a request to retrieve the order book of the `selling` and `buying` resource assets.
+    ///
+    /// Use this to follow the order book of assets returned by
+    /// Horizon, for example the assets of a balance or of an
+    /// `OrderBookSummary`.
+    pub fn from_resource_assets(
+        selling: &resources::Asset,
+        buying: &resources::Asset,
+    ) -> Result<OrderBookRequest> {
+        resources::Asset::is_valid_pair(selling, buying)?;
+        Ok(order_book(selling.try_into()?, buying.try_into()?))
+    }
+
     /// The total number of records returned.
     pub fn with_limit(mut self, limit: u64) -> Self {
         self.limit = Some(limit);
         self
     }
+
+    /// Returns the request limit.
+    pub fn limit(&self) -> &Option<u64> {
+        &self.limit
+    }
+
+    /// Returns the selling asset.
+    pub fn selling(&self) -> &Asset {
+        &self.selling
+    }
+
+    /// Returns the buying asset.
+    pub fn buying(&self) -> &Asset {
+        &self.buying
+    }
 }
 
 impl PathsStrictReceiveRequest {
@@ -200,11 +227,44 @@ impl PathsStrictSendRequest {
     }
 }
 
+impl AllTradesRequest {
+    /// Offset the segments by `offset`.
+    ///
+    /// The offset must be a whole number of hours, less than one day,
+    /// and less than the resolution.
+    pub fn with_offset(mut self, offset: Duration) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Change the order of the returned records.
+    pub fn with_order(mut self, order: &Order) -> Self {
+        self.order = Some(*order);
+        self
+    }
+
+    /// The total number of records returned.
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
 impl Request for OrderBookRequest {
     type Response = resources::OrderBookSummary;
 
+    /// Checks that the assets are a valid pair and the limit is accepted by Horizon.
+    fn validate(&self) -> Result<()> {
+        resources::Asset::is_valid_pair(&(&self.selling).into(), &(&self.buying).into())?;
+        if let Some(limit) = self.limit {
+            validate_limit(limit)?;
+        }
+        Ok(())
+    }
+
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/order_book")?;
+        self.validate()?;
+        let mut url = host.join("order_book")?;
         url = url.append_asset_params(&self.buying, Some("buying"));
         url = url.append_asset_params(&self.selling, Some("selling"));
         if let Some(limit) = &self.limit {
@@ -217,8 +277,20 @@ impl Request for OrderBookRequest {
 impl Request for PathsStrictReceiveRequest {
     type Response = Page<resources::Path>;
 
+    /// Checks that exactly one of the source account and source assets is set.
+    fn validate(&self) -> Result<()> {
+        if self.source_account.is_some() != self.source_assets.is_empty() {
+            return Err(Error::InvalidRequest {
+                field: "source_assets",
+                reason: "set either the source account or the source assets".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/paths/strict-receive")?;
+        self.validate()?;
+        let mut url = host.join("paths/strict-receive")?;
         if let Some(source_account) = &self.source_account {
             url = url.append_query_param("source_account", &source_account);
         }
@@ -236,8 +308,20 @@ impl Request for PathsStrictReceiveRequest {
 impl Request for PathsStrictSendRequest {
     type Response = Page<resources::Path>;
 
+    /// Checks that exactly one of the destination account and destination assets is set.
+    fn validate(&self) -> Result<()> {
+        if self.destination_account.is_some() != self.destination_assets.is_empty() {
+            return Err(Error::InvalidRequest {
+                field: "destination_assets",
+                reason: "set either the destination account or the destination assets".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/paths/strict-send")?;
+        self.validate()?;
+        let mut url = host.join("paths/strict-send")?;
         if let Some(destination_account) = &self.destination_account {
             url = url.append_query_param("destination_account", &destination_account);
         }
@@ -259,8 +343,30 @@ impl StreamRequest for OrderBookRequest {
 impl Request for AllTradesRequest {
     type Response = Page<resources::TradeAggregation>;
 
+    /// Checks that the assets, time range, resolution, offset, and limit are accepted by horizon.
+    fn validate(&self) -> Result<()> {
+        resources::Asset::is_valid_pair(&(&self.base_asset).into(), &(&self.counter_asset).into())?;
+        if self.end_time <= self.start_time {
+            return Err(Error::InvalidTimeRange);
+        }
+        if let Some(limit) = self.limit {
+            validate_limit(limit)?;
+        }
+        let resolution = resolution_to_milliseconds(&self.resolution);
+        if let Some(offset) = &self.offset {
+            let offset = offset.num_milliseconds();
+            let hour = Duration::hours(1).num_milliseconds();
+            let day = Duration::days(1).num_milliseconds();
+            if offset < 0 || offset % hour != 0 || offset >= day || offset >= resolution {
+                return Err(Error::InvalidOffset);
+            }
+        }
+        Ok(())
+    }
+
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/trade_aggregations")?;
+        self.validate()?;
+        let mut url = host.join("trade_aggregations")?;
         let start_time = self.start_time.timestamp_millis();
         url = url.append_query_param("start_time", &start_time.to_string());
         let end_time = self.end_time.timestamp_millis();
@@ -286,7 +392,7 @@ impl Request for FeeStatsRequest {
     type Response = resources::FeeStats;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        Ok(host.join("/fee_stats")?)
+        Ok(host.join("fee_stats")?)
     }
 }
 
@@ -295,7 +401,7 @@ fn serialize_assets_to_query_value(assets: &[CreditAsset]) -> String {
     assets.join(",")
 }
 
-fn resolution_to_milliseconds(resolution: &Resolution) -> u64 {
+fn resolution_to_milliseconds(resolution: &Resolution) -> i64 {
     match resolution {
         Resolution::OneMinute => 60000,
         Resolution::FiveMinutes => 300000,
@@ -303,7 +409,7 @@ fn resolution_to_milliseconds(resolution: &Resolution) -> u64 {
         Resolution::OneHour => 3600000,
         Resolution::OneDay => 86400000,
         Resolution::OneWeek => 604800000,
-        Resolution::Custom(d) => d.num_milliseconds() as u64,
+        Resolution::Custom(d) => d.num_milliseconds(),
     }
 }
 
@@ -311,6 +417,7 @@ fn resolution_to_milliseconds(resolution: &Resolution) -> u64 {
 mod tests {
     use super::*;
     use crate::request::Request;
+    use chrono::TimeZone;
     use std::collections::HashMap;
     use stellar_base::asset::Asset;
     use stellar_base::crypto::PublicKey;
@@ -364,4 +471,174 @@ mod tests {
             .starts_with("https://horizon.stellar.org/order_book?"));
         assert_eq!(Some(&"100".to_string()), query.get("limit"));
     }
+
+    #[test]
+    fn test_order_book_request_uri_with_invalid_limit() {
+        for limit in &[0, 201] {
+            let req = order_book(credit_asset0(), Asset::new_native()).with_limit(*limit);
+            assert!(matches!(
+                req.uri(&host()),
+                Err(Error::InvalidRequest { field: "limit", .. })
+            ));
+        }
+        let req = order_book(credit_asset0(), Asset::new_native()).with_limit(200);
+        assert!(req.uri(&host()).is_ok());
+    }
+
+    #[test]
+    fn test_order_book_request_from_resource_assets() {
+        let selling = resources::Asset::from(&credit_asset0());
+        let buying = resources::Asset::from(&Asset::new_native());
+        let req = OrderBookRequest::from_resource_assets(&selling, &buying)
+            .unwrap()
+            .with_limit(5);
+        assert_eq!(&credit_asset0(), req.selling());
+        assert_eq!(&Asset::new_native(), req.buying());
+        assert_eq!(&Some(5), req.limit());
+        let expected = order_book(credit_asset0(), Asset::new_native()).with_limit(5);
+        assert_eq!(expected.uri(&host()).unwrap(), req.uri(&host()).unwrap());
+
+        assert!(OrderBookRequest::from_resource_assets(&selling, &selling).is_err());
+        let invalid = resources::Asset {
+            asset_type: "credit_alphanum4".to_string(),
+            asset_code: Some("ABCD".to_string()),
+            asset_issuer: None,
+        };
+        assert!(matches!(
+            OrderBookRequest::from_resource_assets(&invalid, &buying),
+            Err(Error::InvalidAsset)
+        ));
+    }
+
+    #[test]
+    fn test_order_book_request_uri_with_same_assets() {
+        let req = order_book(credit_asset0(), credit_asset0());
+        assert!(req.uri(&host()).is_err());
+    }
+
+    #[test]
+    fn test_all_trades_request_uri_with_same_assets() {
+        let now = Utc::now();
+        let req = all_trades(
+            now - Duration::days(1),
+            now,
+            Resolution::OneHour,
+            Asset::new_native(),
+            Asset::new_native(),
+        );
+        assert!(req.uri(&host()).is_err());
+    }
+
+    fn all_trades0(resolution: Resolution) -> AllTradesRequest {
+        let end_time = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        all_trades(
+            end_time - Duration::days(1),
+            end_time,
+            resolution,
+            Asset::new_native(),
+            credit_asset0(),
+        )
+    }
+
+    #[test]
+    fn test_all_trades_request_uri() {
+        let req = all_trades0(Resolution::OneDay)
+            .with_offset(Duration::hours(2))
+            .with_order(&Order::Descending)
+            .with_limit(10);
+        let uri = req.uri(&host()).unwrap();
+        assert!(uri
+            .to_string()
+            .starts_with("https://horizon.stellar.org/trade_aggregations?"));
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"1599913600000".to_string()), query.get("start_time"));
+        assert_eq!(Some(&"1600000000000".to_string()), query.get("end_time"));
+        assert_eq!(Some(&"86400000".to_string()), query.get("resolution"));
+        assert_eq!(Some(&"7200000".to_string()), query.get("offset"));
+        assert_eq!(Some(&"native".to_string()), query.get("base_asset_type"));
+        assert_eq!(Some(&"ABCD".to_string()), query.get("counter_asset_code"));
+        assert_eq!(Some(&"desc".to_string()), query.get("order"));
+        assert_eq!(Some(&"10".to_string()), query.get("limit"));
+    }
+
+    #[test]
+    fn test_all_trades_request_uri_with_invalid_time_range() {
+        let now = Utc::now();
+        let req = all_trades(
+            now,
+            now - Duration::days(1),
+            Resolution::OneHour,
+            Asset::new_native(),
+            credit_asset0(),
+        );
+        assert!(req.uri(&host()).is_err());
+    }
+
+    #[test]
+    fn test_all_trades_request_uri_with_custom_resolution() {
+        let req = all_trades0(Resolution::Custom(Duration::minutes(30)));
+        let uri = req.uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"1800000".to_string()), query.get("resolution"));
+    }
+
+    #[test]
+    fn test_all_trades_request_uri_with_invalid_limit() {
+        for limit in &[0, 1000] {
+            let req = all_trades0(Resolution::OneDay).with_limit(*limit);
+            assert!(matches!(
+                req.uri(&host()),
+                Err(Error::InvalidRequest { field: "limit", .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_all_trades_request_uri_with_invalid_offset() {
+        // Not a whole number of hours.
+        let req = all_trades0(Resolution::OneDay).with_offset(Duration::minutes(30));
+        assert!(req.uri(&host()).is_err());
+        // Not less than one day.
+        let req = all_trades0(Resolution::OneWeek).with_offset(Duration::days(1));
+        assert!(req.uri(&host()).is_err());
+        // Not less than the resolution.
+        let req = all_trades0(Resolution::OneHour).with_offset(Duration::hours(1));
+        assert!(req.uri(&host()).is_err());
+    }
+
+    #[test]
+    fn test_paths_request_validate() {
+        let req = paths_strict_receive(vec![], Asset::new_native(), Stroops::new(100)).unwrap();
+        assert!(matches!(
+            req.validate(),
+            Err(Error::InvalidRequest {
+                field: "source_assets",
+                ..
+            })
+        ));
+        assert!(req.uri(&host()).is_err());
+        assert!(req
+            .clone()
+            .with_source_account(&keypair0())
+            .validate()
+            .is_ok());
+        let credit = CreditAsset::new("ABCD".to_string(), keypair0()).unwrap();
+        let req =
+            paths_strict_receive(vec![credit.clone()], Asset::new_native(), Stroops::new(100))
+                .unwrap();
+        assert!(req.validate().is_ok());
+        assert!(req.with_source_account(&keypair0()).validate().is_err());
+
+        let req = paths_strict_send(Asset::new_native(), vec![], Stroops::new(100)).unwrap();
+        assert!(matches!(
+            req.validate(),
+            Err(Error::InvalidRequest {
+                field: "destination_assets",
+                ..
+            })
+        ));
+        assert!(req.with_destination_account(&keypair0()).validate().is_ok());
+        let req = paths_strict_send(Asset::new_native(), vec![credit], Stroops::new(100)).unwrap();
+        assert!(req.validate().is_ok());
+    }
 }
@@ -1,8 +1,10 @@
-use crate::error::Result;
+use crate::client::HorizonClient;
+use crate::error::{Error, Result};
 use crate::page::Page;
 use crate::request::{Order, PageRequest, Request, UrlPageRequestExt};
 use crate::resources;
-use stellar_base::asset::{Asset, CreditAsset};
+use std::cmp::Reverse;
+use stellar_base::asset::CreditAsset;
 use stellar_base::crypto::PublicKey;
 use url::Url;
 
@@ -42,49 +44,105 @@ impl AllAssetsRequest {
         self.asset_issuer = Some(issuer.account_id());
         self
     }
+
+    /// Returns the asset code filter.
+    pub fn asset_code(&self) -> &Option<String> {
+        &self.asset_code
+    }
+
+    /// Returns the asset issuer filter.
+    pub fn asset_issuer(&self) -> &Option<String> {
+        &self.asset_issuer
+    }
 }
 
 impl Request for AllAssetsRequest {
     type Response = Page<resources::AssetStat>;
 
+    /// Checks that the asset code filter is between 1 and 12 alphanumeric characters.
+    fn validate(&self) -> Result<()> {
+        if let Some(code) = &self.asset_code {
+            if code.is_empty()
+                || code.len() > 12
+                || !code.chars().all(|c| c.is_ascii_alphanumeric())
+            {
+                return Err(Error::InvalidAsset);
+            }
+        }
+        Ok(())
+    }
+
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/assets")?;
+        self.validate()?;
+        let mut url = host.join("assets")?;
         {
             let mut query = url.query_pairs_mut();
             if let Some(asset_code) = &self.asset_code {
-                query.append_pair("asset_code", &asset_code);
+                query.append_pair("asset_code", asset_code);
             }
             if let Some(asset_issuer) = &self.asset_issuer {
-                query.append_pair("asset_issuer", &asset_issuer);
+                query.append_pair("asset_issuer", asset_issuer);
             }
         }
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllAssetsRequest);
 
+/// Fetches up to `max_records` assets, sorted by number of accounts.
+///
+/// Horizon doesn't sort assets by number of accounts, so this function
+/// fetches multiple pages of `request`, starting from its cursor, until
+/// `max_records` assets have been fetched or there are no more assets.
+/// Only the fetched assets are sorted, use `with_asset_code` or
+/// `with_asset_issuer` to narrow down the assets considered.
+pub async fn all_sorted_by_num_accounts<C: HorizonClient>(
+    client: &C,
+    request: AllAssetsRequest,
+    max_records: usize,
+) -> Result<Vec<resources::AssetStat>> {
+    let mut assets = Vec::new();
+    let mut request = request;
+    while assets.len() < max_records {
+        let (_, page) = client.request(request.clone()).await?;
+        let last_paging_token = match page.records.last() {
+            Some(last) => last.paging_token.clone(),
+            None => break,
+        };
+        assets.extend(page.records);
+        request = request.with_cursor(&last_paging_token);
+    }
+    sort_by_num_accounts(&mut assets);
+    assets.truncate(max_records);
+    Ok(assets)
+}
+
+/// Sorts `assets` by number of accounts, in descending order.
+pub fn sort_by_num_accounts(assets: &mut [resources::AssetStat]) {
+    assets.sort_by_key(|asset| Reverse(asset.num_accounts));
+}
+
 pub(crate) fn credit_asset_to_string(asset: &CreditAsset) -> String {
     let code = asset.code();
     let issuer = asset.issuer().account_id();
     format!("{}:{}", code, issuer)
 }
 
-pub(crate) fn asset_to_string(asset: &Asset) -> String {
-    match asset {
-        Asset::Native => "native".to_string(),
-        Asset::Credit(credit) => credit_asset_to_string(&credit),
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use super::all;
+    use super::{all, all_sorted_by_num_accounts, sort_by_num_accounts};
+    use crate::error::Error;
+    use crate::page::Page;
     use crate::request::{Order, PageRequest, Request};
+    use crate::resources::AssetStat;
+    use crate::testing::MockClient;
     use std::collections::HashMap;
     use stellar_base::crypto::PublicKey;
     use url::Url;
 
+    const ASSETS_PAGE: &str = include_str!("../../tests/fixtures/assets.json");
+
     #[test]
     fn test_all_assets_request_uri() {
         let pk =
@@ -101,6 +159,18 @@ mod tests {
         assert_eq!(Some(&pk.account_id()), query.get("asset_issuer"));
     }
 
+    #[test]
+    fn test_all_assets_request_validates_asset_code() {
+        let host: Url = "https://horizon.stellar.org".parse().unwrap();
+        let req = all().with_asset_code("ABCDEFGHIJKL");
+        assert_eq!(&Some("ABCDEFGHIJKL".to_string()), req.asset_code());
+        assert!(req.uri(&host).is_ok());
+        for code in &["", "ABCDEFGHIJKLM", "AB-C"] {
+            let req = all().with_asset_code(*code);
+            assert!(matches!(req.uri(&host), Err(Error::InvalidAsset)));
+        }
+    }
+
     #[test]
     fn test_all_assets_request_uri_with_page() {
         let host: Url = "https://horizon.stellar.org".parse().unwrap();
@@ -117,4 +187,43 @@ mod tests {
         assert_eq!(Some(&"now".to_string()), query.get("cursor"));
         assert_eq!(Some(&"desc".to_string()), query.get("order"));
     }
+
+    #[test]
+    fn test_sort_by_num_accounts() {
+        let mut page: Page<AssetStat> = serde_json::from_str(ASSETS_PAGE).unwrap();
+        sort_by_num_accounts(&mut page.records);
+        let num_accounts: Vec<_> = page.records.iter().map(|a| a.num_accounts).collect();
+        assert_eq!(vec![5, 5, 3, 2, 2, 1, 1, 1, 1, 1], num_accounts);
+    }
+
+    #[tokio::test]
+    async fn test_all_sorted_by_num_accounts() {
+        let client = MockClient::pages(vec![ASSETS_PAGE, ASSETS_PAGE]);
+        let assets = all_sorted_by_num_accounts(&client, all(), 15)
+            .await
+            .unwrap();
+        assert_eq!(15, assets.len());
+        assert_eq!(5, assets[0].num_accounts);
+        assert_eq!(1, assets[14].num_accounts);
+        let uris = client.uris.lock().unwrap();
+        assert_eq!(2, uris.len());
+        let query: HashMap<_, _> = uris[1].query_pairs().into_owned().collect();
+        assert_eq!(
+            Some(
+                &"101_GBIBNMZYLJ5P4B2YKFWWMJYEF4RP77HUGBZWQBRML74ZYEL5FKDQOOQI_credit_alphanum4"
+                    .to_string()
+            ),
+            query.get("cursor")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_all_sorted_by_num_accounts_stops_on_empty_page() {
+        let client = MockClient::pages(vec![ASSETS_PAGE]);
+        let assets = all_sorted_by_num_accounts(&client, all(), 100)
+            .await
+            .unwrap();
+        assert_eq!(10, assets.len());
+        assert_eq!(2, client.uris.lock().unwrap().len());
+    }
 }
@@ -2,10 +2,20 @@ use crate::error::Result;
 use crate::page::Page;
 use crate::request::{Order, PageRequest, Request, UrlPageRequestExt};
 use crate::resources::{self, ClaimableBalanceId};
+use std::convert::TryInto;
 use stellar_base::asset::Asset;
 use stellar_base::crypto::PublicKey;
 use url::Url;
 
+/// Creates a request to retrieve all claimable balances.
+///
+/// Use the request `with_asset`, `with_claimant`, and `with_sponsor`
+/// methods to filter the claimable balances, Horizon returns the
+/// claimable balances that match all the filters.
+pub fn all() -> AllClaimableBalancesRequest {
+    AllClaimableBalancesRequest::default()
+}
+
 /// Creates a request to retrieve all claimable balances filtered by asset.
 pub fn all_by_asset(asset: Asset) -> AllClaimableBalancesRequest {
     AllClaimableBalancesRequest {
@@ -30,12 +40,12 @@ pub fn all_by_sponsor(sponsor: &PublicKey) -> AllClaimableBalancesRequest {
     }
 }
 
-/// Creates a request to retrieve a single offer.
+/// Creates a request to retrieve a single claimable balance.
 pub fn single(balance_id: ClaimableBalanceId) -> SingleClaimableBalanceRequest {
-    SingleClaimableBalanceRequest { balance_id }
+    SingleClaimableBalanceRequest::new(balance_id)
 }
 
-/// Request all open offers.
+/// Request all claimable balances.
 #[derive(Debug, Clone, Default)]
 pub struct AllClaimableBalancesRequest {
     asset: Option<Asset>,
@@ -46,7 +56,7 @@ pub struct AllClaimableBalancesRequest {
     order: Option<Order>,
 }
 
-/// Request a single offer.
+/// Request a single claimable balance.
 #[derive(Debug, Clone)]
 pub struct SingleClaimableBalanceRequest {
     balance_id: ClaimableBalanceId,
@@ -56,7 +66,7 @@ impl Request for AllClaimableBalancesRequest {
     type Response = Page<resources::ClaimableBalance>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/claimable_balances")?;
+        let mut url = host.join("claimable_balances")?;
         if let Some(asset) = self.asset.as_ref() {
             url = url.append_canonical_asset_params("asset", asset);
         }
@@ -66,17 +76,64 @@ impl Request for AllClaimableBalancesRequest {
         if let Some(sponsor) = self.sponsor.as_ref() {
             url = url.append_query_param("sponsor", sponsor);
         }
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllClaimableBalancesRequest);
 
+impl AllClaimableBalancesRequest {
+    /// Filter claimable balances by asset.
+    pub fn with_asset(mut self, asset: Asset) -> Self {
+        self.asset = Some(asset);
+        self
+    }
+
+    /// Filter claimable balances by an asset returned by Horizon.
+    pub fn with_resource_asset(self, asset: &resources::Asset) -> Result<Self> {
+        Ok(self.with_asset(asset.try_into()?))
+    }
+
+    /// Filter claimable balances that can be claimed by `claimant`.
+    pub fn with_claimant(mut self, claimant: &PublicKey) -> Self {
+        self.claimant = Some(claimant.account_id());
+        self
+    }
+
+    /// Filter claimable balances sponsored by `sponsor`.
+    pub fn with_sponsor(mut self, sponsor: &PublicKey) -> Self {
+        self.sponsor = Some(sponsor.account_id());
+        self
+    }
+
+    /// Returns the asset filter.
+    pub fn asset(&self) -> &Option<Asset> {
+        &self.asset
+    }
+
+    /// Returns the claimant filter.
+    pub fn claimant(&self) -> &Option<String> {
+        &self.claimant
+    }
+
+    /// Returns the sponsor filter.
+    pub fn sponsor(&self) -> &Option<String> {
+        &self.sponsor
+    }
+}
+
+impl SingleClaimableBalanceRequest {
+    /// Creates a request to retrieve the claimable balance with id `balance_id`.
+    pub fn new(balance_id: ClaimableBalanceId) -> SingleClaimableBalanceRequest {
+        SingleClaimableBalanceRequest { balance_id }
+    }
+}
+
 impl Request for SingleClaimableBalanceRequest {
     type Response = resources::ClaimableBalance;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        Ok(host.join(&format!("/claimable_balances/{}", self.balance_id))?)
+        Ok(host.join(&format!("claimable_balances/{}", self.balance_id))?)
     }
 }
 
@@ -150,6 +207,49 @@ mod tests {
         assert_eq!(Some(&pk.account_id()), query.get("sponsor"));
     }
 
+    #[test]
+    fn test_all_claimable_balances_with_filters_request_uri() {
+        let uri = all().uri(&host()).unwrap();
+        assert_eq!("/claimable_balances", uri.path());
+        assert_eq!(0, uri.query_pairs().count());
+
+        let claimant =
+            PublicKey::from_account_id("GAYOLLLUIZE4DZMBB2ZBKGBUBZLIOYU6XFLW37GBP2VZD3ABNXCW4BVA")
+                .unwrap();
+        let sponsor =
+            PublicKey::from_account_id("GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623")
+                .unwrap();
+        let asset = resources::Asset {
+            asset_type: "credit_alphanum4".to_string(),
+            asset_code: Some("XYZ".to_string()),
+            asset_issuer: Some(sponsor.account_id()),
+        };
+        let req = all_by_claimant(&claimant)
+            .with_sponsor(&sponsor)
+            .with_resource_asset(&asset)
+            .unwrap()
+            .with_limit(20);
+        assert_eq!(&Some(claimant.account_id()), req.claimant());
+        assert_eq!(&Some(sponsor.account_id()), req.sponsor());
+        assert!(req.asset().is_some());
+        let uri = req.uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&claimant.account_id()), query.get("claimant"));
+        assert_eq!(Some(&sponsor.account_id()), query.get("sponsor"));
+        assert_eq!(
+            Some(&format!("XYZ:{}", sponsor.account_id())),
+            query.get("asset")
+        );
+        assert_eq!(Some(&"20".to_string()), query.get("limit"));
+
+        let invalid = resources::Asset {
+            asset_type: "native".to_string(),
+            asset_code: Some("XYZ".to_string()),
+            asset_issuer: None,
+        };
+        assert!(all().with_resource_asset(&invalid).is_err());
+    }
+
     #[test]
     fn test_single_claimable_balance_request_uri() {
         let req = single(
@@ -159,5 +259,9 @@ mod tests {
         assert!(uri
             .to_string()
             .starts_with("https://horizon.stellar.org/claimable_balances/00000000c582697b67cbec7f9ce64f4dc67bfb2bfd26318bb9f964f4d70e3f41f650b1e6"));
+        let req = SingleClaimableBalanceRequest::new(
+            "00000000c582697b67cbec7f9ce64f4dc67bfb2bfd26318bb9f964f4d70e3f41f650b1e6".to_string(),
+        );
+        assert_eq!(uri, req.uri(&host()).unwrap());
     }
 }
@@ -104,38 +104,34 @@ impl Request for AllEffectsRequest {
     type Response = Page<resources::Effect>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join("/effects")?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join("effects")?;
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllEffectsRequest);
 
-impl StreamRequest for AllEffectsRequest {
-    type Resource = resources::Effect;
-}
+impl_page_stream_request!(AllEffectsRequest, resources::Effect);
 
 impl Request for EffectsForLedgerRequest {
     type Response = Page<resources::Effect>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join(&format!("/ledgers/{}/effects", self.ledger))?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join(&format!("ledgers/{}/effects", self.ledger))?;
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(EffectsForLedgerRequest);
 
-impl StreamRequest for EffectsForLedgerRequest {
-    type Resource = resources::Effect;
-}
+impl_page_stream_request!(EffectsForLedgerRequest, resources::Effect);
 
 impl Request for EffectsForTransactionRequest {
     type Response = Page<resources::Effect>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join(&format!("/transactions/{}/effects", self.tx_hash))?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join(&format!("transactions/{}/effects", self.tx_hash))?;
+        url.append_pagination_params(self)
     }
 }
 
@@ -145,8 +141,8 @@ impl Request for EffectsForOperationRequest {
     type Response = Page<resources::Effect>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join(&format!("/operations/{}/effects", self.operation_id))?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join(&format!("operations/{}/effects", self.operation_id))?;
+        url.append_pagination_params(self)
     }
 }
 
@@ -156,16 +152,14 @@ impl Request for EffectsForAccountRequest {
     type Response = Page<resources::Effect>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join(&format!("/accounts/{}/effects", self.account_id))?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join(&format!("accounts/{}/effects", self.account_id))?;
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(EffectsForAccountRequest);
 
-impl StreamRequest for EffectsForAccountRequest {
-    type Resource = resources::Effect;
-}
+impl_page_stream_request!(EffectsForAccountRequest, resources::Effect);
 
 #[cfg(test)]
 mod tests {
@@ -190,6 +184,14 @@ mod tests {
         assert_eq!(Some(&"now".to_string()), query.get("cursor"));
     }
 
+    #[test]
+    fn test_all_effects_request_uri_with_cursor_now() {
+        let req = all().with_cursor_now();
+        let uri = req.uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"now".to_string()), query.get("cursor"));
+    }
+
     #[test]
     fn test_effects_for_account_request_uri() {
         let pk =
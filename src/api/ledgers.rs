@@ -6,7 +6,7 @@ use url::Url;
 
 /// Creates a request to retrieve a single ledger.
 pub fn single(ledger_sequence: LedgerId) -> SingleLedgerRequest {
-    SingleLedgerRequest { ledger_sequence }
+    SingleLedgerRequest::new(ledger_sequence)
 }
 
 /// Creates a request to retrieve all ledgers.
@@ -32,11 +32,18 @@ pub struct AllLedgersRequest {
     order: Option<Order>,
 }
 
+impl SingleLedgerRequest {
+    /// Creates a request to retrieve the ledger with sequence `ledger_sequence`.
+    pub fn new(ledger_sequence: LedgerId) -> SingleLedgerRequest {
+        SingleLedgerRequest { ledger_sequence }
+    }
+}
+
 impl Request for SingleLedgerRequest {
     type Response = resources::Ledger;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let path = format!("/ledgers/{}", self.ledger_sequence);
+        let path = format!("ledgers/{}", self.ledger_sequence);
         Ok(host.join(&path)?)
     }
 }
@@ -45,21 +52,20 @@ impl Request for AllLedgersRequest {
     type Response = Page<resources::Ledger>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join("/ledgers")?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join("ledgers")?;
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllLedgersRequest);
 
-impl StreamRequest for AllLedgersRequest {
-    type Resource = resources::Ledger;
-}
+impl_page_stream_request!(AllLedgersRequest, resources::Ledger);
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::request::Request;
+    use std::collections::HashMap;
     use url::Url;
 
     fn host() -> Url {
@@ -82,5 +88,56 @@ mod tests {
         assert!(uri
             .to_string()
             .starts_with("https://horizon.stellar.org/ledgers/888"));
+        assert_eq!(uri, SingleLedgerRequest::new(888).uri(&host()).unwrap());
+    }
+
+    #[test]
+    fn test_all_ledgers_request_uri_with_pagination() {
+        let req = all()
+            .with_cursor("123")
+            .with_limit(10)
+            .with_order(&Order::Descending);
+        let uri = req.uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"123".to_string()), query.get("cursor"));
+        assert_eq!(Some(&"10".to_string()), query.get("limit"));
+        assert_eq!(Some(&"desc".to_string()), query.get("order"));
+    }
+
+    #[test]
+    fn test_all_ledgers_request_uri_with_invalid_limit() {
+        use crate::error::Error;
+        assert!(all().with_limit(200).uri(&host()).is_ok());
+        for limit in &[0, 201, 10000] {
+            let err = all().with_limit(*limit).uri(&host()).unwrap_err();
+            assert!(matches!(err, Error::InvalidRequest { field: "limit", .. }));
+            assert_eq!(
+                "invalid request limit: must be between 1 and 200",
+                err.to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_all_ledgers_request_uri_with_order_shorthands() {
+        let uri = all().with_order_desc().uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"desc".to_string()), query.get("order"));
+
+        let uri = all().with_order_asc().uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"asc".to_string()), query.get("order"));
+
+        let uri = all().with_cursor("123").uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(None, query.get("order"));
+    }
+
+    #[test]
+    fn test_single_ledger_response() {
+        let json = include_str!("../../tests/fixtures/ledger.json");
+        let ledger: <SingleLedgerRequest as Request>::Response =
+            serde_json::from_str(json).unwrap();
+        assert_eq!(7, ledger.sequence);
     }
 }
@@ -1,7 +1,6 @@
-use crate::api::assets::asset_to_string;
 use crate::error::Result;
 use crate::page::Page;
-use crate::request::{Order, PageRequest, Request, UrlPageRequestExt};
+use crate::request::{Order, PageRequest, Request, StreamRequest, UrlPageRequestExt};
 use crate::resources::{self, OfferId};
 use stellar_base::asset::Asset;
 use stellar_base::crypto::PublicKey;
@@ -14,7 +13,7 @@ pub fn all() -> AllOffersRequest {
 
 /// Creates a request to retrieve a single offer.
 pub fn single(offer_id: OfferId) -> SingleOfferRequest {
-    SingleOfferRequest { offer_id }
+    SingleOfferRequest::new(offer_id)
 }
 
 /// Creates a request to retrieve the account's offers.
@@ -35,12 +34,18 @@ impl AllOffersRequest {
     }
 
     /// Filter by the asset being sold.
+    ///
+    /// The asset is sent as the `selling_asset_type`,
+    /// `selling_asset_code`, and `selling_asset_issuer` parameters.
     pub fn with_selling(mut self, selling: Asset) -> AllOffersRequest {
         self.selling = Some(selling);
         self
     }
 
     /// Filter by the asset being bought.
+    ///
+    /// The asset is sent as the `buying_asset_type`,
+    /// `buying_asset_code`, and `buying_asset_issuer` parameters.
     pub fn with_buying(mut self, buying: Asset) -> AllOffersRequest {
         self.buying = Some(buying);
         self
@@ -84,30 +89,37 @@ impl Request for AllOffersRequest {
     type Response = Page<resources::Offer>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/offers")?;
+        let mut url = host.join("offers")?;
         if let Some(seller) = self.seller.as_ref() {
             url = url.append_query_param("seller", seller);
         }
         if let Some(selling) = self.selling.as_ref() {
-            url = url.append_query_param("selling", &asset_to_string(selling));
+            url = url.append_asset_params(selling, Some("selling"));
         }
         if let Some(buying) = self.buying.as_ref() {
-            url = url.append_query_param("buying", &asset_to_string(buying));
+            url = url.append_asset_params(buying, Some("buying"));
         }
         if let Some(sponsor) = self.sponsor.as_ref() {
             url = url.append_query_param("sponsor", sponsor);
         }
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllOffersRequest);
 
+impl SingleOfferRequest {
+    /// Creates a request to retrieve the offer with id `offer_id`.
+    pub fn new(offer_id: OfferId) -> SingleOfferRequest {
+        SingleOfferRequest { offer_id }
+    }
+}
+
 impl Request for SingleOfferRequest {
     type Response = resources::Offer;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        Ok(host.join(&format!("/offers/{}", self.offer_id))?)
+        Ok(host.join(&format!("offers/{}", self.offer_id))?)
     }
 }
 
@@ -117,11 +129,13 @@ impl Request for OffersForAccountRequest {
     type Response = Page<resources::Offer>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join(&format!("/accounts/{}/offers", self.account_id))?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join(&format!("accounts/{}/offers", self.account_id))?;
+        url.append_pagination_params(self)
     }
 }
 
+impl_page_stream_request!(OffersForAccountRequest, resources::Offer);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,10 +177,16 @@ mod tests {
             Some(&"GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623".to_string()),
             query.get("seller")
         );
-        assert_eq!(Some(&"native".to_string()), query.get("selling"));
+        assert_eq!(Some(&"native".to_string()), query.get("selling_asset_type"));
+        assert_eq!(None, query.get("selling_asset_code"));
         assert_eq!(
-            Some(&"ABCD:GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623".to_string()),
-            query.get("buying")
+            Some(&"credit_alphanum4".to_string()),
+            query.get("buying_asset_type")
+        );
+        assert_eq!(Some(&"ABCD".to_string()), query.get("buying_asset_code"));
+        assert_eq!(
+            Some(&"GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623".to_string()),
+            query.get("buying_asset_issuer")
         );
     }
 
@@ -189,6 +209,28 @@ mod tests {
         assert_eq!(Some(&sponsor.account_id()), query.get("sponsor"));
     }
 
+    #[test]
+    fn test_all_offers_with_credit_selling_request_uri() {
+        let req = all()
+            .with_selling(credit_asset0())
+            .with_buying(Asset::new_native());
+        let uri = req.uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(
+            Some(&"credit_alphanum4".to_string()),
+            query.get("selling_asset_type")
+        );
+        assert_eq!(Some(&"ABCD".to_string()), query.get("selling_asset_code"));
+        assert_eq!(
+            Some(&"GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623".to_string()),
+            query.get("selling_asset_issuer")
+        );
+        assert_eq!(Some(&"native".to_string()), query.get("buying_asset_type"));
+        assert_eq!(None, query.get("selling"));
+        assert_eq!(None, query.get("seller"));
+        assert_eq!(None, query.get("sponsor"));
+    }
+
     #[test]
     fn test_single_offer_request_uri() {
         let req = single(123);
@@ -196,6 +238,7 @@ mod tests {
         assert!(uri
             .to_string()
             .starts_with("https://horizon.stellar.org/offers/123"));
+        assert_eq!(uri, SingleOfferRequest::new(123).uri(&host()).unwrap());
     }
 
     #[test]
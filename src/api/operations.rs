@@ -1,8 +1,12 @@
 use crate::api::Join;
-use crate::error::Result;
+use crate::client::HorizonClient;
+use crate::error::{Error, Result};
 use crate::page::Page;
+use crate::paginate::{merge_by_toid, paginate};
 use crate::request::{Order, PageRequest, Request, StreamRequest, UrlPageRequestExt};
 use crate::resources::{self, LedgerId};
+use futures::future::{self, Either};
+use futures::stream::{self, Stream, StreamExt};
 use stellar_base::PublicKey;
 use url::Url;
 
@@ -16,10 +20,7 @@ pub fn single<S>(operation_id: S) -> SingleOperationRequest
 where
     S: Into<String>,
 {
-    SingleOperationRequest {
-        operation_id: operation_id.into(),
-        join: None,
-    }
+    SingleOperationRequest::new(operation_id)
 }
 
 /// Creates a request to retrieve the account's operations.
@@ -67,6 +68,17 @@ impl AllOperationsRequest {
 }
 
 impl SingleOperationRequest {
+    /// Creates a request to retrieve the operation with id `operation_id`.
+    pub fn new<S>(operation_id: S) -> SingleOperationRequest
+    where
+        S: Into<String>,
+    {
+        SingleOperationRequest {
+            operation_id: operation_id.into(),
+            join: None,
+        }
+    }
+
     impl_join!();
 }
 
@@ -78,6 +90,17 @@ impl OperationsForAccountRequest {
 impl OperationsForLedgerRequest {
     impl_include_failed!();
     impl_join!();
+
+    /// Request the operations of `ledger` instead.
+    pub fn with_ledger(mut self, ledger: LedgerId) -> Self {
+        self.ledger = ledger;
+        self
+    }
+
+    /// Returns the ledger of the operations.
+    pub fn ledger(&self) -> LedgerId {
+        self.ledger
+    }
 }
 
 impl OperationsForTransactionRequest {
@@ -139,24 +162,22 @@ impl Request for AllOperationsRequest {
     type Response = Page<resources::Operation>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/operations")?;
+        let mut url = host.join("operations")?;
         url = url.append_include_failed(&self.include_failed);
         url = url.appen_join(&self.join);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllOperationsRequest);
 
-impl StreamRequest for AllOperationsRequest {
-    type Resource = resources::Operation;
-}
+impl_page_stream_request!(AllOperationsRequest, resources::Operation);
 
 impl Request for SingleOperationRequest {
     type Response = resources::Operation;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/operations/{}", self.operation_id))?;
+        let mut url = host.join(&format!("operations/{}", self.operation_id))?;
         url = url.appen_join(&self.join);
         Ok(url)
     }
@@ -166,58 +187,199 @@ impl Request for OperationsForAccountRequest {
     type Response = Page<resources::Operation>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/accounts/{}/operations", self.account_id))?;
+        let mut url = host.join(&format!("accounts/{}/operations", self.account_id))?;
         url = url.append_include_failed(&self.include_failed);
         url = url.appen_join(&self.join);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(OperationsForAccountRequest);
 
-impl StreamRequest for OperationsForAccountRequest {
-    type Resource = resources::Operation;
-}
+impl_page_stream_request!(OperationsForAccountRequest, resources::Operation);
 
 impl Request for OperationsForLedgerRequest {
     type Response = Page<resources::Operation>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/ledgers/{}/operations", self.ledger))?;
+        let mut url = host.join(&format!("ledgers/{}/operations", self.ledger))?;
         url = url.append_include_failed(&self.include_failed);
         url = url.appen_join(&self.join);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(OperationsForLedgerRequest);
 
-impl StreamRequest for OperationsForLedgerRequest {
-    type Resource = resources::Operation;
-}
+impl_page_stream_request!(OperationsForLedgerRequest, resources::Operation);
 
 impl Request for OperationsForTransactionRequest {
     type Response = Page<resources::Operation>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/transactions/{}/operations", self.tx_id))?;
+        let mut url = host.join(&format!("transactions/{}/operations", self.tx_id))?;
         url = url.append_include_failed(&self.include_failed);
         url = url.appen_join(&self.join);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(OperationsForTransactionRequest);
 
+/// Returns the operations of multiple accounts in one stream, ordered by id.
+///
+/// Each request in `requests` is paginated separately and the
+/// operations are merged by their total order id, in the order of the
+/// requests. Operations involving more than one of the accounts are
+/// returned once. The stream fails with `Error::InvalidRequest` if the
+/// requests don't all have the same order.
+pub fn for_accounts_merged<'a, C: HorizonClient>(
+    client: &'a C,
+    requests: Vec<OperationsForAccountRequest>,
+) -> impl Stream<Item = Result<resources::Operation>> + 'a {
+    let order = |request: &OperationsForAccountRequest| request.order.unwrap_or(Order::Ascending);
+    let first = requests.first().map(order).unwrap_or(Order::Ascending);
+    if requests.iter().any(|request| order(request) != first) {
+        let err = Error::InvalidRequest {
+            field: "order",
+            reason: "all requests must have the same order".to_string(),
+        };
+        return Either::Left(stream::once(future::ready(Err(err))));
+    }
+    let streams = requests
+        .into_iter()
+        .map(|request| Box::pin(paginate(client, request)))
+        .collect();
+    Either::Right(merge_by_toid(streams, first))
+}
+
+/// Returns the operations of the ledgers from `start` to `end` included.
+///
+/// The operations of each ledger are paginated with `request` for that
+/// ledger, keeping its filters, join, limit, and order. Ledgers are
+/// fetched one after the other, from `start` to `end`, or from `end`
+/// to `start` if `request` is in descending order. The cursor of
+/// `request` only applies to the first ledger, to resume a backfill.
+/// The stream ends after the first error.
+pub fn for_ledger_range<'a, C: HorizonClient>(
+    client: &'a C,
+    start: LedgerId,
+    end: LedgerId,
+    request: OperationsForLedgerRequest,
+) -> impl Stream<Item = Result<resources::Operation>> + 'a {
+    let descending = request.order == Some(Order::Descending);
+    let first = if descending { end } else { start };
+    stream::iter(start..=end)
+        .map(move |ledger| {
+            let ledger = if descending {
+                end - (ledger - start)
+            } else {
+                ledger
+            };
+            let mut request = request.clone().with_ledger(ledger);
+            if ledger != first {
+                request.cursor = None;
+            }
+            paginate(client, request)
+        })
+        .flatten()
+        .scan(false, |failed, operation| {
+            if *failed {
+                return future::ready(None);
+            }
+            *failed = operation.is_err();
+            future::ready(Some(operation))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::api::Join;
     use crate::request::Request;
+    use crate::resources::PagingToken;
+    use crate::testing::{operations_page, MockClient};
+    use crate::toid::Toid;
+    use futures::stream::StreamExt;
     use std::collections::HashMap;
     use stellar_base::crypto::PublicKey;
     use url::Url;
 
+    /// Returns a client that returns pages of the operations with the given ids for each account.
+    fn accounts_client(operations: HashMap<String, Vec<Toid>>) -> MockClient {
+        MockClient::new(move |uri| {
+            let account_id = uri.path_segments().unwrap().nth(1).unwrap();
+            let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+            let cursor = query
+                .get("cursor")
+                .map(|cursor| cursor.parse::<Toid>().unwrap());
+            let limit = query.get("limit").map(|l| l.parse().unwrap()).unwrap_or(10);
+            let toids: Vec<Toid> = operations[account_id]
+                .iter()
+                .filter(|toid| cursor.map(|cursor| **toid > cursor).unwrap_or(true))
+                .take(limit)
+                .cloned()
+                .collect();
+            Ok(operations_page(&toids))
+        })
+    }
+
+    fn keypair(account_id: &str) -> PublicKey {
+        PublicKey::from_account_id(account_id).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_for_accounts_merged() {
+        let first = keypair("GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623");
+        let second = keypair("GAYOLLLUIZE4DZMBB2ZBKGBUBZLIOYU6XFLW37GBP2VZD3ABNXCW4BVA");
+        let mut operations = HashMap::new();
+        operations.insert(
+            first.account_id(),
+            vec![Toid::new(1, 1, 1), Toid::new(3, 1, 1), Toid::new(5, 1, 1)],
+        );
+        operations.insert(
+            second.account_id(),
+            vec![Toid::new(2, 1, 1), Toid::new(3, 1, 1), Toid::new(4, 1, 1)],
+        );
+        let client = accounts_client(operations);
+        let requests = vec![
+            for_account(&first).with_limit(2),
+            for_account(&second).with_limit(2),
+        ];
+        let toids: Vec<Toid> = for_accounts_merged(&client, requests)
+            .map(|operation| operation.unwrap().paging_token().parse::<Toid>().unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            vec![
+                Toid::new(1, 1, 1),
+                Toid::new(2, 1, 1),
+                Toid::new(3, 1, 1),
+                Toid::new(4, 1, 1),
+                Toid::new(5, 1, 1),
+            ],
+            toids
+        );
+    }
+
+    #[tokio::test]
+    async fn test_for_accounts_merged_with_different_orders() {
+        let first = keypair("GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623");
+        let second = keypair("GAYOLLLUIZE4DZMBB2ZBKGBUBZLIOYU6XFLW37GBP2VZD3ABNXCW4BVA");
+        let client = MockClient::empty();
+        let requests = vec![
+            for_account(&first),
+            for_account(&second).with_order(&Order::Descending),
+        ];
+        let results: Vec<_> = for_accounts_merged(&client, requests).collect().await;
+        assert_eq!(1, results.len());
+        assert!(matches!(
+            results[0],
+            Err(Error::InvalidRequest { field: "order", .. })
+        ));
+        assert!(client.uris.lock().unwrap().is_empty());
+    }
+
     fn host() -> Url {
         "https://horizon.stellar.org".parse().unwrap()
     }
@@ -233,6 +395,7 @@ mod tests {
             .starts_with("https://horizon.stellar.org/operations?"));
         let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
         assert_eq!(Some(&"true".to_string()), query.get("include_failed"));
+        assert_eq!(Some(&"transactions".to_string()), query.get("join"));
     }
 
     #[test]
@@ -244,6 +407,8 @@ mod tests {
             .starts_with("https://horizon.stellar.org/operations/8181?"));
         let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
         assert_eq!(Some(&"transactions".to_string()), query.get("join"));
+        let req = SingleOperationRequest::new("8181").with_join(Join::Transactions);
+        assert_eq!(uri, req.uri(&host()).unwrap());
     }
 
     #[test]
@@ -283,4 +448,65 @@ mod tests {
             .to_string()
             .starts_with("https://horizon.stellar.org/transactions/715ffb63673a4ee9b84d4b60924b3e141b34fe3777697f35bad6d4b990524ca2/operations?"));
     }
+
+    #[tokio::test]
+    async fn test_for_ledger_range() {
+        let empty = operations_page::<i64>(&[]);
+        let client = MockClient::pages(vec![
+            operations_page(&[1, 2]),
+            empty.clone(),
+            empty.clone(),
+            operations_page(&[3]),
+            empty,
+        ]);
+        let req = for_ledger(0)
+            .with_limit(2)
+            .with_cursor("10")
+            .with_include_failed(true);
+        assert_eq!(0, req.ledger());
+        let ids: Vec<String> = for_ledger_range(&client, 5, 7, req)
+            .map(|operation| operation.unwrap().base().id.clone())
+            .collect()
+            .await;
+        assert_eq!(vec!["1", "2", "3"], ids);
+        let uris = client.uris.lock().unwrap();
+        let paths: Vec<_> = uris.iter().map(|uri| uri.path()).collect();
+        assert_eq!(
+            vec![
+                "/ledgers/5/operations",
+                "/ledgers/5/operations",
+                "/ledgers/6/operations",
+                "/ledgers/7/operations",
+                "/ledgers/7/operations",
+            ],
+            paths
+        );
+        assert_eq!(
+            Some("include_failed=true&cursor=10&limit=2"),
+            uris[0].query()
+        );
+        assert_eq!(
+            Some("include_failed=true&cursor=2&limit=2"),
+            uris[1].query()
+        );
+        assert_eq!(Some("include_failed=true&limit=2"), uris[2].query());
+    }
+
+    #[tokio::test]
+    async fn test_for_ledger_range_descending() {
+        let client = MockClient::empty();
+        let req = for_ledger(0).with_order(&Order::Descending);
+        let operations: Vec<_> = for_ledger_range(&client, 5, 7, req).collect().await;
+        assert!(operations.is_empty());
+        let uris = client.uris.lock().unwrap();
+        let paths: Vec<_> = uris.iter().map(|uri| uri.path()).collect();
+        assert_eq!(
+            vec![
+                "/ledgers/7/operations",
+                "/ledgers/6/operations",
+                "/ledgers/5/operations",
+            ],
+            paths
+        );
+    }
 }
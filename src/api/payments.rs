@@ -117,57 +117,55 @@ impl Request for AllPaymentsRequest {
     type Response = Page<resources::Payment>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/payments")?;
+        let mut url = host.join("payments")?;
         url = url.append_include_failed(&self.include_failed);
         url = url.appen_join(&self.join);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllPaymentsRequest);
 
-impl StreamRequest for AllPaymentsRequest {
-    type Resource = resources::Payment;
-}
+impl_page_stream_request!(AllPaymentsRequest, resources::Payment);
 
 impl Request for PaymentsForAccountRequest {
     type Response = Page<resources::Payment>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/accounts/{}/payments", self.account_id))?;
+        let mut url = host.join(&format!("accounts/{}/payments", self.account_id))?;
         url = url.append_include_failed(&self.include_failed);
         url = url.appen_join(&self.join);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(PaymentsForAccountRequest);
 
-impl StreamRequest for PaymentsForAccountRequest {
-    type Resource = resources::Payment;
-}
+impl_page_stream_request!(PaymentsForAccountRequest, resources::Payment);
 
 impl Request for PaymentsForLedgerRequest {
     type Response = Page<resources::Payment>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/ledgers/{}/payments", self.ledger_id))?;
+        let mut url = host.join(&format!("ledgers/{}/payments", self.ledger_id))?;
         url = url.append_include_failed(&self.include_failed);
         url = url.appen_join(&self.join);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(PaymentsForLedgerRequest);
 
+impl_page_stream_request!(PaymentsForLedgerRequest, resources::Payment);
+
 impl Request for PaymentsForTransactionRequest {
     type Response = Page<resources::Payment>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/transactions/{}/payments", self.tx_hash))?;
+        let mut url = host.join(&format!("transactions/{}/payments", self.tx_hash))?;
         url = url.append_include_failed(&self.include_failed);
         url = url.appen_join(&self.join);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
@@ -197,6 +195,7 @@ mod tests {
             .starts_with("https://horizon.stellar.org/payments?"));
         let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
         assert_eq!(Some(&"true".to_string()), query.get("include_failed"));
+        assert_eq!(Some(&"transactions".to_string()), query.get("join"));
     }
 
     #[test]
@@ -17,3 +17,20 @@ impl Request for RootRequest {
         Ok(host.clone())
     }
 }
+
+/// Creates a request to retrieve the health status of Horizon.
+pub fn health() -> HealthRequest {
+    HealthRequest {}
+}
+
+/// Request the health status of Horizon.
+#[derive(Debug, Copy, Clone)]
+pub struct HealthRequest {}
+
+impl Request for HealthRequest {
+    type Response = resources::Health;
+
+    fn uri(&self, host: &Url) -> Result<Url> {
+        Ok(host.join("health")?)
+    }
+}
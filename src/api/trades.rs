@@ -9,6 +9,7 @@ use url::Url;
 pub fn all() -> AllTradesRequest {
     AllTradesRequest {
         offer_id: None,
+        trade_type: None,
         base_asset: None,
         counter_asset: None,
         limit: None,
@@ -37,10 +38,33 @@ pub fn for_offer(offer_id: OfferId) -> TradesForOfferRequest {
     }
 }
 
+/// Filter trades by how they were executed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TradeType {
+    /// Trades against offers in the order book.
+    Orderbook,
+    /// Trades against liquidity pools.
+    LiquidityPool,
+    /// All trades.
+    All,
+}
+
+impl TradeType {
+    /// Return the trade type query value.
+    pub fn to_query_value(&self) -> String {
+        match self {
+            TradeType::Orderbook => "orderbook".to_string(),
+            TradeType::LiquidityPool => "liquidity_pool".to_string(),
+            TradeType::All => "all".to_string(),
+        }
+    }
+}
+
 /// Request all trades.
 #[derive(Debug, Clone)]
 pub struct AllTradesRequest {
     offer_id: Option<OfferId>,
+    trade_type: Option<TradeType>,
     base_asset: Option<Asset>,
     counter_asset: Option<Asset>,
     limit: Option<u64>,
@@ -73,6 +97,12 @@ impl AllTradesRequest {
         self
     }
 
+    /// Filter trades by trade type.
+    pub fn with_trade_type(mut self, trade_type: TradeType) -> AllTradesRequest {
+        self.trade_type = Some(trade_type);
+        self
+    }
+
     /// Filter trades by base asset.
     pub fn with_base_asset(mut self, asset: Asset) -> AllTradesRequest {
         self.base_asset = Some(asset);
@@ -90,47 +120,49 @@ impl Request for AllTradesRequest {
     type Response = Page<resources::Trade>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/trades")?;
+        if let (Some(base), Some(counter)) = (&self.base_asset, &self.counter_asset) {
+            resources::Asset::is_valid_pair(&base.into(), &counter.into())?;
+        }
+        let mut url = host.join("trades")?;
         if let Some(offer_id) = &self.offer_id {
             url = url.append_query_param("offer_id", &offer_id.to_string());
         }
+        if let Some(trade_type) = &self.trade_type {
+            url = url.append_query_param("trade_type", &trade_type.to_query_value());
+        }
         if let Some(asset) = &self.base_asset {
-            url = url.append_asset_params(&asset, Some("base"));
+            url = url.append_asset_params(asset, Some("base"));
         }
         if let Some(asset) = &self.counter_asset {
-            url = url.append_asset_params(&asset, Some("counter"));
+            url = url.append_asset_params(asset, Some("counter"));
         }
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllTradesRequest);
 
-impl StreamRequest for AllTradesRequest {
-    type Resource = resources::Trade;
-}
+impl_page_stream_request!(AllTradesRequest, resources::Trade);
 
 impl Request for TradesForAccountRequest {
     type Response = Page<resources::Trade>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join(&format!("/accounts/{}/trades", self.account_id))?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join(&format!("accounts/{}/trades", self.account_id))?;
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(TradesForAccountRequest);
 
-impl StreamRequest for TradesForAccountRequest {
-    type Resource = resources::Trade;
-}
+impl_page_stream_request!(TradesForAccountRequest, resources::Trade);
 
 impl Request for TradesForOfferRequest {
     type Response = Page<resources::Trade>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let url = host.join(&format!("/offers/{}/trades", self.offer_id))?;
-        Ok(url.append_pagination_params(self))
+        let url = host.join(&format!("offers/{}/trades", self.offer_id))?;
+        url.append_pagination_params(self)
     }
 }
 
@@ -171,11 +203,38 @@ mod tests {
             .to_string()
             .starts_with("https://horizon.stellar.org/trades?"));
         let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"123".to_string()), query.get("offer_id"));
+        assert_eq!(None, query.get("trade_type"));
         assert_eq!(Some(&"native".to_string()), query.get("base_asset_type"));
         assert_eq!(
             Some(&"credit_alphanum4".to_string()),
             query.get("counter_asset_type")
         );
+        assert_eq!(Some(&"ABCD".to_string()), query.get("counter_asset_code"));
+        assert_eq!(
+            Some(&"GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623".to_string()),
+            query.get("counter_asset_issuer")
+        );
+    }
+
+    #[test]
+    fn test_all_trades_request_uri_with_trade_type() {
+        let req = all().with_trade_type(TradeType::LiquidityPool);
+        let uri = req.uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"liquidity_pool".to_string()), query.get("trade_type"));
+        let req = all().with_trade_type(TradeType::Orderbook);
+        let uri = req.uri(&host()).unwrap();
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"orderbook".to_string()), query.get("trade_type"));
+    }
+
+    #[test]
+    fn test_all_trades_request_uri_with_same_assets() {
+        let req = all()
+            .with_base_asset(credit_asset0())
+            .with_counter_asset(credit_asset0());
+        assert!(req.uri(&host()).is_err());
     }
 
     #[test]
@@ -19,13 +19,19 @@ pub fn all() -> AllTransactionsRequest {
 
 /// Creates a request to retrieve a single transaction.
 pub fn single<S: Into<String>>(id: S) -> SingleTransactionRequest {
-    SingleTransactionRequest { id: id.into() }
+    SingleTransactionRequest::new(id)
 }
 
 /// Creates a request to submit a transaction.
+///
+/// `tx` can be any transaction envelope, including fee bump
+/// transactions, and is submitted unchanged.
 pub fn submit(tx: &TransactionEnvelope) -> Result<SubmitTransactionRequest> {
     let xdr = tx.xdr_base64()?;
-    Ok(SubmitTransactionRequest { xdr })
+    Ok(SubmitTransactionRequest {
+        xdr,
+        idempotency_key: None,
+    })
 }
 
 /// Creates a request to retrieve a account's transactions.
@@ -69,6 +75,7 @@ pub struct SingleTransactionRequest {
 #[derive(Debug, Clone)]
 pub struct SubmitTransactionRequest {
     xdr: String,
+    idempotency_key: Option<String>,
 }
 
 /// Request an account's transaction.
@@ -99,29 +106,55 @@ impl Request for AllTransactionsRequest {
     type Response = Page<resources::Transaction>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join("/transactions")?;
+        let mut url = host.join("transactions")?;
         url = url.append_include_failed(&self.include_failed);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(AllTransactionsRequest);
 
-impl StreamRequest for AllTransactionsRequest {
-    type Resource = resources::Transaction;
+impl_page_stream_request!(AllTransactionsRequest, resources::Transaction);
+
+impl SingleTransactionRequest {
+    /// Creates a request to retrieve the transaction with hash `id`.
+    pub fn new<S: Into<String>>(id: S) -> SingleTransactionRequest {
+        SingleTransactionRequest { id: id.into() }
+    }
 }
 
 impl Request for SingleTransactionRequest {
     type Response = resources::Transaction;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        Ok(host.join(&format!("/transactions/{}", self.id))?)
+        Ok(host.join(&format!("transactions/{}", self.id))?)
+    }
+}
+
+impl SubmitTransactionRequest {
+    /// Sends `key` in the `Idempotency-Key` header.
+    ///
+    /// Horizon ignores this header, it already returns the result of the
+    /// previous submission when the same transaction is submitted twice,
+    /// because transactions are identified by their hash and consume a
+    /// sequence number. The header is for gateways and proxies in front
+    /// of Horizon that implement their own idempotency.
+    pub fn with_idempotency_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
     }
 }
 
 impl Request for SubmitTransactionRequest {
     type Response = resources::Transaction;
 
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        match &self.idempotency_key {
+            Some(key) => vec![("Idempotency-Key", key.clone())],
+            None => Vec::new(),
+        }
+    }
+
     fn post_body(&self) -> Result<Option<String>> {
         let body = form_urlencoded::Serializer::new(String::new())
             .append_pair("tx", &self.xdr)
@@ -130,7 +163,7 @@ impl Request for SubmitTransactionRequest {
     }
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        Ok(host.join("/transactions")?)
+        Ok(host.join("transactions")?)
     }
 }
 
@@ -142,17 +175,15 @@ impl Request for TransactionsForAccountRequest {
     type Response = Page<resources::Transaction>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/accounts/{}/transactions", self.account_id))?;
+        let mut url = host.join(&format!("accounts/{}/transactions", self.account_id))?;
         url = url.append_include_failed(&self.include_failed);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(TransactionsForAccountRequest);
 
-impl StreamRequest for TransactionsForAccountRequest {
-    type Resource = resources::Transaction;
-}
+impl_page_stream_request!(TransactionsForAccountRequest, resources::Transaction);
 
 impl TransactionsForLedgerRequest {
     impl_include_failed!();
@@ -162,22 +193,21 @@ impl Request for TransactionsForLedgerRequest {
     type Response = Page<resources::Transaction>;
 
     fn uri(&self, host: &Url) -> Result<Url> {
-        let mut url = host.join(&format!("/ledgers/{}/transactions", self.ledger))?;
+        let mut url = host.join(&format!("ledgers/{}/transactions", self.ledger))?;
         url = url.append_include_failed(&self.include_failed);
-        Ok(url.append_pagination_params(self))
+        url.append_pagination_params(self)
     }
 }
 
 impl_page_request!(TransactionsForLedgerRequest);
 
-impl StreamRequest for TransactionsForLedgerRequest {
-    type Resource = resources::Transaction;
-}
+impl_page_stream_request!(TransactionsForLedgerRequest, resources::Transaction);
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::request::Request;
+    use std::collections::HashMap;
     use stellar_base::crypto::PublicKey;
     use url::Url;
 
@@ -192,6 +222,57 @@ mod tests {
         assert!(uri
             .to_string()
             .starts_with("https://horizon.stellar.org/transactions?"));
+        let query: HashMap<_, _> = uri.query_pairs().into_owned().collect();
+        assert_eq!(Some(&"true".to_string()), query.get("include_failed"));
+    }
+
+    #[test]
+    fn test_submit_transaction_request_headers() {
+        let req = SubmitTransactionRequest {
+            xdr: "AAAA".to_string(),
+            idempotency_key: None,
+        };
+        assert!(req.headers().is_empty());
+        let req = req.with_idempotency_key("key-1");
+        assert_eq!(
+            vec![("Idempotency-Key", "key-1".to_string())],
+            req.headers()
+        );
+        assert_eq!(Some("tx=AAAA".to_string()), req.post_body().unwrap());
+    }
+
+    #[test]
+    fn test_submit_fee_bump_transaction_request() {
+        use stellar_base::amount::Stroops;
+        use stellar_base::crypto::KeyPair;
+        use stellar_base::network::Network;
+        use stellar_base::operations::Operation;
+        use stellar_base::transaction::{FeeBumpTransaction, Transaction, MIN_BASE_FEE};
+
+        let source = KeyPair::random().unwrap();
+        let fee_source = KeyPair::random().unwrap();
+        let mut inner = Transaction::builder(source.public_key().clone(), 1, MIN_BASE_FEE)
+            .add_operation(
+                Operation::new_bump_sequence()
+                    .with_bump_to(2)
+                    .build()
+                    .unwrap(),
+            )
+            .into_transaction()
+            .unwrap();
+        inner.sign(&source, &Network::new_test()).unwrap();
+        let mut tx = FeeBumpTransaction::new(
+            fee_source.public_key().clone().into(),
+            Stroops::new(1000),
+            inner,
+        )
+        .into_envelope();
+        tx.sign(&fee_source, &Network::new_test()).unwrap();
+        let req = submit(&tx).unwrap();
+        let body = req.post_body().unwrap().unwrap();
+        let (key, xdr) = form_urlencoded::parse(body.as_bytes()).next().unwrap();
+        assert_eq!("tx", key);
+        assert_eq!(tx.xdr_base64().unwrap(), xdr);
     }
 
     #[test]
@@ -201,6 +282,10 @@ mod tests {
         assert!(uri
             .to_string()
             .starts_with("https://horizon.stellar.org/transactions/23bf920c4a000b78268589df224c1ba4c883a905687f5a5b3bdba721ee1f481e"));
+        let req = SingleTransactionRequest::new(
+            "23bf920c4a000b78268589df224c1ba4c883a905687f5a5b3bdba721ee1f481e",
+        );
+        assert_eq!(uri, req.uri(&host()).unwrap());
     }
 
     #[test]
@@ -0,0 +1,136 @@
+//! Blocking Horizon client.
+//!
+//! The blocking client wraps `client::HorizonHttpClient` and runs its
+//! requests on an internal runtime, so it can be used without setting
+//! up an async runtime. The runtime connections are driven by a
+//! worker thread, so requests and streams of the same client can be
+//! used from different threads without waiting for each other.
+//!
+//! ```rust,no_run
+//! use stellar_horizon::api;
+//! use stellar_horizon::blocking::HorizonHttpClient;
+//!
+//! # fn run() -> stellar_horizon::error::Result<()> {
+//! let client = HorizonHttpClient::public()?;
+//! let root = client.request(api::root::root())?;
+//! println!("Horizon Version = {}", root.horizon_version);
+//! # Ok(())
+//! # }
+//! ```
+use crate::client::{self, HorizonClient};
+use crate::error::Result;
+use crate::page::Page;
+use crate::paginate::paginate;
+use crate::request::{PageRequest, Request, StreamRequest};
+use crate::resources::PagingToken;
+use futures::stream::{Stream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::convert::TryInto;
+use std::marker::Unpin;
+use std::sync::Arc;
+use tokio::runtime::{Handle, Runtime};
+use url::Url;
+
+/// Blocking client to send requests to Horizon.
+pub struct HorizonHttpClient {
+    inner: client::HorizonHttpClient,
+    runtime: Arc<Runtime>,
+    handle: Handle,
+}
+
+/// An iterator over the resources of a stream request.
+#[must_use = "Iterators are lazy and do nothing unless consumed"]
+pub struct HorizonHttpStream<T> {
+    stream: Box<dyn Stream<Item = Result<T>> + Send + Unpin>,
+    // Keeps the runtime running while the stream is alive.
+    _runtime: Arc<Runtime>,
+    handle: Handle,
+}
+
+impl HorizonHttpClient {
+    /// Creates a new blocking horizon client with the specified host url str.
+    pub fn new_from_str(host: &str) -> Result<HorizonHttpClient> {
+        HorizonHttpClient::from_client(client::HorizonHttpClient::new_from_str(host)?)
+    }
+
+    /// Creates a new blocking horizon client connected to the SDF public network Horizon.
+    pub fn public() -> Result<HorizonHttpClient> {
+        HorizonHttpClient::from_client(client::HorizonHttpClient::public()?)
+    }
+
+    /// Creates a new blocking horizon client connected to the SDF test network Horizon.
+    pub fn testnet() -> Result<HorizonHttpClient> {
+        HorizonHttpClient::from_client(client::HorizonHttpClient::testnet()?)
+    }
+
+    /// Creates a new blocking horizon client with the specified host url.
+    pub fn new<U>(host: U) -> Result<HorizonHttpClient>
+    where
+        U: TryInto<Url>,
+    {
+        HorizonHttpClient::from_client(client::HorizonHttpClient::new(host)?)
+    }
+
+    /// Creates a new blocking horizon client from an async `client`.
+    ///
+    /// Use this to configure the client with the async client builder methods.
+    pub fn from_client(client: client::HorizonHttpClient) -> Result<HorizonHttpClient> {
+        let runtime = tokio::runtime::Builder::new()
+            .threaded_scheduler()
+            .core_threads(1)
+            .enable_all()
+            .build()?;
+        let handle = runtime.handle().clone();
+        Ok(HorizonHttpClient {
+            inner: client,
+            runtime: Arc::new(runtime),
+            handle,
+        })
+    }
+
+    /// Returns the url `req` is sent to, without sending it.
+    pub fn request_url<R: Request>(&self, req: &R) -> Result<Url> {
+        self.inner.request_url(req)
+    }
+
+    /// Send a request `R` to horizon, returns the corresponding response.
+    pub fn request<R: Request>(&self, req: R) -> Result<R::Response> {
+        let (_, response) = self.block_on(self.inner.request(req))?;
+        Ok(response)
+    }
+
+    /// Fetches all the records of `request`, starting from its cursor.
+    pub fn fetch_all<R, T>(&self, request: R) -> Result<Vec<T>>
+    where
+        R: PageRequest<Response = Page<T>> + Clone,
+        T: PagingToken + DeserializeOwned + Serialize + Clone,
+    {
+        self.block_on(paginate(&self.inner, request).try_collect())
+    }
+
+    /// Create a stream request, returns an iterator over the streamed resources.
+    pub fn stream<R: StreamRequest + 'static>(
+        &self,
+        req: R,
+    ) -> Result<HorizonHttpStream<R::Resource>> {
+        let stream = self.inner.stream(req)?;
+        Ok(HorizonHttpStream {
+            stream,
+            _runtime: self.runtime.clone(),
+            handle: self.handle.clone(),
+        })
+    }
+
+    fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        self.handle.block_on(future)
+    }
+}
+
+impl<T> Iterator for HorizonHttpStream<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.handle.block_on(self.stream.next())
+    }
+}
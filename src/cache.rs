@@ -0,0 +1,79 @@
+//! Cache responses for conditional requests.
+use crate::error::Result;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// A response body saved with its `ETag`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedResponse {
+    /// The `ETag` header of the response.
+    pub etag: String,
+    /// The response body.
+    pub body: Vec<u8>,
+}
+
+/// Storage for responses with an `ETag`, by request url.
+///
+/// Used by `HorizonHttpClient::with_response_cache` to send
+/// conditional requests, reusing the cached body when Horizon
+/// responds with `304 Not Modified`.
+pub trait ResponseCache: Send + Sync {
+    /// Returns the response cached for `url`, if any.
+    fn get(&self, url: &str) -> Result<Option<CachedResponse>>;
+
+    /// Saves `response` as the response for `url`.
+    fn put(&self, url: &str, response: CachedResponse) -> Result<()>;
+}
+
+/// A `ResponseCache` that keeps the responses in memory.
+#[derive(Debug, Default)]
+pub struct MemoryResponseCache {
+    responses: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl MemoryResponseCache {
+    /// Creates a new empty cache.
+    pub fn new() -> MemoryResponseCache {
+        MemoryResponseCache::default()
+    }
+}
+
+impl ResponseCache for MemoryResponseCache {
+    fn get(&self, url: &str) -> Result<Option<CachedResponse>> {
+        Ok(self.responses.lock().unwrap().get(url).cloned())
+    }
+
+    fn put(&self, url: &str, response: CachedResponse) -> Result<()> {
+        self.responses
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), response);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_response_cache() {
+        let cache = MemoryResponseCache::new();
+        assert_eq!(None, cache.get("https://horizon.stellar.org/").unwrap());
+        let response = CachedResponse {
+            etag: "\"abc\"".to_string(),
+            body: b"{}".to_vec(),
+        };
+        cache
+            .put("https://horizon.stellar.org/", response.clone())
+            .unwrap();
+        assert_eq!(
+            Some(response),
+            cache.get("https://horizon.stellar.org/").unwrap()
+        );
+        assert_eq!(
+            None,
+            cache.get("https://horizon.stellar.org/ledgers").unwrap()
+        );
+    }
+}
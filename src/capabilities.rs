@@ -0,0 +1,134 @@
+//! Detect the features supported by an Horizon instance.
+use crate::resources::Root;
+
+/// First Horizon version with the `/transactions_async` endpoint.
+const ASYNC_SUBMIT_HORIZON_VERSION: (u32, u32, u32) = (2, 27, 0);
+
+/// The versions and features of an Horizon instance, from its root resource.
+///
+/// Use `HorizonHttpClient::capabilities` to fetch them once and reuse
+/// them, to choose endpoints based on what the instance supports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    root: Root,
+    horizon_version: Option<(u32, u32, u32)>,
+}
+
+impl Capabilities {
+    /// Creates the capabilities of the Horizon instance with `root`.
+    pub fn new(root: Root) -> Capabilities {
+        let horizon_version = parse_version(&root.horizon_version);
+        Capabilities {
+            root,
+            horizon_version,
+        }
+    }
+
+    /// Returns the root resource.
+    pub fn root(&self) -> &Root {
+        &self.root
+    }
+
+    /// Returns the Horizon version, as reported by Horizon.
+    pub fn horizon_version(&self) -> &str {
+        &self.root.horizon_version
+    }
+
+    /// Returns the major, minor, and patch Horizon version, if it can be parsed.
+    pub fn horizon_version_number(&self) -> Option<(u32, u32, u32)> {
+        self.horizon_version
+    }
+
+    /// Returns the Stellar Core version, as reported by Horizon.
+    pub fn core_version(&self) -> &str {
+        &self.root.core_version
+    }
+
+    /// Returns the protocol version of the network.
+    pub fn protocol_version(&self) -> i32 {
+        self.root.current_protocol_version
+    }
+
+    /// Returns the latest protocol version supported by Stellar Core.
+    pub fn core_supported_protocol_version(&self) -> i32 {
+        self.root.core_supported_protocol_version
+    }
+
+    /// Returns the latest ledger ingested by Horizon.
+    pub fn history_latest_ledger(&self) -> i32 {
+        self.root.history_latest_ledger
+    }
+
+    /// Returns the passphrase of the network.
+    pub fn network_passphrase(&self) -> &str {
+        &self.root.network_passphrase
+    }
+
+    /// Returns `true` if the Horizon version is at least `major.minor.patch`.
+    ///
+    /// Returns `false` if the version can't be parsed.
+    pub fn is_horizon_version_at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        self.horizon_version
+            .map(|version| version >= (major, minor, patch))
+            .unwrap_or(false)
+    }
+
+    /// Returns `true` if Horizon supports submitting transactions
+    /// asynchronously with `/transactions_async`.
+    pub fn supports_async_submit(&self) -> bool {
+        let (major, minor, patch) = ASYNC_SUBMIT_HORIZON_VERSION;
+        self.is_horizon_version_at_least(major, minor, patch)
+    }
+}
+
+/// Parses the first `major.minor.patch` version in `version`, such as
+/// `1.6.0-3284133` or `horizon-v2.27.0`.
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let start = version.find(|c: char| c.is_ascii_digit())?;
+    let mut parts = version[start..]
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|part| part.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = parts.next()?.ok()?;
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_version, Capabilities};
+    use crate::resources::Root;
+
+    fn root() -> Root {
+        serde_json::from_str(include_str!("../tests/fixtures/root.json")).unwrap()
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            Some((1, 6, 0)),
+            parse_version("1.6.0-328413370fadb4074b68b9269b2199ed853f5b42")
+        );
+        assert_eq!(Some((2, 27, 0)), parse_version("horizon-v2.27.0"));
+        assert_eq!(None, parse_version("1.6"));
+        assert_eq!(None, parse_version("snapshot"));
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let capabilities = Capabilities::new(root());
+        assert_eq!(Some((1, 6, 0)), capabilities.horizon_version_number());
+        assert_eq!(13, capabilities.protocol_version());
+        assert_eq!(
+            "Public Global Stellar Network ; September 2015",
+            capabilities.network_passphrase()
+        );
+        assert!(capabilities.is_horizon_version_at_least(1, 5, 9));
+        assert!(!capabilities.is_horizon_version_at_least(1, 6, 1));
+        assert!(!capabilities.supports_async_submit());
+
+        let mut root = root();
+        root.horizon_version = "2.27.0".to_string();
+        assert!(Capabilities::new(root).supports_async_submit());
+    }
+}
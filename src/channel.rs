@@ -0,0 +1,156 @@
+//! Forward streams to bounded channels.
+//!
+//! Use `into_channel` to consume a stream from another task, for
+//! example an actor receiving the events of a `HorizonClient::stream`.
+//! The stream is polled by a spawned task, it must be called from
+//! the context of a tokio runtime.
+//!
+//! The channel applies backpressure: when its buffer is full the task
+//! stops polling the stream until the receiver makes room, instead of
+//! dropping events or buffering them without bounds. A slow receiver
+//! delays the events, the ones not read yet wait in the connection.
+//!
+//! ```rust,no_run
+//! use stellar_horizon::api;
+//! use stellar_horizon::channel::into_channel;
+//! use stellar_horizon::client::{HorizonClient, HorizonHttpClient};
+//!
+//! # async fn run() -> stellar_horizon::error::Result<()> {
+//! let client = HorizonHttpClient::new_from_str("https://horizon.stellar.org")?;
+//! let (mut receiver, _task) = into_channel(client.stream(api::ledgers::all())?, 16);
+//! while let Some(ledger) = receiver.recv().await {
+//!     println!("Ledger = {:?}", ledger);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use crate::close::{closeable, CloseHandle};
+use futures::stream::{Stream, StreamExt};
+use std::marker::Unpin;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Spawns a task forwarding the items of `stream` to a channel with `buffer` slots.
+///
+/// Returns the channel receiver and the handle of the task. The task
+/// ends when the stream ends, or when the receiver is dropped or
+/// closed: the stream, and its connection, are dropped right away,
+/// even if the stream is waiting for its next event.
+///
+/// # Panics
+///
+/// Panics if `buffer` is zero.
+pub fn into_channel<S>(stream: S, buffer: usize) -> (StreamReceiver<S::Item>, JoinHandle<()>)
+where
+    S: Stream + Unpin + Send + 'static,
+    S::Item: Send + 'static,
+{
+    let (mut sender, receiver) = mpsc::channel(buffer);
+    let (mut stream, handle) = closeable(stream);
+    let task = tokio::spawn(async move {
+        while let Some(item) = stream.next().await {
+            if sender.send(item).await.is_err() {
+                break;
+            }
+        }
+    });
+    let receiver = StreamReceiver { receiver, handle };
+    (receiver, task)
+}
+
+/// The receiver of a channel created with `into_channel`.
+///
+/// Dropping the receiver closes the stream forwarded to it.
+#[must_use = "Streams are lazy and do nothing unless polled"]
+pub struct StreamReceiver<T> {
+    receiver: mpsc::Receiver<T>,
+    handle: CloseHandle,
+}
+
+impl<T> StreamReceiver<T> {
+    /// Receives the next item, returns `None` when the stream ended.
+    pub async fn recv(&mut self) -> Option<T> {
+        self.receiver.recv().await
+    }
+
+    /// Closes the stream forwarded to the channel.
+    ///
+    /// The items already in the channel can still be received.
+    pub fn close(&mut self) {
+        self.handle.close();
+    }
+
+    /// Returns `true` if the stream was closed.
+    pub fn is_closed(&self) -> bool {
+        self.handle.is_closed()
+    }
+}
+
+impl<T> Stream for StreamReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        self.get_mut().receiver.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for StreamReceiver<T> {
+    fn drop(&mut self) {
+        self.handle.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::into_channel;
+    use futures::stream::{self, StreamExt};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_into_channel() {
+        let (receiver, task) = into_channel(stream::iter(vec![1, 2, 3]), 1);
+        assert_eq!(vec![1, 2, 3], receiver.collect::<Vec<_>>().await);
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_into_channel_backpressure() {
+        let polled = Arc::new(AtomicUsize::new(0));
+        let counter = polled.clone();
+        let items = stream::iter(0..100).inspect(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let (mut receiver, _task) = into_channel(items, 2);
+        tokio::time::delay_for(Duration::from_millis(20)).await;
+        // Two items in the channel, and one waiting to be sent.
+        assert_eq!(3, polled.load(Ordering::SeqCst));
+        assert_eq!(Some(0), receiver.recv().await);
+        tokio::time::delay_for(Duration::from_millis(20)).await;
+        assert_eq!(4, polled.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_dropping_receiver_ends_task() {
+        let (receiver, task) = into_channel(stream::pending::<u32>(), 1);
+        assert!(!receiver.is_closed());
+        drop(receiver);
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_close() {
+        let (mut receiver, task) = into_channel(stream::iter(vec![1]).chain(stream::pending()), 1);
+        assert_eq!(Some(1), receiver.recv().await);
+        receiver.close();
+        assert!(receiver.is_closed());
+        assert_eq!(None, receiver.recv().await);
+        task.await.unwrap();
+    }
+}
@@ -1,10 +1,17 @@
 //! Horizon client traits and types.
+use crate::api;
+use crate::cache::{CachedResponse, ResponseCache};
+use crate::capabilities::Capabilities;
+use crate::cursor::CursorStore;
 use crate::error::{Error, Result};
 use crate::headers::HeaderMap;
 use crate::horizon_error::HorizonError;
-use crate::request::{Request, StreamRequest};
-use futures::future::{BoxFuture, Future};
-use futures::stream::TryStreamExt;
+use crate::json::{self, DeserializationMode};
+use crate::request::{Request, RequestExt, StreamRequest};
+use crate::sse;
+use futures::future::{BoxFuture, Future, FutureExt};
+use futures::io::AsyncBufRead;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
 use futures::Stream;
 use hyper::client::ResponseFuture;
 use hyper::Client;
@@ -12,10 +19,30 @@ use hyper_tls::HttpsConnector;
 use std::convert::TryInto;
 use std::marker::Unpin;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{Delay, Instant};
 use url::Url;
 
+/// Url of the Horizon instance serving the public network.
+const PUBLIC_HORIZON_URL: &str = "https://horizon.stellar.org";
+
+/// Url of the Horizon instance serving the test network.
+const TESTNET_HORIZON_URL: &str = "https://horizon-testnet.stellar.org";
+
+/// Default size of the buffer used to read stream responses.
+const DEFAULT_SSE_READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Default time streams wait before reconnecting, when Horizon sent no `retry`.
+const DEFAULT_STREAM_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum time streams wait before reconnecting.
+const MAX_STREAM_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Default time idle connections are kept in the pool, the `hyper` default.
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
 /// Horizon Client trait. Send HTTP and stream requests to Horizon.
 pub trait HorizonClient {
     /// Send a request `R` to horizon, returns the corresponding response.
@@ -24,27 +51,177 @@ pub trait HorizonClient {
         req: R,
     ) -> BoxFuture<'a, Result<(HeaderMap, R::Response)>>;
     /// Create a stream request.
+    ///
+    /// Dropping the stream closes its connection, use
+    /// `close::closeable` to close it from another task.
+    ///
+    /// Streams only support the ascending order: Horizon closes
+    /// descending streams once it sent the existing resources, instead
+    /// of waiting for new ones. Requests with `Order::Descending` are
+    /// rejected with `Error::InvalidRequest`, request pages instead.
     fn stream<'a, R: StreamRequest + 'static>(
         &'a self,
         req: R,
     ) -> Result<Box<dyn Stream<Item = Result<R::Resource>> + 'static + Send + Unpin>>;
+    /// Create a stream request that resumes from, and saves, the cursor in `store`.
+    ///
+    /// The stream resumes from the cursor in `store` with the
+    /// `Last-Event-Id` header. The id of a resource is saved when the
+    /// next resource is polled, once the consumer processed it, so the
+    /// delivery is at-least-once: a resource being processed when the
+    /// application crashes is delivered again on restart, and so is
+    /// the last resource received before the stream is dropped.
+    ///
+    /// Defaults to `stream_with_meta` with the cursor header, resources
+    /// without an id are not saved.
+    fn stream_durable<'a, R: StreamRequest + 'static, S: CursorStore + Send + 'static>(
+        &'a self,
+        req: R,
+        store: S,
+    ) -> Result<Box<dyn Stream<Item = Result<R::Resource>> + 'static + Send + Unpin>> {
+        let stream = match store.load()? {
+            Some(cursor) => self.stream_with_meta(req.with_header("Last-Event-Id", cursor))?,
+            None => self.stream_with_meta(req)?,
+        };
+        Ok(Box::new(durable(stream, store)))
+    }
+    /// Create a stream request that also returns keep-alive events.
+    ///
+    /// Keep-alive events let consumers know the connection is still
+    /// live when no resources are being streamed. Defaults to the
+    /// resources of `stream`, without keep-alive events.
+    fn stream_events<R: StreamRequest + 'static>(
+        &self,
+        req: R,
+    ) -> Result<BoxEventStream<R::Resource>> {
+        Ok(Box::new(self.stream(req)?.map_ok(StreamEvent::Resource)))
+    }
+    /// Create a stream request that returns the resources with the id of
+    /// their event.
+    ///
+    /// Save the id after processing a resource, and pass it as the
+    /// request cursor on restart, to resume after the last resource
+    /// processed: a crash while processing delivers the resource again.
+    /// Defaults to the resources of `stream`, without metadata.
+    fn stream_with_meta<R: StreamRequest + 'static>(
+        &self,
+        req: R,
+    ) -> Result<BoxMetaStream<R::Resource>> {
+        let stream = self.stream(req)?.map_ok(|resource| StreamItem {
+            id: None,
+            retry: None,
+            resource,
+        });
+        Ok(Box::new(stream))
+    }
+    /// Send `reqs` with at most `concurrency` requests in flight.
+    ///
+    /// Responses are returned as they complete, with the index of their
+    /// request in `reqs`. A `concurrency` of 0 is treated as 1.
+    fn request_many<'a, R>(
+        &'a self,
+        reqs: Vec<R>,
+        concurrency: usize,
+    ) -> BoxStream<'a, (usize, Result<R::Response>)>
+    where
+        Self: Sync,
+        R: Request + Send + 'a,
+    {
+        stream::iter(reqs.into_iter().enumerate())
+            .map(move |(index, req)| {
+                self.request(req)
+                    .map(move |result| (index, result.map(|(_, response)| response)))
+            })
+            .buffer_unordered(concurrency.max(1))
+            .boxed()
+    }
+}
+
+/// A boxed stream of `StreamEvent`s, returned by `HorizonClient::stream_events`.
+pub type BoxEventStream<T> =
+    Box<dyn Stream<Item = Result<StreamEvent<T>>> + 'static + Send + Unpin>;
+
+/// A boxed stream of `StreamItem`s, returned by `HorizonClient::stream_with_meta`.
+pub type BoxMetaStream<T> = Box<dyn Stream<Item = Result<StreamItem<T>>> + 'static + Send + Unpin>;
+
+/// What a stream does when an event can't be decoded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StreamErrorPolicy {
+    /// Return the error.
+    FailFast,
+    /// Skip events that can't be deserialized and continue.
+    ///
+    /// Errors decoding the event stream itself leave the connection in
+    /// an unknown state, so they reconnect instead.
+    Skip,
+    /// Reconnect, resuming after the last event received.
+    Reconnect,
+}
+
+/// An event received on a stream.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent<T> {
+    /// A resource sent by horizon.
+    Resource(T),
+    /// A keep-alive event, such as the `hello` event sent when the stream opens.
+    KeepAlive,
+}
+
+/// A resource received on a stream, with the metadata of its event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamItem<T> {
+    /// The id of the event, Horizon uses the resource paging token.
+    pub id: Option<String>,
+    /// The reconnection time last requested by Horizon, if any.
+    ///
+    /// Streams wait for it before reconnecting, instead of the reconnect
+    /// delay of the client. It's only informational for consumers.
+    pub retry: Option<Duration>,
+    /// The resource.
+    pub resource: T,
+}
+
+impl<T> StreamEvent<T> {
+    /// Returns the resource, if this event is a resource.
+    pub fn into_resource(self) -> Option<T> {
+        match self {
+            StreamEvent::Resource(resource) => Some(resource),
+            StreamEvent::KeepAlive => None,
+        }
+    }
 }
 
 type HttpClient = Client<HttpsConnector<hyper::client::HttpConnector>>;
 
+/// Callback invoked with the uri, status code, and elapsed time of each response.
+type ResponseHook = Arc<dyn Fn(&hyper::Uri, hyper::StatusCode, Duration) + Send + Sync>;
+
 /// Type that implements `HorizonClient` using `hyper` for http.
 pub struct HorizonHttpClient {
     inner: Arc<HorizonHttpClientInner>,
+    sse_read_buffer_size: usize,
+    stream_idle_timeout: Option<Duration>,
+    stream_connect_timeout: Option<Duration>,
+    stream_reconnect_delay: Duration,
+    stream_error_policy: StreamErrorPolicy,
+    deserialization_mode: DeserializationMode,
+    on_response: Option<ResponseHook>,
+    response_cache: Option<Arc<dyn ResponseCache>>,
+    capabilities: Mutex<Option<Capabilities>>,
 }
 
+#[derive(Clone)]
 struct HorizonHttpClientInner {
     inner: HttpClient,
     host: Url,
     client_name: String,
     client_version: String,
+    send_client_headers: bool,
+    pool_max_idle_per_host: usize,
+    pool_idle_timeout: Option<Duration>,
 }
 
-type BoxDecoder = Box<dyn Unpin + Send + Stream<Item = http_types::Result<async_sse::Event>>>;
+type BoxBody = Box<dyn AsyncBufRead + Unpin + Send>;
 
 /// A `Stream` that represents a horizon stream connection.
 #[must_use = "Streams are lazy and do nothing unless polled"]
@@ -56,14 +233,35 @@ where
     last_id: Option<String>,
     request: R,
     response: Option<ResponseFuture>,
-    decoder: Option<BoxDecoder>,
+    body: Option<BoxBody>,
+    decoder: sse::Decoder,
+    sse_read_buffer_size: usize,
+    error_policy: StreamErrorPolicy,
+    deserialization_mode: DeserializationMode,
+    retry: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    idle_delay: Option<Delay>,
+    connect_timeout: Option<Duration>,
+    connect_delay: Option<Delay>,
+    waiting_first_event: bool,
+    reconnect_delay: Duration,
+    reconnect_wait: Option<Delay>,
+    reconnect_attempts: u32,
+    connected_before: bool,
+    last_activity: Arc<Mutex<Instant>>,
+    on_response: Option<ResponseHook>,
+    connect_uri: hyper::Uri,
+    connect_started: Instant,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
 }
 
 impl HorizonHttpClientInner {
     pub fn new(host: Url) -> Result<HorizonHttpClientInner> {
-        let https = HttpsConnector::new();
-        let inner = Client::builder().build::<_, hyper::Body>(https);
-        let host = host.try_into().map_err(|_| Error::InvalidHost)?;
+        let pool_max_idle_per_host = usize::MAX;
+        let pool_idle_timeout = Some(DEFAULT_POOL_IDLE_TIMEOUT);
+        let inner = build_http_client(pool_max_idle_per_host, pool_idle_timeout);
+        let host = normalize_host(host)?;
         let client_name = "aurora-rs/stellar-horizon-rs".to_string();
         let client_version = crate::VERSION.to_string();
         Ok(HorizonHttpClientInner {
@@ -71,12 +269,23 @@ impl HorizonHttpClientInner {
             host,
             client_name,
             client_version,
+            send_client_headers: true,
+            pool_max_idle_per_host,
+            pool_idle_timeout,
         })
     }
 
+    /// Rebuilds the http client with the current pool settings.
+    fn rebuild_http_client(&mut self) {
+        self.inner = build_http_client(self.pool_max_idle_per_host, self.pool_idle_timeout);
+    }
+
     pub fn request_builder(&self, uri: Url) -> http::request::Builder {
-        hyper::Request::builder()
-            .uri(uri.to_string())
+        let builder = hyper::Request::builder().uri(uri.to_string());
+        if !self.send_client_headers {
+            return builder;
+        }
+        builder
             .header("X-Client-Name", self.client_name.to_string())
             .header("X-Client-Version", self.client_version.to_string())
     }
@@ -90,6 +299,14 @@ impl HorizonHttpClientInner {
     }
 }
 
+fn build_http_client(max_idle_per_host: usize, idle_timeout: Option<Duration>) -> HttpClient {
+    let https = HttpsConnector::new();
+    Client::builder()
+        .pool_max_idle_per_host(max_idle_per_host)
+        .pool_idle_timeout(idle_timeout)
+        .build::<_, hyper::Body>(https)
+}
+
 impl HorizonHttpClient {
     /// Creates a new horizon client with the specified host url str.
     pub fn new_from_str(host: &str) -> Result<HorizonHttpClient> {
@@ -97,6 +314,16 @@ impl HorizonHttpClient {
         HorizonHttpClient::new(host)
     }
 
+    /// Creates a new horizon client connected to the SDF public network Horizon.
+    pub fn public() -> Result<HorizonHttpClient> {
+        HorizonHttpClient::new_from_str(PUBLIC_HORIZON_URL)
+    }
+
+    /// Creates a new horizon client connected to the SDF test network Horizon.
+    pub fn testnet() -> Result<HorizonHttpClient> {
+        HorizonHttpClient::new_from_str(TESTNET_HORIZON_URL)
+    }
+
     /// Creates a new horizon client with the specified host url.
     pub fn new<U>(host: U) -> Result<HorizonHttpClient>
     where
@@ -104,7 +331,246 @@ impl HorizonHttpClient {
     {
         let host = host.try_into().map_err(|_| Error::InvalidHost)?;
         let inner = Arc::new(HorizonHttpClientInner::new(host)?);
-        Ok(HorizonHttpClient { inner })
+        Ok(HorizonHttpClient {
+            inner,
+            sse_read_buffer_size: DEFAULT_SSE_READ_BUFFER_SIZE,
+            stream_idle_timeout: None,
+            stream_connect_timeout: None,
+            stream_reconnect_delay: DEFAULT_STREAM_RECONNECT_DELAY,
+            stream_error_policy: StreamErrorPolicy::FailFast,
+            deserialization_mode: DeserializationMode::default(),
+            on_response: None,
+            response_cache: None,
+            capabilities: Mutex::new(None),
+        })
+    }
+
+    /// Sets the size of the buffer used to read stream responses.
+    ///
+    /// A larger buffer reduces the number of reads for streams with a
+    /// high rate of events. Defaults to 64 KiB.
+    pub fn with_sse_read_buffer_size(mut self, size: usize) -> Self {
+        self.sse_read_buffer_size = size;
+        self
+    }
+
+    /// Returns the size of the buffer used to read stream responses.
+    pub fn sse_read_buffer_size(&self) -> usize {
+        self.sse_read_buffer_size
+    }
+
+    /// Reconnects streams that receive no data for longer than `timeout`.
+    ///
+    /// Streams resume from the last event received. Horizon sends
+    /// keep-alive messages on idle streams, so `timeout` should be longer
+    /// than the keep-alive interval.
+    pub fn with_stream_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the idle timeout of streams, if any.
+    pub fn stream_idle_timeout(&self) -> Option<Duration> {
+        self.stream_idle_timeout
+    }
+
+    /// Reconnects streams that receive no event within `timeout` of connecting.
+    ///
+    /// This covers servers accepting the connection but stalling before
+    /// the response or the first event, such as the `hello` event sent
+    /// by Horizon. Unlike the idle timeout, data that doesn't complete
+    /// an event doesn't reset it. Defaults to no timeout.
+    pub fn with_stream_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the connect timeout of streams, if any.
+    pub fn stream_connect_timeout(&self) -> Option<Duration> {
+        self.stream_connect_timeout
+    }
+
+    /// Sets the time streams wait before reconnecting, when Horizon
+    /// didn't send a `retry` time.
+    ///
+    /// The time doubles after each reconnection that receives no event,
+    /// up to one minute, so that streams don't reconnect in a tight
+    /// loop while Horizon is down. Defaults to one second.
+    pub fn with_stream_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.stream_reconnect_delay = delay;
+        self
+    }
+
+    /// Returns the time streams wait before reconnecting.
+    pub fn stream_reconnect_delay(&self) -> Duration {
+        self.stream_reconnect_delay
+    }
+
+    /// Sets what streams do when an event can't be decoded.
+    ///
+    /// Defaults to `StreamErrorPolicy::FailFast`.
+    pub fn with_stream_error_policy(mut self, policy: StreamErrorPolicy) -> Self {
+        self.stream_error_policy = policy;
+        self
+    }
+
+    /// Returns what streams do when an event can't be decoded.
+    pub fn stream_error_policy(&self) -> StreamErrorPolicy {
+        self.stream_error_policy
+    }
+
+    /// Sets how responses with fields unknown to this crate are deserialized.
+    ///
+    /// Defaults to `DeserializationMode::Lenient`, use
+    /// `DeserializationMode::Strict` to detect the fields added by new
+    /// Horizon versions.
+    pub fn with_deserialization_mode(mut self, mode: DeserializationMode) -> Self {
+        self.deserialization_mode = mode;
+        self
+    }
+
+    /// Returns how responses with unknown fields are deserialized.
+    pub fn deserialization_mode(&self) -> DeserializationMode {
+        self.deserialization_mode
+    }
+
+    /// Sets whether to send the `X-Client-Name` and `X-Client-Version`
+    /// headers with requests and streams.
+    ///
+    /// Defaults to `true`.
+    pub fn with_send_client_headers(mut self, send_client_headers: bool) -> Self {
+        Arc::make_mut(&mut self.inner).send_client_headers = send_client_headers;
+        self
+    }
+
+    /// Returns whether the client identifying headers are sent.
+    pub fn send_client_headers(&self) -> bool {
+        self.inner.send_client_headers
+    }
+
+    /// Sets the maximum number of idle connections kept open per host.
+    ///
+    /// Defaults to no limit. Setting this recreates the connection pool,
+    /// call it before sending requests.
+    pub fn with_pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.pool_max_idle_per_host = max_idle;
+        inner.rebuild_http_client();
+        self
+    }
+
+    /// Returns the maximum number of idle connections kept open per host.
+    pub fn pool_max_idle_per_host(&self) -> usize {
+        self.inner.pool_max_idle_per_host
+    }
+
+    /// Sets how long idle connections are kept open, `None` keeps them
+    /// open until the server closes them.
+    ///
+    /// Defaults to 90 seconds. Setting this recreates the connection
+    /// pool, call it before sending requests.
+    pub fn with_pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.pool_idle_timeout = timeout;
+        inner.rebuild_http_client();
+        self
+    }
+
+    /// Returns how long idle connections are kept open.
+    pub fn pool_idle_timeout(&self) -> Option<Duration> {
+        self.inner.pool_idle_timeout
+    }
+
+    /// Calls `hook` with the uri, status code, and elapsed time of each response.
+    ///
+    /// For streams, `hook` is called when a connection is established
+    /// and the elapsed time is the time to receive the response headers.
+    pub fn with_on_response<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&hyper::Uri, hyper::StatusCode, Duration) + Send + Sync + 'static,
+    {
+        self.on_response = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sends conditional requests using the responses saved in `cache`.
+    ///
+    /// Responses with an `ETag` header are saved in `cache` by url,
+    /// and requests for the same url send the saved `ETag` in the
+    /// `If-None-Match` header. When Horizon responds with `304 Not
+    /// Modified` the saved body is returned. Only `GET` requests are
+    /// cached, streams are never cached.
+    pub fn with_response_cache<C>(mut self, cache: C) -> Self
+    where
+        C: ResponseCache + 'static,
+    {
+        self.response_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Returns the Horizon base url, its path always ends with `/`.
+    pub fn host(&self) -> &Url {
+        &self.inner.host
+    }
+
+    /// Returns the url `req` is sent to, without sending it.
+    ///
+    /// Stream requests also send the `Last-Event-Id` header when
+    /// resuming, but connect to the same url.
+    pub fn request_url<R: Request>(&self, req: &R) -> Result<Url> {
+        req.uri(&self.inner.host)
+    }
+
+    /// Returns the capabilities of Horizon, fetching them the first time.
+    ///
+    /// The capabilities are cached by the client, use
+    /// `refresh_capabilities` to fetch them again, for example after
+    /// Horizon is upgraded.
+    pub async fn capabilities(&self) -> Result<Capabilities> {
+        if let Some(capabilities) = self.capabilities.lock().unwrap().as_ref() {
+            return Ok(capabilities.clone());
+        }
+        self.refresh_capabilities().await
+    }
+
+    /// Fetches the capabilities of Horizon and caches them.
+    pub async fn refresh_capabilities(&self) -> Result<Capabilities> {
+        let (_, root) = self.request(api::root::root()).await?;
+        let capabilities = Capabilities::new(root);
+        *self.capabilities.lock().unwrap() = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    /// Creates a new stream for `request`.
+    fn new_stream<R: StreamRequest>(&self, request: R) -> Result<HorizonHttpStream<R>> {
+        request.validate_stream()?;
+        Ok(HorizonHttpStream {
+            client: self.inner.clone(),
+            request,
+            last_id: None,
+            response: None,
+            body: None,
+            decoder: sse::Decoder::new(),
+            sse_read_buffer_size: self.sse_read_buffer_size,
+            error_policy: self.stream_error_policy,
+            deserialization_mode: self.deserialization_mode,
+            retry: None,
+            idle_timeout: self.stream_idle_timeout,
+            idle_delay: None,
+            connect_timeout: self.stream_connect_timeout,
+            connect_delay: None,
+            waiting_first_event: false,
+            reconnect_delay: self.stream_reconnect_delay,
+            reconnect_wait: None,
+            reconnect_attempts: 0,
+            connected_before: false,
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            on_response: self.on_response.clone(),
+            connect_uri: hyper::Uri::default(),
+            connect_started: Instant::now(),
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!("horizon_stream"),
+        })
     }
 
     /// Returns a request builder with default headers.
@@ -130,24 +596,161 @@ impl HorizonClient for HorizonHttpClient {
         &'a self,
         request: R,
     ) -> Result<Box<dyn Stream<Item = Result<R::Resource>> + 'static + Send + Unpin>> {
-        Ok(Box::new(HorizonHttpStream {
-            client: self.inner.clone(),
-            request,
-            last_id: None,
-            response: None,
-            decoder: None,
-        }))
+        let stream = self.new_stream(request)?;
+        Ok(Box::new(only_resources(stream)))
+    }
+
+    fn stream_events<R: StreamRequest + 'static>(
+        &self,
+        request: R,
+    ) -> Result<BoxEventStream<R::Resource>> {
+        Ok(Box::new(self.new_stream(request)?))
+    }
+
+    fn stream_with_meta<R: StreamRequest + 'static>(
+        &self,
+        request: R,
+    ) -> Result<BoxMetaStream<R::Resource>> {
+        let stream = self.new_stream(request)?;
+        Ok(Box::new(with_meta(stream)))
+    }
+}
+
+/// Shares a client between tasks.
+///
+/// Spawned futures can't borrow the client, clone the `Arc` into them
+/// instead, for example to keep one `Arc<HorizonHttpClient>` in the
+/// state of an application.
+impl<C> HorizonClient for Arc<C>
+where
+    C: HorizonClient + ?Sized,
+{
+    fn request<'a, R: Request + 'a>(
+        &'a self,
+        req: R,
+    ) -> BoxFuture<'a, Result<(HeaderMap, R::Response)>> {
+        (**self).request(req)
     }
+
+    fn stream<'a, R: StreamRequest + 'static>(
+        &'a self,
+        req: R,
+    ) -> Result<Box<dyn Stream<Item = Result<R::Resource>> + 'static + Send + Unpin>> {
+        (**self).stream(req)
+    }
+
+    fn stream_durable<'a, R: StreamRequest + 'static, S: CursorStore + Send + 'static>(
+        &'a self,
+        req: R,
+        store: S,
+    ) -> Result<Box<dyn Stream<Item = Result<R::Resource>> + 'static + Send + Unpin>> {
+        (**self).stream_durable(req, store)
+    }
+
+    fn stream_events<R: StreamRequest + 'static>(
+        &self,
+        req: R,
+    ) -> Result<BoxEventStream<R::Resource>> {
+        (**self).stream_events(req)
+    }
+
+    fn stream_with_meta<R: StreamRequest + 'static>(
+        &self,
+        req: R,
+    ) -> Result<BoxMetaStream<R::Resource>> {
+        (**self).stream_with_meta(req)
+    }
+}
+
+/// Checks that `host` is an http(s) url without query nor fragment,
+/// and returns it with a path ending in exactly one `/`, so that
+/// joining endpoint paths keeps any path prefix.
+fn normalize_host(mut host: Url) -> Result<Url> {
+    let is_http = host.scheme() == "http" || host.scheme() == "https";
+    if !is_http || host.host().is_none() || host.query().is_some() || host.fragment().is_some() {
+        return Err(Error::InvalidHost);
+    }
+    let path = format!("{}/", host.path().trim_end_matches('/'));
+    host.set_path(&path);
+    Ok(host)
+}
+
+/// Filters out the keep-alive events of `stream`.
+fn only_resources<R: StreamRequest>(
+    stream: HorizonHttpStream<R>,
+) -> impl Stream<Item = Result<R::Resource>> {
+    stream.try_filter_map(|event| futures::future::ready(Ok(event.into_resource())))
+}
+
+/// Saves the id of the resources of `stream` in `store` once they are processed.
+///
+/// The id of a resource is saved when the next resource is polled.
+fn durable<T, S>(
+    mut stream: BoxMetaStream<T>,
+    mut store: S,
+) -> impl Stream<Item = Result<T>> + Send + Unpin
+where
+    T: Send + 'static,
+    S: CursorStore + Send + 'static,
+{
+    let mut processed: Option<String> = None;
+    stream::poll_fn(move |cx| {
+        if let Some(id) = processed.take() {
+            store.save(&id)?;
+        }
+        match Pin::new(&mut stream).poll_next(cx) {
+            Poll::Ready(Some(Ok(item))) => {
+                processed = item.id;
+                Poll::Ready(Some(Ok(item.resource)))
+            }
+            poll => poll.map_ok(|item| item.resource),
+        }
+    })
+}
+
+/// Adds the metadata of their event to the resources of `stream`.
+fn with_meta<R: StreamRequest>(
+    mut stream: HorizonHttpStream<R>,
+) -> impl Stream<Item = Result<StreamItem<R::Resource>>> {
+    stream::poll_fn(move |cx| loop {
+        match Pin::new(&mut stream).poll_next(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(None) => return Poll::Ready(None),
+            Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+            Poll::Ready(Some(Ok(StreamEvent::KeepAlive))) => {}
+            Poll::Ready(Some(Ok(StreamEvent::Resource(resource)))) => {
+                let item = StreamItem {
+                    id: stream.last_id.clone(),
+                    retry: stream.retry,
+                    resource,
+                };
+                return Poll::Ready(Some(Ok(item)));
+            }
+        }
+    })
 }
 
 async fn execute_request<R: Request>(
     client: &HorizonHttpClient,
     req: R,
 ) -> Result<(HeaderMap, R::Response)> {
-    let uri = req.uri(&client.inner.host)?;
-    let request_builder = client.request_builder(uri);
+    req.validate()?;
+    let uri = client.request_url(&req)?;
+    let body = req.post_body()?;
+    let cache = client.response_cache.as_ref().filter(|_| body.is_none());
+    let cached = match cache {
+        Some(cache) => cache.get(uri.as_str())?,
+        None => None,
+    };
+    let cache_url = uri.to_string();
+    let mut request_builder = client.request_builder(uri);
+    request_builder = with_request_headers(request_builder, req.headers());
+    if let Some(cached) = &cached {
+        request_builder =
+            request_builder.header(hyper::header::IF_NONE_MATCH, cached.etag.as_str());
+    }
 
-    let request = if let Some(body) = req.post_body()? {
+    let request = if let Some(body) = body {
         request_builder
             .method(hyper::Method::POST)
             .header(
@@ -161,19 +764,234 @@ async fn execute_request<R: Request>(
             .body(hyper::Body::empty())?
     };
 
-    let response = client.raw_request(request).await?;
-
-    if response.status().is_success() {
-        let headers = response.headers().clone();
-        let bytes = hyper::body::to_bytes(response).await?;
-        let result: R::Response = serde_json::from_slice(&bytes)?;
-        Ok((headers, result))
-    } else if response.status().is_client_error() {
-        let bytes = hyper::body::to_bytes(response).await?;
-        let result: HorizonError = serde_json::from_slice(&bytes)?;
-        Err(Error::HorizonRequestError(result))
-    } else {
-        Err(Error::HorizonServerError)
+    let uri = request.uri().clone();
+    #[cfg(feature = "tracing")]
+    let span = tracing::info_span!(
+        "horizon_request",
+        method = %request.method(),
+        path = uri.path(),
+        status = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+    );
+    #[cfg(feature = "tracing")]
+    let request_span = span.clone();
+    let response = async move {
+        let started = Instant::now();
+        let response = client.raw_request(request).await?;
+
+        let status = response.status();
+        let elapsed = started.elapsed();
+        #[cfg(feature = "tracing")]
+        span.record("status", status.as_u16())
+            .record("elapsed_ms", elapsed.as_millis() as u64);
+        if let Some(hook) = &client.on_response {
+            hook(&uri, status, elapsed);
+        }
+        if status == hyper::StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                let result: R::Response =
+                    json::from_slice(&cached.body, client.deserialization_mode)
+                        .map_err(|err| Error::deserialization(err, &cached.body))?;
+                return Ok((response.headers().clone(), result));
+            }
+        }
+        if status.is_success() {
+            let headers = response.headers().clone();
+            let bytes = hyper::body::to_bytes(response).await?;
+            let result: R::Response = json::from_slice(&bytes, client.deserialization_mode)
+                .map_err(|err| Error::deserialization(err, &bytes))?;
+            let etag = headers
+                .get(hyper::header::ETAG)
+                .and_then(|value| value.to_str().ok());
+            if let (Some(cache), Some(etag)) = (cache, etag) {
+                let response = CachedResponse {
+                    etag: etag.to_string(),
+                    body: bytes.to_vec(),
+                };
+                cache.put(&cache_url, response)?;
+            }
+            Ok((headers, result))
+        } else {
+            match status_error(status, response.headers()) {
+                Some(Error::Timeout { request_id, .. }) => {
+                    let bytes = hyper::body::to_bytes(response).await?;
+                    let error = serde_json::from_slice::<HorizonError>(&bytes)
+                        .ok()
+                        .map(|error| {
+                            Box::new(HorizonError {
+                                request_id: request_id.clone(),
+                                ..error
+                            })
+                        });
+                    Err(Error::Timeout { request_id, error })
+                }
+                Some(err) => Err(err),
+                None => {
+                    let request_id = crate::headers::request_id(response.headers());
+                    let bytes = hyper::body::to_bytes(response).await?;
+                    match serde_json::from_slice::<HorizonError>(&bytes) {
+                        Ok(result) => Err(Error::HorizonRequestError(HorizonError {
+                            request_id,
+                            ..result
+                        })),
+                        Err(_) => Err(Error::UnexpectedStatus {
+                            status: status.as_u16(),
+                            request_id,
+                        }),
+                    }
+                }
+            }
+        }
+    };
+    #[cfg(feature = "tracing")]
+    let response = tracing::Instrument::instrument(response, request_span);
+    response.await
+}
+
+/// Adds the request `headers`, replacing the client headers with the same name.
+fn with_request_headers(
+    mut builder: http::request::Builder,
+    headers: Vec<(&'static str, String)>,
+) -> http::request::Builder {
+    for (name, value) in headers {
+        let name_and_value = (
+            http::header::HeaderName::from_bytes(name.as_bytes()),
+            http::header::HeaderValue::from_str(&value),
+        );
+        match (builder.headers_mut(), name_and_value) {
+            (Some(headers), (Ok(name), Ok(value))) => {
+                headers.insert(name, value);
+            }
+            // Let the builder report the invalid header.
+            _ => builder = builder.header(name, value),
+        }
+    }
+    builder
+}
+
+/// Returns the error for responses with a status that is not a
+/// successful status nor a Horizon request error.
+fn status_error(status: hyper::StatusCode, headers: &HeaderMap) -> Option<Error> {
+    match status {
+        hyper::StatusCode::TOO_MANY_REQUESTS => Some(Error::RateLimited {
+            reset: crate::headers::rate_limit_reset(headers).map(u64::from),
+            retry_after: crate::headers::retry_after(headers),
+            request_id: crate::headers::request_id(headers),
+        }),
+        hyper::StatusCode::GATEWAY_TIMEOUT => Some(Error::Timeout {
+            request_id: crate::headers::request_id(headers),
+            error: None,
+        }),
+        status if status.is_client_error() => None,
+        status => Some(unexpected_status(status, headers)),
+    }
+}
+
+fn unexpected_status(status: hyper::StatusCode, headers: &HeaderMap) -> Error {
+    Error::UnexpectedStatus {
+        status: status.as_u16(),
+        request_id: crate::headers::request_id(headers),
+    }
+}
+
+/// Returns `true` if the response content type is `text/event-stream`.
+fn is_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|media_type| media_type.trim().eq_ignore_ascii_case("text/event-stream"))
+        .unwrap_or(false)
+}
+
+impl<R> HorizonHttpStream<R>
+where
+    R: StreamRequest,
+{
+    /// Records that data was received from horizon.
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Returns `true` if no data was received for longer than the idle timeout.
+    ///
+    /// Registers a wakeup for when the timeout expires otherwise.
+    fn poll_idle(&mut self, cx: &mut Context) -> bool {
+        let timeout = match self.idle_timeout {
+            Some(timeout) => timeout,
+            None => return false,
+        };
+        let deadline = *self.last_activity.lock().unwrap() + timeout;
+        let delay = self
+            .idle_delay
+            .get_or_insert_with(|| tokio::time::delay_until(deadline));
+        if delay.deadline() != deadline {
+            delay.reset(deadline);
+        }
+        Pin::new(delay).poll(cx).is_ready()
+    }
+
+    /// Returns `true` if no event was received within the connect timeout.
+    ///
+    /// Registers a wakeup for when the timeout expires otherwise.
+    fn poll_connect_timeout(&mut self, cx: &mut Context) -> bool {
+        let timeout = match self.connect_timeout {
+            Some(timeout) if self.waiting_first_event => timeout,
+            _ => return false,
+        };
+        let deadline = self.connect_started + timeout;
+        let delay = self
+            .connect_delay
+            .get_or_insert_with(|| tokio::time::delay_until(deadline));
+        if delay.deadline() != deadline {
+            delay.reset(deadline);
+        }
+        Pin::new(delay).poll(cx).is_ready()
+    }
+
+    /// Returns `true` while waiting to reconnect.
+    ///
+    /// The first connection is immediate, the following ones wait the
+    /// `retry` time of Horizon, or the reconnect delay, doubled after
+    /// each reconnection that received no event.
+    fn poll_reconnect_wait(&mut self, cx: &mut Context) -> bool {
+        if !self.connected_before {
+            self.connected_before = true;
+            return false;
+        }
+        let wait = match self.reconnect_wait.as_mut() {
+            Some(wait) => wait,
+            None => {
+                let delay = self.retry.unwrap_or(self.reconnect_delay);
+                let factor = 1u32
+                    .checked_shl(self.reconnect_attempts)
+                    .unwrap_or(u32::MAX);
+                let delay = delay
+                    .checked_mul(factor)
+                    .unwrap_or(MAX_STREAM_RECONNECT_DELAY)
+                    .min(MAX_STREAM_RECONNECT_DELAY);
+                self.reconnect_attempts = self.reconnect_attempts.saturating_add(1);
+                self.reconnect_wait
+                    .get_or_insert(tokio::time::delay_for(delay))
+            }
+        };
+        if Pin::new(wait).poll(cx).is_pending() {
+            return true;
+        }
+        self.reconnect_wait = None;
+        false
+    }
+
+    /// Drops the current connection, the next poll reconnects from the last event.
+    fn reset_connection(&mut self) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(parent: &self.span, "resetting connection");
+        self.response = None;
+        self.body = None;
+        self.decoder.reset();
+        self.idle_delay = None;
+        self.connect_delay = None;
+        self.waiting_first_event = false;
     }
 }
 
@@ -181,72 +999,154 @@ impl<R> Stream for HorizonHttpStream<R>
 where
     R: StreamRequest,
 {
-    type Item = Result<R::Resource>;
+    type Item = Result<StreamEvent<R::Resource>>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
         loop {
-            if self.response.is_none() && self.decoder.is_none() {
+            if self.response.is_none() && self.body.is_none() {
+                if self.poll_reconnect_wait(cx) {
+                    return Poll::Pending;
+                }
                 let uri = self.request.uri(&self.client.host)?;
+                let request_builder = self.client.get(uri).header("Accept", "text/event-stream");
                 let mut request_builder =
-                    self.client.get(uri).header("Accept", "text/event-stream");
+                    with_request_headers(request_builder, self.request.headers());
+                // Resume after the last event received, instead of the
+                // `Last-Event-Id` the request may start from.
                 if let Some(last_id) = &self.last_id {
-                    request_builder = request_builder.header("Last-Event-Id", last_id.clone());
+                    let header = vec![("Last-Event-Id", last_id.clone())];
+                    request_builder = with_request_headers(request_builder, header);
                 }
 
                 let request = request_builder.body(hyper::Body::empty())?;
+                #[cfg(feature = "tracing")]
+                tracing::info!(
+                    parent: &self.span,
+                    path = request.uri().path(),
+                    last_event_id = ?self.last_id,
+                    "connecting"
+                );
+                self.connect_uri = request.uri().clone();
+                self.connect_started = Instant::now();
+                self.waiting_first_event = true;
                 let response = self.client.raw_request(request);
                 self.response = Some(response);
+                self.touch();
             }
 
             if let Some(mut resp) = self.response.take() {
                 match Pin::new(&mut resp).poll(cx) {
                     Poll::Pending => {
                         self.response = Some(resp);
+                        if self.poll_idle(cx) || self.poll_connect_timeout(cx) {
+                            self.reset_connection();
+                            continue;
+                        }
                         return Poll::Pending;
                     }
                     Poll::Ready(Err(e)) => {
                         return Poll::Ready(Some(Err(e.into())));
                     }
                     Poll::Ready(Ok(resp)) => {
-                        // TODO(fra): handle non success statuses
-                        assert!(resp.status().is_success());
+                        let elapsed = self.connect_started.elapsed();
+                        #[cfg(feature = "tracing")]
+                        tracing::info!(
+                            parent: &self.span,
+                            status = resp.status().as_u16(),
+                            elapsed_ms = elapsed.as_millis() as u64,
+                            "connected"
+                        );
+                        if let Some(hook) = &self.on_response {
+                            hook(&self.connect_uri, resp.status(), elapsed);
+                        }
+                        if !resp.status().is_success() {
+                            let status = resp.status();
+                            let err = status_error(status, resp.headers())
+                                .unwrap_or_else(|| unexpected_status(status, resp.headers()));
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                        if !is_event_stream(resp.headers()) {
+                            let content_type = resp
+                                .headers()
+                                .get(hyper::header::CONTENT_TYPE)
+                                .and_then(|value| value.to_str().ok())
+                                .map(|value| value.to_string());
+                            return Poll::Ready(Some(Err(Error::NotAnEventStream {
+                                content_type,
+                            })));
+                        }
+                        let last_activity = self.last_activity.clone();
                         let body_stream = resp
                             .into_body()
+                            .inspect_ok(move |_| *last_activity.lock().unwrap() = Instant::now())
                             .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
                             .into_async_read();
-
-                        let decoder = Box::new(async_sse::decode(body_stream));
-                        self.decoder = Some(decoder);
+                        let body_stream = futures::io::BufReader::with_capacity(
+                            self.sse_read_buffer_size,
+                            body_stream,
+                        );
+                        self.body = Some(Box::new(body_stream));
                     }
                 }
             }
 
-            if let Some(mut decoder) = self.decoder.take() {
-                match Pin::new(&mut decoder).poll_next(cx) {
+            if let Some(mut body) = self.body.take() {
+                let this = &mut *self;
+                match this.decoder.poll_event(Pin::new(&mut body), cx) {
                     Poll::Pending => {
-                        self.decoder = Some(decoder);
+                        this.body = Some(body);
+                        if this.poll_idle(cx) || this.poll_connect_timeout(cx) {
+                            this.reset_connection();
+                            continue;
+                        }
                         return Poll::Pending;
                     }
-                    Poll::Ready(None) => {}
-                    Poll::Ready(Some(Err(_))) => {
-                        let err = Error::SSEDecoderError;
+                    Poll::Ready(None) => {
+                        this.decoder.reset();
+                    }
+                    Poll::Ready(Some(Err(err))) => {
+                        if this.error_policy != StreamErrorPolicy::FailFast {
+                            this.reset_connection();
+                            continue;
+                        }
+                        this.decoder.reset();
+                        let err = Error::SSEDecoderError(err.to_string());
                         return Poll::Ready(Some(Err(err)));
                     }
-                    Poll::Ready(Some(Ok(message))) => {
-                        self.decoder = Some(decoder);
-                        match message {
-                            async_sse::Event::Message(msg) => {
-                                if let Some(last_id) = msg.id() {
-                                    self.last_id = Some(last_id.to_string());
+                    Poll::Ready(Some(Ok(event))) => {
+                        this.body = Some(body);
+                        this.waiting_first_event = false;
+                        this.reconnect_attempts = 0;
+                        match event {
+                            sse::Event::Message { id, name, data } => {
+                                if let Some(id) = id {
+                                    let last_id = this.last_id.get_or_insert_with(String::new);
+                                    last_id.clear();
+                                    last_id.push_str(id);
                                 }
-                                if msg.name() == "message" {
+                                if name == "message" {
                                     let result: R::Resource =
-                                        serde_json::from_slice(&msg.into_bytes())?;
-                                    return Poll::Ready(Some(Ok(result)));
+                                        match json::from_slice(data, this.deserialization_mode) {
+                                            Ok(result) => result,
+                                            Err(err) => match this.error_policy {
+                                                StreamErrorPolicy::FailFast => {
+                                                    let err = Error::deserialization(err, data);
+                                                    return Poll::Ready(Some(Err(err)));
+                                                }
+                                                StreamErrorPolicy::Skip => continue,
+                                                StreamErrorPolicy::Reconnect => {
+                                                    this.reset_connection();
+                                                    continue;
+                                                }
+                                            },
+                                        };
+                                    return Poll::Ready(Some(Ok(StreamEvent::Resource(result))));
+                                } else {
+                                    return Poll::Ready(Some(Ok(StreamEvent::KeepAlive)));
                                 }
                             }
-                            async_sse::Event::Retry(duration) => {
-                                println!("got duration {:?}", duration);
+                            sse::Event::Retry(duration) => {
+                                this.retry = Some(duration);
                             }
                         }
                     }
@@ -255,3 +1155,151 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_host, HorizonClient, HorizonHttpClient};
+    use crate::api;
+    use crate::error::Error;
+    use crate::request::{Order, PageRequest, RequestExt};
+    use crate::testing::MockClient;
+    use futures::stream::StreamExt;
+    use std::time::Duration;
+    use stellar_base::asset::Asset;
+    use url::Url;
+
+    fn normalized(host: &str) -> String {
+        normalize_host(host.parse().unwrap()).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_normalize_host_trailing_slashes() {
+        assert_eq!(
+            "https://horizon.stellar.org/",
+            normalized("https://horizon.stellar.org")
+        );
+        assert_eq!(
+            "https://horizon.stellar.org/",
+            normalized("https://horizon.stellar.org/")
+        );
+        assert_eq!(
+            "http://127.0.0.1:8000/",
+            normalized("http://127.0.0.1:8000")
+        );
+        assert_eq!(
+            "https://example.org/horizon/",
+            normalized("https://example.org/horizon")
+        );
+        assert_eq!(
+            "https://example.org/horizon/",
+            normalized("https://example.org/horizon//")
+        );
+    }
+
+    #[test]
+    fn test_normalize_host_rejects_invalid_hosts() {
+        let invalid = [
+            "ftp://horizon.stellar.org",
+            "localhost:8000",
+            "https://horizon.stellar.org/?network=public",
+            "https://horizon.stellar.org/#fragment",
+            "https://horizon.stellar.org/?",
+            "unix:/var/run/horizon.sock",
+        ];
+        for host in invalid.iter() {
+            let url: Url = host.parse().unwrap();
+            assert!(
+                matches!(normalize_host(url), Err(Error::InvalidHost)),
+                "{} should be invalid",
+                host
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_from_str_invalid_host() {
+        for host in ["horizon.stellar.org", "", "https://"].iter() {
+            assert!(matches!(
+                HorizonHttpClient::new_from_str(host),
+                Err(Error::InvalidHost)
+            ));
+        }
+        assert!(matches!(
+            HorizonHttpClient::new_from_str("https://horizon.stellar.org?a=b"),
+            Err(Error::InvalidHost)
+        ));
+    }
+
+    #[test]
+    fn test_request_url() {
+        let client = HorizonHttpClient::new_from_str("https://example.org/horizon").unwrap();
+        assert_eq!("https://example.org/horizon/", client.host().as_str());
+        let url = client
+            .request_url(&api::ledgers::all().with_limit(5))
+            .unwrap();
+        assert_eq!("https://example.org/horizon/ledgers?limit=5", url.as_str());
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_validated_before_sending() {
+        let client = HorizonHttpClient::new_from_str("http://127.0.0.1:1").unwrap();
+        let req = api::aggregations::order_book(Asset::new_native(), Asset::new_native());
+        assert!(matches!(
+            client.request(req.clone()).await,
+            Err(Error::InvalidAssetPair)
+        ));
+        assert!(matches!(client.stream(req), Err(Error::InvalidAssetPair)));
+    }
+
+    #[test]
+    fn test_descending_streams_are_rejected() {
+        let client = HorizonHttpClient::new_from_str("http://127.0.0.1:1").unwrap();
+        let req = api::ledgers::all().with_order(&Order::Descending);
+        match client.stream(req) {
+            Err(Error::InvalidRequest { field, .. }) => assert_eq!("order", field),
+            _ => panic!("expected invalid request error"),
+        }
+        let req = api::ledgers::all()
+            .with_order_desc()
+            .with_header("X-Test", "1");
+        assert!(matches!(
+            client.stream_events(req),
+            Err(Error::InvalidRequest { field: "order", .. })
+        ));
+        let req = api::ledgers::all().with_order(&Order::Ascending);
+        assert!(client.stream_events(req).is_ok());
+    }
+
+    #[test]
+    fn test_pool_settings() {
+        let client = HorizonHttpClient::new_from_str("https://example.org").unwrap();
+        assert_eq!(usize::MAX, client.pool_max_idle_per_host());
+        assert_eq!(Some(Duration::from_secs(90)), client.pool_idle_timeout());
+        let client = client
+            .with_pool_max_idle_per_host(4)
+            .with_pool_idle_timeout(None);
+        assert_eq!(4, client.pool_max_idle_per_host());
+        assert_eq!(None, client.pool_idle_timeout());
+    }
+
+    #[tokio::test]
+    async fn test_request_many() {
+        let client = MockClient::empty();
+        let reqs = (1..=3)
+            .map(|cursor| api::ledgers::all().with_cursor(&cursor.to_string()))
+            .collect();
+        let mut indices: Vec<_> = client
+            .request_many(reqs, 2)
+            .map(|(index, result)| {
+                assert!(result.is_ok());
+                index
+            })
+            .collect()
+            .await;
+        indices.sort();
+        assert_eq!(vec![0, 1, 2], indices);
+        let uris = client.uris.lock().unwrap();
+        assert_eq!(3, uris.len());
+        assert!(uris.iter().any(|uri| uri.query() == Some("cursor=3")));
+    }
+}
@@ -0,0 +1,157 @@
+//! Close streams from another task.
+//!
+//! Dropping a stream returned by `HorizonClient::stream` closes its
+//! connection: the response future and the event decoder are dropped
+//! with it, and `hyper` closes the connection instead of returning it to
+//! the pool. Use `closeable` when the code that decides to stop the
+//! stream doesn't own it, for example to stop the stream of a
+//! subscription while a task is waiting on its next event.
+//!
+//! ```rust,no_run
+//! use futures::stream::StreamExt;
+//! use stellar_horizon::api;
+//! use stellar_horizon::client::{HorizonClient, HorizonHttpClient};
+//! use stellar_horizon::close::closeable;
+//!
+//! # async fn run() -> stellar_horizon::error::Result<()> {
+//! let client = HorizonHttpClient::new_from_str("https://horizon.stellar.org")?;
+//! let (mut stream, handle) = closeable(client.stream(api::ledgers::all())?);
+//! tokio::spawn(async move {
+//!     while let Some(ledger) = stream.next().await {
+//!         println!("Ledger = {:?}", ledger);
+//!     }
+//! });
+//! // Later, ends the stream and closes its connection.
+//! handle.close();
+//! # Ok(())
+//! # }
+//! ```
+use futures::stream::Stream;
+use futures::task::AtomicWaker;
+use std::marker::Unpin;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Returns a stream that ends when closed, and the handle to close it.
+pub fn closeable<S: Stream + Unpin>(stream: S) -> (Closeable<S>, CloseHandle) {
+    let state = Arc::new(CloseState {
+        closed: AtomicBool::new(false),
+        waker: AtomicWaker::new(),
+    });
+    let handle = CloseHandle {
+        state: state.clone(),
+    };
+    let stream = Closeable {
+        stream: Some(stream),
+        state,
+    };
+    (stream, handle)
+}
+
+struct CloseState {
+    closed: AtomicBool,
+    waker: AtomicWaker,
+}
+
+/// A handle to close a `Closeable` stream.
+#[derive(Clone)]
+pub struct CloseHandle {
+    state: Arc<CloseState>,
+}
+
+impl CloseHandle {
+    /// Closes the stream.
+    ///
+    /// A task waiting on the stream is woken up, and the stream ends
+    /// with `None` the next time it's polled. The wrapped stream, and
+    /// its connection, are dropped at that point.
+    pub fn close(&self) {
+        self.state.closed.store(true, Ordering::SeqCst);
+        self.state.waker.wake();
+    }
+
+    /// Returns `true` if the stream was closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.closed.load(Ordering::SeqCst)
+    }
+}
+
+/// A stream that can be closed with a `CloseHandle`.
+#[must_use = "Streams are lazy and do nothing unless polled"]
+pub struct Closeable<S> {
+    stream: Option<S>,
+    state: Arc<CloseState>,
+}
+
+impl<S> Closeable<S> {
+    /// Closes the stream and drops the wrapped stream immediately.
+    pub fn close(&mut self) {
+        self.state.closed.store(true, Ordering::SeqCst);
+        self.stream = None;
+    }
+
+    /// Returns `true` if the stream was closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.closed.load(Ordering::SeqCst)
+    }
+}
+
+impl<S: Stream + Unpin> Stream for Closeable<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.state.waker.register(cx.waker());
+        if this.state.closed.load(Ordering::SeqCst) {
+            this.stream = None;
+            return Poll::Ready(None);
+        }
+        let stream = match this.stream.as_mut() {
+            Some(stream) => stream,
+            None => return Poll::Ready(None),
+        };
+        let item = Pin::new(stream).poll_next(cx);
+        if let Poll::Ready(None) = item {
+            this.stream = None;
+        }
+        item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::closeable;
+    use futures::stream::{self, StreamExt};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_close_wakes_pending_stream() {
+        let (mut stream, handle) = closeable(stream::pending::<u32>());
+        let closer = handle.clone();
+        tokio::spawn(async move {
+            tokio::time::delay_for(Duration::from_millis(10)).await;
+            closer.close();
+        });
+        assert_eq!(None, stream.next().await);
+        assert!(handle.is_closed());
+        assert!(stream.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_close_drops_stream() {
+        let (mut stream, _handle) = closeable(stream::iter(vec![1, 2, 3]));
+        assert_eq!(Some(1), stream.next().await);
+        stream.close();
+        assert!(stream.stream.is_none());
+        assert_eq!(None, stream.next().await);
+    }
+
+    #[tokio::test]
+    async fn test_not_closed() {
+        let (stream, handle) = closeable(stream::iter(vec![1, 2]));
+        assert_eq!(vec![1, 2], stream.collect::<Vec<_>>().await);
+        assert!(!handle.is_closed());
+    }
+}
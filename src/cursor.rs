@@ -0,0 +1,270 @@
+//! Stream cursors and their persistence.
+use crate::error::Result;
+use crate::toid::Toid;
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A cursor, the paging token of a record.
+///
+/// Cursors are ordered like Horizon orders the records: tokens are
+/// compared by their `-` separated parts, numeric parts by their value
+/// and other parts lexically. This orders operation ids, such as
+/// `9` before `10`, and trade tokens, such as `12884905985-0` before
+/// `12884905985-1`. The `now` cursor is after all the others.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Cursor(String);
+
+impl Cursor {
+    /// Creates a new cursor.
+    pub fn new<S: Into<String>>(cursor: S) -> Cursor {
+        Cursor(cursor.into())
+    }
+
+    /// Returns the `now` cursor, used to stream only new records.
+    pub fn now() -> Cursor {
+        Cursor::new("now")
+    }
+
+    /// Returns `true` if this is the `now` cursor.
+    pub fn is_now(&self) -> bool {
+        self.0 == "now"
+    }
+
+    /// Returns the cursor as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the total order id of the cursor, if the cursor is one.
+    ///
+    /// Transactions, operations and effects cursors start with their
+    /// total order id.
+    pub fn toid(&self) -> Option<Toid> {
+        self.0.split('-').next()?.parse().ok()
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Cursor, Infallible> {
+        Ok(Cursor::new(s))
+    }
+}
+
+impl From<&str> for Cursor {
+    fn from(cursor: &str) -> Cursor {
+        Cursor::new(cursor)
+    }
+}
+
+impl From<String> for Cursor {
+    fn from(cursor: String) -> Cursor {
+        Cursor(cursor)
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Cursor {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Ord for Cursor {
+    fn cmp(&self, other: &Cursor) -> Ordering {
+        match (self.is_now(), other.is_now()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Greater,
+            (false, true) => return Ordering::Less,
+            (false, false) => {}
+        }
+        let mut parts = self.0.split('-');
+        let mut other_parts = other.0.split('-');
+        loop {
+            let ordering = match (parts.next(), other_parts.next()) {
+                (None, None) => return self.0.cmp(&other.0),
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(part), Some(other_part)) => compare_parts(part, other_part),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+    }
+}
+
+impl PartialOrd for Cursor {
+    fn partial_cmp(&self, other: &Cursor) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares numeric parts by value, before the other parts compared lexically.
+fn compare_parts(part: &str, other: &str) -> Ordering {
+    let is_numeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match (is_numeric(part), is_numeric(other)) {
+        (true, true) => {
+            let part = part.trim_start_matches('0');
+            let other = other.trim_start_matches('0');
+            part.len().cmp(&other.len()).then_with(|| part.cmp(other))
+        }
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => part.cmp(other),
+    }
+}
+
+/// Storage for the cursor of a stream.
+///
+/// Used by `HorizonClient::stream_durable` to resume a stream after
+/// a restart.
+pub trait CursorStore {
+    /// Returns the last saved cursor, if any.
+    fn load(&self) -> Result<Option<String>>;
+
+    /// Saves `cursor` as the last seen cursor.
+    fn save(&mut self, cursor: &str) -> Result<()>;
+}
+
+/// A `CursorStore` that keeps the cursor in a file.
+#[derive(Debug, Clone)]
+pub struct FileCursorStore {
+    path: PathBuf,
+}
+
+impl FileCursorStore {
+    /// Creates a new store that saves the cursor to `path`.
+    ///
+    /// The file is created the first time a cursor is saved.
+    pub fn new<P: Into<PathBuf>>(path: P) -> FileCursorStore {
+        FileCursorStore { path: path.into() }
+    }
+}
+
+impl CursorStore for FileCursorStore {
+    fn load(&self) -> Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => {
+                let cursor = content.trim();
+                if cursor.is_empty() {
+                    Ok(None)
+                } else {
+                    Ok(Some(cursor.to_string()))
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn save(&mut self, cursor: &str) -> Result<()> {
+        // Write to a temporary file first so that a crash never leaves
+        // a truncated cursor behind.
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, cursor)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_cursor_ordering() {
+        let cursor = |s: &str| Cursor::from_str(s).unwrap();
+        assert!(cursor("9") < cursor("10"));
+        assert!(cursor("12884905985-0") < cursor("12884905985-1"));
+        assert!(cursor("12884905985-9") < cursor("12884905985-10"));
+        assert!(cursor("12884905985") < cursor("12884905985-0"));
+        assert!(cursor("12884905985-1") < cursor("12884905986"));
+        assert!(cursor("10") < cursor("abc"));
+        assert!(cursor("abc") < cursor("abd"));
+        assert!(cursor("99999999999999999999") < cursor("100000000000000000000"));
+        assert!(cursor("12884905985") < Cursor::now());
+        assert!(cursor("007") < cursor("7"));
+        assert_eq!(Ordering::Equal, Cursor::now().cmp(&Cursor::now()));
+
+        let mut cursors = vec![cursor("10"), Cursor::now(), cursor("9-1"), cursor("9")];
+        cursors.sort();
+        assert_eq!(
+            vec![cursor("9"), cursor("9-1"), cursor("10"), Cursor::now()],
+            cursors
+        );
+    }
+
+    #[test]
+    fn test_cursor() {
+        let cursor = Cursor::new("12884905985-2");
+        assert_eq!("12884905985-2", cursor.to_string());
+        assert_eq!("12884905985-2", cursor.as_str());
+        assert_eq!(Some(Toid::from_i64(12884905985)), cursor.toid());
+        assert_eq!(None, Cursor::now().toid());
+        assert!(Cursor::now().is_now());
+        assert_eq!(
+            "\"42\"",
+            serde_json::to_string(&Cursor::from("42")).unwrap()
+        );
+        assert_eq!(
+            Cursor::from("42".to_string()),
+            serde_json::from_str("\"42\"").unwrap()
+        );
+    }
+
+    #[derive(Default)]
+    struct MemoryCursorStore {
+        cursor: Option<String>,
+    }
+
+    impl CursorStore for MemoryCursorStore {
+        fn load(&self) -> Result<Option<String>> {
+            Ok(self.cursor.clone())
+        }
+
+        fn save(&mut self, cursor: &str) -> Result<()> {
+            self.cursor = Some(cursor.to_string());
+            Ok(())
+        }
+    }
+
+    fn save_and_load<S: CursorStore>(store: &mut S) {
+        assert_eq!(None, store.load().unwrap());
+        store.save("12884905985-1").unwrap();
+        assert_eq!(Some("12884905985-1".to_string()), store.load().unwrap());
+        store.save("12884905985-2").unwrap();
+        assert_eq!(Some("12884905985-2".to_string()), store.load().unwrap());
+    }
+
+    #[test]
+    fn test_memory_cursor_store() {
+        let mut store = MemoryCursorStore::default();
+        save_and_load(&mut store);
+    }
+
+    #[test]
+    fn test_file_cursor_store() {
+        let path = env::temp_dir().join(format!("stellar-horizon-cursor-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut store = FileCursorStore::new(&path);
+        save_and_load(&mut store);
+        let store = FileCursorStore::new(&path);
+        assert_eq!(Some("12884905985-2".to_string()), store.load().unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,28 +1,166 @@
 //! Crate error type.
+use std::convert::TryFrom;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("stellar base error")]
     StellarBaseError(#[from] stellar_base::error::Error),
-    #[error("sse decoder error")]
-    SSEDecoderError,
+    #[error("sse decoder error: {0}")]
+    SSEDecoderError(String),
+    #[error("response is not an event stream")]
+    NotAnEventStream { content_type: Option<String> },
     #[error("horizon request error")]
     HorizonRequestError(crate::horizon_error::HorizonError),
-    #[error("horizon server error")]
-    HorizonServerError,
+    #[error("rate limited")]
+    RateLimited {
+        reset: Option<u64>,
+        retry_after: Option<std::time::Duration>,
+        request_id: Option<String>,
+    },
+    #[error("unexpected status code {status}")]
+    UnexpectedStatus {
+        status: u16,
+        request_id: Option<String>,
+    },
+    #[error("timeout")]
+    Timeout {
+        request_id: Option<String>,
+        /// The Horizon error response, if the response body is one.
+        error: Option<Box<crate::horizon_error::HorizonError>>,
+    },
     #[error("http error")]
-    HttpError(#[from] http::Error),
-    #[error("hyper error")]
-    HyperError(#[from] hyper::Error),
-    #[error("json error")]
-    JsonError(#[from] serde_json::error::Error),
+    Http(#[from] hyper::Error),
+    #[error("invalid http request")]
+    InvalidHttpRequest(#[from] http::Error),
+    #[error("deserialization error")]
+    Deserialization {
+        #[source]
+        source: serde_json::error::Error,
+        body_snippet: String,
+    },
     #[error("invalid uri")]
     InvalidUri(#[from] http::uri::InvalidUri),
+    #[error("base64 decode error")]
+    Base64DecodeError(#[from] base64::DecodeError),
+    #[error("io error")]
+    IoError(#[from] std::io::Error),
     #[error("invalid url")]
     InvalidUrl(#[from] url::ParseError),
     #[error("invalid host")]
     InvalidHost,
     #[error("invalid predicate")]
     InvalidPredicate,
+    #[error("invalid asset")]
+    InvalidAsset,
+    #[error("invalid asset pair")]
+    InvalidAssetPair,
+    #[error("invalid offset")]
+    InvalidOffset,
+    #[error("invalid time range")]
+    InvalidTimeRange,
+    #[error("invalid toid")]
+    InvalidToid,
+    #[error("invalid request {field}: {reason}")]
+    InvalidRequest { field: &'static str, reason: String },
+    #[error("invalid link template")]
+    InvalidLinkTemplate,
+    #[error("link not found: {0}")]
+    LinkNotFound(String),
+    #[error("no client available")]
+    NoClientAvailable,
+}
+
+/// How many bytes of the response body to keep in `Error::Deserialization`.
+const BODY_SNIPPET_LEN: usize = 256;
+
+impl Error {
+    /// Creates a deserialization error keeping the start of `body`.
+    pub(crate) fn deserialization(source: serde_json::error::Error, body: &[u8]) -> Error {
+        let snippet = &body[..body.len().min(BODY_SNIPPET_LEN)];
+        Error::Deserialization {
+            source,
+            body_snippet: String::from_utf8_lossy(snippet).into_owned(),
+        }
+    }
+
+    /// Returns `true` if the request can be retried later.
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            Error::Http(_) | Error::Timeout { .. } | Error::RateLimited { .. } => true,
+            Error::UnexpectedStatus { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Returns how long to wait before retrying a rate limited request.
+    ///
+    /// This is the `Retry-After` header of the response if present,
+    /// otherwise the time remaining in the rate limit window.
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            Error::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } => Some(*retry_after),
+            Error::RateLimited {
+                reset: Some(reset), ..
+            } => Some(std::time::Duration::from_secs(*reset)),
+            _ => None,
+        }
+    }
+
+    /// Returns the Horizon error response, if any.
+    pub fn horizon_error(&self) -> Option<&crate::horizon_error::HorizonError> {
+        match self {
+            Error::HorizonRequestError(err) => Some(err),
+            Error::Timeout {
+                error: Some(err), ..
+            } => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if Horizon answered that the resource doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        self.horizon_error()
+            .map(|err| err.is_not_found())
+            .unwrap_or(false)
+    }
+
+    /// Returns the status code of the Horizon response that caused the error, if any.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::HorizonRequestError(err) => u16::try_from(err.status).ok(),
+            Error::RateLimited { .. } => Some(429),
+            Error::Timeout { .. } => Some(504),
+            Error::UnexpectedStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns the `X-Request-Id` header of the Horizon response that
+    /// caused the error, if any.
+    ///
+    /// Horizon operators use the request id to find the request in
+    /// their logs.
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Error::HorizonRequestError(err) => err.request_id.as_deref(),
+            Error::RateLimited { request_id, .. }
+            | Error::UnexpectedStatus { request_id, .. }
+            | Error::Timeout { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::error::Error> for Error {
+    fn from(source: serde_json::error::Error) -> Error {
+        Error::Deserialization {
+            source,
+            body_snippet: String::new(),
+        }
+    }
 }
@@ -1,8 +1,14 @@
 //! Helper functions to access Horizon headers.
+use chrono::{DateTime, Utc};
 use std::str::FromStr;
+use std::time::Duration;
 
 pub use hyper::HeaderMap;
 
+/// Headers used by caches and CDNs to report the cache status, in
+/// order of preference.
+const CACHE_STATUS_HEADERS: [&str; 4] = ["Cache-Status", "X-Cache", "X-Cached", "CF-Cache-Status"];
+
 /// Returns the remaining requests quota in the current window.
 pub fn rate_limit_remaining(headers: &HeaderMap) -> Option<u32> {
     headers
@@ -26,3 +32,168 @@ pub fn rate_limit_reset(headers: &HeaderMap) -> Option<u32> {
         .map(|value| u32::from_str(value.to_str().unwrap_or("")).ok())
         .unwrap_or(None)
 }
+
+/// Returns how long to wait before sending another request, from the `Retry-After` header.
+///
+/// The header is either a number of seconds or an HTTP date, dates in
+/// the past return a zero duration.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    retry_after_at(headers, Utc::now())
+}
+
+fn retry_after_at(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers
+        .get(hyper::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = u64::from_str(value) {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        date.with_timezone(&Utc)
+            .signed_duration_since(now)
+            .to_std()
+            .unwrap_or_else(|_| Duration::from_secs(0)),
+    )
+}
+
+/// Returns the id Horizon assigned to the request.
+pub fn request_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("X-Request-Id")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+/// Returns the cache status reported by a cache or CDN in front of Horizon.
+///
+/// Looks for `Cache-Status`, `X-Cache`, `X-Cached` and `CF-Cache-Status`,
+/// in this order, and returns the first one present.
+pub fn cache_status(headers: &HeaderMap) -> Option<String> {
+    CACHE_STATUS_HEADERS
+        .iter()
+        .filter_map(|name| headers.get(*name))
+        .find_map(|value| value.to_str().ok().map(|s| s.to_string()))
+}
+
+/// Caching directives from the `Cache-Control` header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    /// The number of seconds the response is fresh for.
+    pub max_age: Option<u32>,
+    /// If `true`, the response must be revalidated before being reused.
+    pub no_cache: bool,
+    /// The number of seconds a stale response can be used while it's revalidated.
+    pub stale_while_revalidate: Option<u32>,
+}
+
+/// Returns the caching directives in the `Cache-Control` header.
+///
+/// Unknown or malformed directives are ignored, and a missing header
+/// returns the default value.
+pub fn cache_control(headers: &HeaderMap) -> CacheControl {
+    let mut cache_control = CacheControl::default();
+    let value = match headers
+        .get(hyper::header::CACHE_CONTROL)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(value) => value,
+        None => return cache_control,
+    };
+    for directive in value.split(',') {
+        let mut parts = directive.splitn(2, '=');
+        let name = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let seconds = parts
+            .next()
+            .and_then(|v| u32::from_str(v.trim().trim_matches('"')).ok());
+        match name.as_str() {
+            "max-age" => cache_control.max_age = seconds,
+            "no-cache" => cache_control.no_cache = true,
+            "stale-while-revalidate" => cache_control.stale_while_revalidate = seconds,
+            _ => {}
+        }
+    }
+    cache_control
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_status_missing() {
+        let headers = HeaderMap::new();
+        assert_eq!(None, cache_status(&headers));
+    }
+
+    #[test]
+    fn test_cache_status() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Cache", "Hit from cloudfront".parse().unwrap());
+        assert_eq!(
+            Some("Hit from cloudfront".to_string()),
+            cache_status(&headers)
+        );
+        headers.insert("Cache-Status", "ExampleCache; hit".parse().unwrap());
+        assert_eq!(
+            Some("ExampleCache; hit".to_string()),
+            cache_status(&headers)
+        );
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(None, retry_after(&headers));
+        headers.insert("Retry-After", "120".parse().unwrap());
+        assert_eq!(Some(Duration::from_secs(120)), retry_after(&headers));
+
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:27:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        headers.insert(
+            "Retry-After",
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(Some(Duration::from_secs(30)), retry_after_at(&headers, now));
+        let later = now + chrono::Duration::minutes(1);
+        assert_eq!(
+            Some(Duration::from_secs(0)),
+            retry_after_at(&headers, later)
+        );
+        headers.insert("Retry-After", "soon".parse().unwrap());
+        assert_eq!(None, retry_after(&headers));
+    }
+
+    #[test]
+    fn test_cache_control_missing() {
+        let headers = HeaderMap::new();
+        assert_eq!(CacheControl::default(), cache_control(&headers));
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Cache-Control",
+            "public, Max-Age=5, stale-while-revalidate=30"
+                .parse()
+                .unwrap(),
+        );
+        let expected = CacheControl {
+            max_age: Some(5),
+            no_cache: false,
+            stale_while_revalidate: Some(30),
+        };
+        assert_eq!(expected, cache_control(&headers));
+        headers.insert("Cache-Control", "no-cache, max-age=abc".parse().unwrap());
+        let expected = CacheControl {
+            max_age: None,
+            no_cache: true,
+            stale_while_revalidate: None,
+        };
+        assert_eq!(expected, cache_control(&headers));
+    }
+}
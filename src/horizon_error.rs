@@ -1,13 +1,164 @@
 //! Horizon error response.
+use crate::resources::{OperationResultCode, TransactionResultCodes};
 use serde::{Deserialize, Serialize};
 
 /// Horizon error response.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HorizonError {
+    /// The url identifying the type of the error, for example
+    /// `https://stellar.org/horizon-errors/not_found`.
+    #[serde(rename = "type", default)]
+    pub type_: String,
     /// A short description of the error.
     pub title: String,
     /// A longer description of the error.
     pub detail: String,
     /// The status code.
     pub status: i64,
+    /// Additional information about the error.
+    pub extras: Option<Box<HorizonErrorExtras>>,
+    /// The `X-Request-Id` header of the response.
+    #[serde(skip)]
+    pub request_id: Option<String>,
+}
+
+/// Additional information returned with some errors, for example
+/// when a transaction submission fails.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HorizonErrorExtras {
+    /// A base64 encoded string of the submitted `TransactionEnvelope` XDR.
+    pub envelope_xdr: Option<String>,
+    /// A base64 encoded string of the `TransactionResult` XDR.
+    pub result_xdr: Option<String>,
+    /// The transaction and operations result codes.
+    pub result_codes: Option<TransactionResultCodes>,
+}
+
+/// The type of an Horizon error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HorizonErrorKind {
+    /// The resource doesn't exist, for example an account not created yet.
+    NotFound,
+    /// The transaction was submitted and failed.
+    TransactionFailed,
+    /// The transaction is not a valid transaction envelope.
+    TransactionMalformed,
+    /// Too many requests were sent.
+    RateLimitExceeded,
+    /// The request parameters are invalid.
+    BadRequest,
+    /// The data requested is older than the history of this Horizon.
+    BeforeHistory,
+    /// The history of this Horizon is not up to date with the network.
+    StaleHistory,
+    /// Horizon timed out before a response was ready.
+    Timeout,
+    /// Horizon failed to handle the request.
+    ServerError,
+    /// Another type of error.
+    Other,
+}
+
+/// Prefix of the Horizon error types.
+const ERROR_TYPE_PREFIX: &str = "https://stellar.org/horizon-errors/";
+
+impl HorizonError {
+    /// Returns the type of the error.
+    ///
+    /// Older Horizon versions don't send the error type, the kind is
+    /// then guessed from the status code.
+    pub fn kind(&self) -> HorizonErrorKind {
+        let name = self
+            .type_
+            .strip_prefix(ERROR_TYPE_PREFIX)
+            .unwrap_or(&self.type_);
+        match name {
+            "not_found" => HorizonErrorKind::NotFound,
+            "transaction_failed" => HorizonErrorKind::TransactionFailed,
+            "transaction_malformed" => HorizonErrorKind::TransactionMalformed,
+            "rate_limit_exceeded" => HorizonErrorKind::RateLimitExceeded,
+            "bad_request" => HorizonErrorKind::BadRequest,
+            "before_history" => HorizonErrorKind::BeforeHistory,
+            "stale_history" => HorizonErrorKind::StaleHistory,
+            "timeout" => HorizonErrorKind::Timeout,
+            "server_error" => HorizonErrorKind::ServerError,
+            "" => match self.status {
+                400 => HorizonErrorKind::BadRequest,
+                404 => HorizonErrorKind::NotFound,
+                429 => HorizonErrorKind::RateLimitExceeded,
+                504 => HorizonErrorKind::Timeout,
+                500..=599 => HorizonErrorKind::ServerError,
+                _ => HorizonErrorKind::Other,
+            },
+            _ => HorizonErrorKind::Other,
+        }
+    }
+
+    /// Returns `true` if the resource doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        self.kind() == HorizonErrorKind::NotFound
+    }
+
+    /// Returns `true` if the transaction was submitted and failed.
+    pub fn is_transaction_failed(&self) -> bool {
+        self.kind() == HorizonErrorKind::TransactionFailed
+    }
+
+    /// Returns `true` if too many requests were sent.
+    pub fn is_rate_limited(&self) -> bool {
+        self.kind() == HorizonErrorKind::RateLimitExceeded
+    }
+
+    /// Returns the transaction and operations result codes, if any.
+    pub fn result_codes(&self) -> Option<&TransactionResultCodes> {
+        self.extras.as_ref()?.result_codes.as_ref()
+    }
+
+    /// Returns the result code of each operation, if any.
+    pub fn operation_result_codes(&self) -> Option<&[OperationResultCode]> {
+        self.result_codes().map(|codes| codes.operations.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HorizonError, HorizonErrorKind};
+
+    fn error(type_: &str, status: i64) -> HorizonError {
+        HorizonError {
+            type_: type_.to_string(),
+            title: String::new(),
+            detail: String::new(),
+            status,
+            extras: None,
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_kind() {
+        let err = error("https://stellar.org/horizon-errors/not_found", 404);
+        assert_eq!(HorizonErrorKind::NotFound, err.kind());
+        assert!(err.is_not_found());
+        assert!(!err.is_transaction_failed());
+        let err = error(
+            "https://stellar.org/horizon-errors/rate_limit_exceeded",
+            429,
+        );
+        assert!(err.is_rate_limited());
+        let err = error("https://stellar.org/horizon-errors/stale_history", 503);
+        assert_eq!(HorizonErrorKind::StaleHistory, err.kind());
+        let err = error("https://stellar.org/horizon-errors/payment_required", 402);
+        assert_eq!(HorizonErrorKind::Other, err.kind());
+    }
+
+    #[test]
+    fn test_kind_without_type() {
+        assert_eq!(HorizonErrorKind::NotFound, error("", 404).kind());
+        assert_eq!(HorizonErrorKind::ServerError, error("", 500).kind());
+        assert_eq!(HorizonErrorKind::Other, error("", 402).kind());
+        let err: HorizonError =
+            serde_json::from_str(r#"{"title": "Not Found", "detail": "", "status": 404}"#).unwrap();
+        assert!(err.is_not_found());
+    }
 }
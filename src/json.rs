@@ -0,0 +1,404 @@
+//! Deserialization of Horizon responses.
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, Deserializer, Error as _, MapAccess, SeqAccess, Visitor,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::cell::RefCell;
+
+thread_local! {
+    /// The strict deserialization in progress on this thread.
+    static STRICT: RefCell<Option<Strict>> = const { RefCell::new(None) };
+}
+
+/// The state of a strict deserialization.
+#[derive(Default)]
+struct Strict {
+    /// The path of the value being deserialized.
+    path: String,
+    /// The path of the first unknown field.
+    unknown: Option<String>,
+}
+
+/// How responses with fields unknown to this crate are deserialized.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DeserializationMode {
+    /// Ignore unknown fields.
+    #[default]
+    Lenient,
+    /// Fail with a deserialization error on unknown fields.
+    ///
+    /// Use it to detect the fields added by new Horizon versions. A
+    /// field is unknown if it is skipped while deserializing, fields
+    /// captured by a flattened map are known. Operations and effects are
+    /// buffered before being deserialized, their fields are unknown if
+    /// they are missing when serializing them back.
+    Strict,
+}
+
+/// Deserializes `bytes` as `T` with `mode`.
+pub(crate) fn from_slice<T>(bytes: &[u8], mode: DeserializationMode) -> serde_json::Result<T>
+where
+    T: DeserializeOwned,
+{
+    match mode {
+        DeserializationMode::Lenient => serde_json::from_slice(bytes),
+        DeserializationMode::Strict => {
+            let input: Value = serde_json::from_slice(bytes)?;
+            STRICT.with(|strict| strict.replace(Some(Strict::default())));
+            let result = T::deserialize(Tracked {
+                value: &input,
+                path: String::new(),
+            });
+            let strict = STRICT.with(|strict| strict.take()).unwrap_or_default();
+            match strict.unknown {
+                Some(path) if result.is_ok() => Err(serde_json::Error::custom(format!(
+                    "unknown field `{}`",
+                    path
+                ))),
+                _ => result,
+            }
+        }
+    }
+}
+
+/// Returns the path of the value being deserialized, if the
+/// deserialization is strict.
+pub(crate) fn strict_path() -> Option<String> {
+    STRICT.with(|strict| strict.borrow().as_ref().map(|strict| strict.path.clone()))
+}
+
+/// Records the first field of `value`, at `path`, missing from `record`
+/// serialized back.
+///
+/// Used by the types buffering their value before deserializing it, which
+/// a `Tracked` deserializer doesn't see.
+pub(crate) fn check_record<T: Serialize>(path: &str, value: &Value, record: &T) {
+    if let Ok(known) = serde_json::to_value(record) {
+        if let Some(path) = unknown_field(path, value, &known) {
+            record_unknown(path);
+        }
+    }
+}
+
+/// Returns the path of the first field of `value` missing from `known`.
+fn unknown_field(path: &str, value: &Value, known: &Value) -> Option<String> {
+    match (value, known) {
+        (Value::Object(values), Value::Object(known)) => values.iter().find_map(|(key, value)| {
+            let path = field_path(path, key);
+            match known.get(key) {
+                Some(known) => unknown_field(&path, value, known),
+                None if is_empty(value) => None,
+                None => Some(path),
+            }
+        }),
+        (Value::Array(values), Value::Array(known)) => values
+            .iter()
+            .zip(known)
+            .enumerate()
+            .find_map(|(i, (value, known))| {
+                unknown_field(&format!("{}[{}]", path, i), value, known)
+            }),
+        _ => None,
+    }
+}
+
+/// Records `path` as unknown, unless an unknown field was already found.
+fn record_unknown(path: String) {
+    STRICT.with(|strict| {
+        if let Some(strict) = strict.borrow_mut().as_mut() {
+            strict.unknown.get_or_insert(path);
+        }
+    });
+}
+
+/// Sets the path of the value being deserialized to `path`.
+fn set_path(path: &str) {
+    STRICT.with(|strict| {
+        if let Some(strict) = strict.borrow_mut().as_mut() {
+            strict.path.clear();
+            strict.path.push_str(path);
+        }
+    });
+}
+
+/// Returns the path of `key` in the object at `path`.
+fn field_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// A deserializer of `value` that records the path of the first field
+/// skipped by the deserialized type.
+///
+/// Fields without a value, such as `null` or empty arrays, are not
+/// reported.
+struct Tracked<'a> {
+    value: &'a Value,
+    path: String,
+}
+
+impl<'a> Tracked<'a> {
+    fn child(&self, value: &'a Value, path: String) -> Tracked<'a> {
+        set_path(&path);
+        Tracked { value, path }
+    }
+}
+
+impl<'de> Deserializer<'de> for Tracked<'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Value::Array(values) => visitor.visit_seq(TrackedSeq {
+                values: values.iter().enumerate(),
+                parent: self,
+            }),
+            Value::Object(values) => visitor.visit_map(TrackedMap {
+                values: values.iter(),
+                value: None,
+                parent: self,
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        if !is_empty(self.value) {
+            record_unknown(self.path);
+        }
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier
+    }
+}
+
+struct TrackedSeq<'a, I> {
+    values: I,
+    parent: Tracked<'a>,
+}
+
+impl<'de, I> SeqAccess<'de> for TrackedSeq<'de, I>
+where
+    I: Iterator<Item = (usize, &'de Value)>,
+{
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> serde_json::Result<Option<T::Value>> {
+        match self.values.next() {
+            Some((i, value)) => {
+                let path = format!("{}[{}]", self.parent.path, i);
+                seed.deserialize(self.parent.child(value, path)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+struct TrackedMap<'a> {
+    values: serde_json::map::Iter<'a>,
+    value: Option<(&'a String, &'a Value)>,
+    parent: Tracked<'a>,
+}
+
+impl<'de> MapAccess<'de> for TrackedMap<'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> serde_json::Result<Option<K::Value>> {
+        match self.values.next() {
+            Some((key, value)) => {
+                self.value = Some((key, value));
+                seed.deserialize(BorrowedStrDeserializer::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> serde_json::Result<V::Value> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| serde_json::Error::custom("value is missing"))?;
+        let path = field_path(&self.parent.path, key);
+        seed.deserialize(self.parent.child(value, path))
+    }
+}
+
+/// Returns `true` if `value` is `null`, or an empty array or object.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(values) => values.is_empty(),
+        Value::Object(values) => values.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_slice, DeserializationMode};
+    use crate::page::Page;
+    use crate::resources::{Effect, FeeStats, Ledger, Operation, Price, TradeAggregation};
+
+    const LEDGER: &str = include_str!("../tests/fixtures/ledger.json");
+
+    fn with_field(json: &str, pointer: &str, name: &str) -> String {
+        let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+        value
+            .pointer_mut(pointer)
+            .unwrap()
+            .as_object_mut()
+            .unwrap()
+            .insert(name.to_string(), serde_json::Value::from(1));
+        value.to_string()
+    }
+
+    fn strict_error<T: serde::de::DeserializeOwned>(json: &str) -> Option<String> {
+        from_slice::<T>(json.as_bytes(), DeserializationMode::Strict)
+            .err()
+            .map(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_default_mode_is_lenient() {
+        assert_eq!(DeserializationMode::Lenient, DeserializationMode::default());
+    }
+
+    #[test]
+    fn test_lenient_ignores_unknown_fields() {
+        let json = with_field(LEDGER, "", "new_field");
+        assert!(from_slice::<Ledger>(json.as_bytes(), DeserializationMode::Lenient).is_ok());
+    }
+
+    #[test]
+    fn test_strict_without_unknown_fields() {
+        assert_eq!(None, strict_error::<Ledger>(LEDGER));
+    }
+
+    #[test]
+    fn test_strict_rejects_unknown_fields() {
+        let json = with_field(LEDGER, "", "new_field");
+        assert_eq!(
+            Some("unknown field `new_field`".to_string()),
+            strict_error::<Ledger>(&json)
+        );
+        let json = with_field(LEDGER, "/_links/self", "new_field");
+        assert_eq!(
+            Some("unknown field `_links.self.new_field`".to_string()),
+            strict_error::<Ledger>(&json)
+        );
+    }
+
+    #[test]
+    fn test_strict_reports_path_in_arrays() {
+        let page = r#"{
+            "_links": null,
+            "_embedded": {"records": [{"n": 1, "d": 2}, {"n": 1, "d": 2, "e": 3}]}
+        }"#;
+        assert_eq!(
+            Some("unknown field `_embedded.records[1].e`".to_string()),
+            strict_error::<Page<Price>>(page)
+        );
+    }
+
+    #[test]
+    fn test_strict_allows_aliases_and_empty_values() {
+        let price = r#"{"N": 1, "D": 2, "unknown": null}"#;
+        assert_eq!(None, strict_error::<Price>(price));
+        let aggregations = include_str!("../tests/fixtures/all_trade_aggregations.json");
+        assert_eq!(None, strict_error::<Page<TradeAggregation>>(aggregations));
+    }
+
+    #[test]
+    fn test_strict_allows_fields_of_flattened_maps() {
+        let fee_stats = include_str!("../tests/fixtures/fee_stats.json");
+        let json = with_field(fee_stats, "/fee_charged", "p100");
+        assert_eq!(None, strict_error::<FeeStats>(&json));
+        let fees: FeeStats = serde_json::from_str(&json).unwrap();
+        assert!(fees.fee_charged.other.contains_key("p100"));
+        let json = with_field(fee_stats, "", "new_field");
+        assert_eq!(
+            Some("unknown field `new_field`".to_string()),
+            strict_error::<FeeStats>(&json)
+        );
+    }
+
+    #[test]
+    fn test_strict_checks_operations_and_effects() {
+        let operations = include_str!("../tests/fixtures/all_operations.json");
+        assert_eq!(
+            Some("unknown field `_embedded.records[202].sponsor`".to_string()),
+            strict_error::<Page<Operation>>(operations)
+        );
+        let effects = include_str!("../tests/fixtures/effects_trade_and_unknown.json");
+        assert_eq!(None, strict_error::<Page<Effect>>(effects));
+        let operation = include_str!("../tests/fixtures/operation_with_transaction.json");
+        assert_eq!(None, strict_error::<Operation>(operation));
+    }
+
+    #[test]
+    fn test_strict_rejects_unknown_fields_of_operations() {
+        let operations = include_str!("../tests/fixtures/all_operations.json");
+        let json = with_field(operations, "/_embedded/records/1", "new_field");
+        assert_eq!(
+            Some("unknown field `_embedded.records[1].new_field`".to_string()),
+            strict_error::<Page<Operation>>(&json)
+        );
+        let json = with_field(operations, "/_embedded/records/0/_links/self", "new_field");
+        assert_eq!(
+            Some("unknown field `_embedded.records[0]._links.self.new_field`".to_string()),
+            strict_error::<Page<Operation>>(&json)
+        );
+        assert!(
+            from_slice::<Page<Operation>>(json.as_bytes(), DeserializationMode::Lenient).is_ok()
+        );
+        let effects = include_str!("../tests/fixtures/effects_trade_and_unknown.json");
+        let json = with_field(effects, "/_embedded/records/0", "new_field");
+        assert_eq!(
+            Some("unknown field `_embedded.records[0].new_field`".to_string()),
+            strict_error::<Page<Effect>>(&json)
+        );
+    }
+}
@@ -71,7 +71,7 @@
 //!
 //! # async fn run() -> stellar_horizon::error::Result<()> {
 //! let client = HorizonHttpClient::new_from_str("https://horizon.stellar.org")?;
-//! let request = api::transactions::all().with_cursor("now");
+//! let request = api::transactions::all().with_cursor_now();
 //! // Only take the first 5 events.
 //! let mut stream = client.stream(request)?.take(5);
 //! while let Some(event) = stream.try_next().await? {
@@ -80,6 +80,44 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Blocking client
+//!
+//! Enabling the `blocking` feature adds `blocking::HorizonHttpClient`,
+//! a client with synchronous methods for scripts and command line
+//! tools that don't use an async runtime.
+//!
+//! ## XDR decoding
+//!
+//! Enabling the `xdr` feature adds methods to decode the XDR fields of
+//! resources into `stellar_base` types, for example
+//! `Transaction::decode_result` to inspect the result of each operation.
+//!
+//! ## Load balancing
+//!
+//! `load_balancer::LoadBalancedClient` is an `HorizonClient` that
+//! sends requests round-robin to several Horizon instances, skipping
+//! the instances that are unhealthy or not synced with the network.
+//!
+//! ## Instrumentation
+//!
+//! `HorizonHttpClient::with_on_response` registers a callback called
+//! with the uri, status code, and latency of each response, which can
+//! be used to export metrics. Enabling the `tracing` feature also
+//! emits a `tracing` span for each request and events when a stream
+//! connects or reconnects.
+//!
+//! ## Custom HTTP backends
+//!
+//! Requests only describe *what* to send to Horizon: the uri (through
+//! `Request::uri`), the optional form body (`Request::post_body`) and
+//! the response type. `HorizonHttpClient` is just one implementation
+//! of `HorizonClient` built on `hyper`, you can drive the same request
+//! types with a different http library by implementing `HorizonClient`
+//! yourself.
+//!
+//! Note that the crate does not currently build for
+//! `wasm32-unknown-unknown` since `stellar-base` depends on `libsodium`.
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
@@ -90,13 +128,29 @@ extern crate stellar_base;
 pub mod request;
 
 pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod capabilities;
+pub mod channel;
 pub mod client;
+pub mod close;
+pub mod cursor;
 pub mod error;
 pub mod headers;
 pub mod horizon_error;
+pub mod json;
+#[macro_use]
 pub mod link;
+pub mod load_balancer;
 pub mod page;
+pub mod paginate;
+pub mod prelude;
 pub mod resources;
+mod sse;
+#[cfg(test)]
+mod testing;
+pub mod toid;
 
 /// The crate version.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
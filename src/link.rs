@@ -1,4 +1,13 @@
-//! Pagination link.
+//! Resource links and requests following them.
+use crate::error::{Error, Result};
+use crate::page::Page;
+use crate::request::{Order, PageRequest, Request, StreamRequest, UrlPageRequestExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use url::form_urlencoded;
+use url::Url;
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Link {
@@ -10,6 +19,118 @@ pub struct Link {
     pub templated: bool,
 }
 
+impl Link {
+    /// Expands the link template with `params`, returns the link url.
+    ///
+    /// Simple expressions like `{key}` are replaced by the percent
+    /// encoded value of the parameter and are required. Query
+    /// expressions like `{?cursor,limit}` add the parameters that are
+    /// present to the url query, and are dropped otherwise.
+    pub fn expand(&self, params: &[(&str, &str)]) -> Result<Url> {
+        if !self.templated {
+            return Ok(Url::parse(&self.href)?);
+        }
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+        let mut href = String::with_capacity(self.href.len());
+        let mut query = Vec::new();
+        let mut rest = self.href.as_str();
+        while let Some(start) = rest.find('{') {
+            href.push_str(&rest[..start]);
+            let end = rest[start..].find('}').ok_or(Error::InvalidLinkTemplate)? + start;
+            let expression = &rest[start + 1..end];
+            if let Some(names) = expression.strip_prefix('?') {
+                for name in names.split(',') {
+                    if let Some(value) = param(name) {
+                        query.push((name, value));
+                    }
+                }
+            } else {
+                let value = param(expression).ok_or(Error::InvalidLinkTemplate)?;
+                let encoded: String = form_urlencoded::byte_serialize(value.as_bytes()).collect();
+                href.push_str(&encoded.replace('+', "%20"));
+            }
+            rest = &rest[end + 1..];
+        }
+        href.push_str(rest);
+        let mut url = Url::parse(&href)?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        Ok(url)
+    }
+}
+
+/// The links of a resource, by name.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Links(BTreeMap<String, Link>);
+
+impl Links {
+    /// Returns the link with `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&Link> {
+        self.0.get(name)
+    }
+
+    /// Returns an iterator over the links names and links.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Link)> {
+        self.0.iter()
+    }
+
+    /// Expands the link with `name` with `params`, see `Link::expand`.
+    pub fn expand(&self, name: &str, params: &[(&str, &str)]) -> Result<Url> {
+        self.get(name)
+            .ok_or_else(|| Error::LinkNotFound(name.to_string()))?
+            .expand(params)
+    }
+}
+
+/// A link field of a typed links struct, that may be missing.
+pub(crate) trait LinkField {
+    fn link(&self) -> Option<&Link>;
+}
+
+impl LinkField for Link {
+    fn link(&self) -> Option<&Link> {
+        Some(self)
+    }
+}
+
+impl LinkField for Option<Link> {
+    fn link(&self) -> Option<&Link> {
+        self.as_ref()
+    }
+}
+
+/// Implements `From<&$name> for Links` for a typed links struct, by
+/// listing the link names and the fields they are stored in.
+macro_rules! impl_links {
+    ($name:ty { $($link:literal => $field:ident),* $(,)? }) => {
+        impl From<&$name> for $crate::link::Links {
+            fn from(links: &$name) -> $crate::link::Links {
+                use $crate::link::LinkField;
+                let mut map = std::collections::BTreeMap::new();
+                $(
+                    if let Some(link) = links.$field.link() {
+                        map.insert($link.to_string(), link.clone());
+                    }
+                )*
+                $crate::link::Links::from(map)
+            }
+        }
+    };
+}
+
+impl From<BTreeMap<String, Link>> for Links {
+    fn from(links: BTreeMap<String, Link>) -> Links {
+        Links(links)
+    }
+}
+
 fn default_templated_as_false() -> bool {
     false
 }
@@ -17,3 +138,286 @@ fn default_templated_as_false() -> bool {
 fn templated_is_false(v: &bool) -> bool {
     !v
 }
+
+/// Returns `url` resolved against the client `host`.
+///
+/// Links must have the same origin as `host`. Their path is joined to
+/// the path of `host`, unless it already starts with it.
+fn resolve(url: &Url, host: &Url) -> Result<Url> {
+    if url.origin() != host.origin() {
+        return Err(Error::InvalidRequest {
+            field: "url",
+            reason: format!(
+                "{} is not on the client host",
+                url.origin().ascii_serialization()
+            ),
+        });
+    }
+    if url.path().starts_with(host.path()) {
+        return Ok(url.clone());
+    }
+    let mut resolved = host.join(url.path().trim_start_matches('/'))?;
+    resolved.set_query(url.query());
+    Ok(resolved)
+}
+
+/// Request the resource pointed by a link.
+///
+/// The request uri is the link url resolved against the client host,
+/// links to other hosts are rejected.
+#[derive(Debug, Clone)]
+pub struct LinkRequest<T> {
+    url: Url,
+    resource: PhantomData<fn() -> T>,
+}
+
+impl<T> LinkRequest<T> {
+    /// Creates a request for the resource at `url`.
+    pub fn new(url: Url) -> LinkRequest<T> {
+        LinkRequest {
+            url,
+            resource: PhantomData,
+        }
+    }
+
+    /// Creates a request for the resource pointed by `link`, expanded with `params`.
+    pub fn from_link(link: &Link, params: &[(&str, &str)]) -> Result<LinkRequest<T>> {
+        Ok(LinkRequest::new(link.expand(params)?))
+    }
+
+    /// Returns the request url.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+impl<T: DeserializeOwned> Request for LinkRequest<T> {
+    type Response = T;
+
+    fn uri(&self, host: &Url) -> Result<Url> {
+        resolve(&self.url, host)
+    }
+}
+
+/// Request the page of resources pointed by a link.
+///
+/// The request uri is the link url, resolved against the client host,
+/// with the pagination parameters. Links to other hosts are rejected.
+#[derive(Debug, Clone)]
+pub struct LinkPageRequest<T> {
+    url: Url,
+    limit: Option<u64>,
+    cursor: Option<String>,
+    order: Option<Order>,
+    resource: PhantomData<fn() -> T>,
+}
+
+impl<T> LinkPageRequest<T> {
+    /// Creates a request for the page of resources at `url`.
+    pub fn new(url: Url) -> LinkPageRequest<T> {
+        LinkPageRequest {
+            url,
+            limit: None,
+            cursor: None,
+            order: None,
+            resource: PhantomData,
+        }
+    }
+
+    /// Creates a request for the page of resources pointed by `link`.
+    ///
+    /// The pagination parameters of templated links are set with the
+    /// request `with_cursor`, `with_limit`, and `with_order` methods.
+    pub fn from_link(link: &Link) -> Result<LinkPageRequest<T>> {
+        Ok(LinkPageRequest::new(link.expand(&[])?))
+    }
+
+    /// Returns the request url, without the pagination parameters.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+impl<T: DeserializeOwned + Serialize + Clone> Request for LinkPageRequest<T> {
+    type Response = Page<T>;
+
+    fn uri(&self, host: &Url) -> Result<Url> {
+        resolve(&self.url, host)?.append_pagination_params(self)
+    }
+}
+
+impl<T: DeserializeOwned + Serialize + Clone> PageRequest for LinkPageRequest<T> {
+    fn with_cursor(mut self, cursor: &str) -> Self {
+        self.cursor = Some(cursor.to_string());
+        self
+    }
+
+    fn cursor(&self) -> &Option<String> {
+        &self.cursor
+    }
+
+    fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    fn limit(&self) -> &Option<u64> {
+        &self.limit
+    }
+
+    fn with_order(mut self, order: &Order) -> Self {
+        self.order = Some(*order);
+        self
+    }
+
+    fn order(&self) -> &Option<Order> {
+        &self.order
+    }
+}
+
+impl<T: DeserializeOwned + Serialize + Clone + Send + Sync> StreamRequest for LinkPageRequest<T> {
+    type Resource = T;
+
+    fn validate_stream(&self) -> Result<()> {
+        self.validate()?;
+        crate::request::validate_stream_order(&self.order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Link, LinkPageRequest, LinkRequest, Links};
+    use crate::error::Error;
+    use crate::request::{PageRequest, Request};
+    use crate::resources::{Account, Operation};
+    use url::Url;
+
+    fn templated(href: &str) -> Link {
+        Link {
+            href: href.to_string(),
+            templated: true,
+        }
+    }
+
+    #[test]
+    fn test_expand_query_template() {
+        let link =
+            templated("https://horizon.stellar.org/accounts/GA/operations{?cursor,limit,order}");
+        let url = link.expand(&[]).unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/accounts/GA/operations",
+            url.as_str()
+        );
+        let url = link.expand(&[("limit", "5"), ("other", "x")]).unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/accounts/GA/operations?limit=5",
+            url.as_str()
+        );
+    }
+
+    #[test]
+    fn test_expand_simple_template() {
+        let link = templated("https://horizon.stellar.org/accounts/GA/data/{key}");
+        let url = link.expand(&[("key", "my key")]).unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/accounts/GA/data/my%20key",
+            url.as_str()
+        );
+        assert!(matches!(link.expand(&[]), Err(Error::InvalidLinkTemplate)));
+    }
+
+    #[test]
+    fn test_expand_not_templated() {
+        let link = Link {
+            href: "https://horizon.stellar.org/accounts/{GA}".to_string(),
+            templated: false,
+        };
+        let url = link.expand(&[("GA", "GB")]).unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/accounts/%7BGA%7D",
+            url.as_str()
+        );
+    }
+
+    #[test]
+    fn test_link_page_request_uri() {
+        let url = Url::parse("https://horizon.stellar.org/accounts/GA/operations").unwrap();
+        let host = Url::parse("https://horizon.stellar.org/").unwrap();
+        let req = LinkPageRequest::<Operation>::new(url)
+            .with_limit(10)
+            .with_order_desc();
+        let uri = req.uri(&host).unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/accounts/GA/operations?limit=10&order=desc",
+            uri.as_str()
+        );
+    }
+
+    #[test]
+    fn test_link_request_uri_uses_host_path() {
+        let host = Url::parse("https://horizon.stellar.org/horizon/").unwrap();
+        let url = Url::parse("https://horizon.stellar.org/accounts/GA/data/key?x=1").unwrap();
+        let uri = LinkRequest::<Account>::new(url).uri(&host).unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/horizon/accounts/GA/data/key?x=1",
+            uri.as_str()
+        );
+        let url = Url::parse("https://horizon.stellar.org/horizon/accounts/GA").unwrap();
+        let uri = LinkRequest::<Account>::new(url).uri(&host).unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/horizon/accounts/GA",
+            uri.as_str()
+        );
+    }
+
+    #[test]
+    fn test_link_request_rejects_other_hosts() {
+        let host = Url::parse("https://horizon.stellar.org/").unwrap();
+        for url in &[
+            "https://example.org/accounts/GA",
+            "http://horizon.stellar.org/accounts/GA",
+            "https://horizon.stellar.org:8000/accounts/GA",
+        ] {
+            let req = LinkRequest::<Account>::new(Url::parse(url).unwrap());
+            assert!(matches!(
+                req.uri(&host),
+                Err(Error::InvalidRequest { field: "url", .. })
+            ));
+            let req = LinkPageRequest::<Operation>::new(Url::parse(url).unwrap());
+            assert!(req.uri(&host).is_err());
+        }
+    }
+
+    #[test]
+    fn test_links() {
+        let account: Account =
+            serde_json::from_str(include_str!("../tests/fixtures/account.json")).unwrap();
+        let links = Links::from(&account.links);
+        assert_eq!(Some(&account.links.transactions), links.get("transactions"));
+        assert_eq!(Some(&account.links.self_), links.get("self"));
+        assert_eq!(8, links.iter().count());
+        let url = links.expand("transactions", &[("limit", "2")]).unwrap();
+        assert_eq!(
+            format!(
+                "https://horizon.stellar.org/accounts/{}/transactions?limit=2",
+                account.account_id
+            ),
+            url.as_str()
+        );
+        assert!(links.get("missing").is_none());
+        assert!(matches!(
+            links.expand("missing", &[]),
+            Err(Error::LinkNotFound(name)) if name == "missing"
+        ));
+
+        let req =
+            LinkPageRequest::<Operation>::from_link(links.get("operations").unwrap()).unwrap();
+        assert_eq!(
+            format!(
+                "https://horizon.stellar.org/accounts/{}/operations",
+                account.account_id
+            ),
+            req.url().as_str()
+        );
+    }
+}
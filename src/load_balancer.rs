@@ -0,0 +1,386 @@
+//! Health-aware load balancing over multiple Horizon instances.
+//!
+//! `LoadBalancedClient` wraps several clients and sends each request to
+//! the next healthy instance, in round-robin order. The health of the
+//! instances is updated by `LoadBalancedClient::check_health`, usually
+//! called periodically by spawning `LoadBalancedClient::run_health_checks`.
+//!
+//! ```rust,no_run
+//! use stellar_horizon::api;
+//! use stellar_horizon::client::{HorizonClient, HorizonHttpClient};
+//! use stellar_horizon::load_balancer::LoadBalancedClient;
+//!
+//! # async fn run() -> stellar_horizon::error::Result<()> {
+//! let client = LoadBalancedClient::new(vec![
+//!     HorizonHttpClient::new_from_str("https://horizon-1.example.org")?,
+//!     HorizonHttpClient::new_from_str("https://horizon-2.example.org")?,
+//! ]);
+//! let health_checks = client.clone();
+//! tokio::spawn(async move { health_checks.run_health_checks().await });
+//! let (_, root) = client.request(api::root::root()).await?;
+//! println!("Horizon Version = {}", root.horizon_version);
+//! # Ok(())
+//! # }
+//! ```
+use crate::api;
+use crate::client::{BoxEventStream, BoxMetaStream, HorizonClient, HorizonHttpClient};
+use crate::cursor::CursorStore;
+use crate::error::{Error, Result};
+use crate::headers::HeaderMap;
+use crate::request::{Request, StreamRequest};
+use futures::future::{self, BoxFuture};
+use futures::Stream;
+use std::marker::Unpin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default time between two health checks.
+const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default number of ledgers an instance can be behind and still be healthy.
+const DEFAULT_SYNC_TOLERANCE: u32 = 5;
+
+/// A client that balances requests between several Horizon instances.
+///
+/// An instance is healthy if its `/health` endpoint reports Stellar
+/// Core as synced, and if its latest ingested ledger is behind neither
+/// its own Stellar Core nor the most recent instance by more than the
+/// sync tolerance. Instances are considered healthy until the first
+/// health check, and an instance is removed until the next health
+/// check when a request to it fails with a recoverable error.
+///
+/// If no instance is healthy the requests are sent to all instances in
+/// round-robin order.
+pub struct LoadBalancedClient<C = HorizonHttpClient> {
+    clients: Arc<Vec<C>>,
+    healthy: Arc<Vec<AtomicBool>>,
+    next: Arc<AtomicUsize>,
+    health_check_interval: Duration,
+    sync_tolerance: u32,
+}
+
+impl<C> LoadBalancedClient<C>
+where
+    C: HorizonClient + Send + Sync,
+{
+    /// Creates a new load balanced client over `clients`.
+    pub fn new(clients: Vec<C>) -> LoadBalancedClient<C> {
+        let healthy = clients.iter().map(|_| AtomicBool::new(true)).collect();
+        LoadBalancedClient {
+            clients: Arc::new(clients),
+            healthy: Arc::new(healthy),
+            next: Arc::new(AtomicUsize::new(0)),
+            health_check_interval: DEFAULT_HEALTH_CHECK_INTERVAL,
+            sync_tolerance: DEFAULT_SYNC_TOLERANCE,
+        }
+    }
+
+    /// Sets the time between two health checks in `run_health_checks`.
+    pub fn with_health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
+    /// Returns the time between two health checks.
+    pub fn health_check_interval(&self) -> &Duration {
+        &self.health_check_interval
+    }
+
+    /// Sets the number of ledgers an instance can be behind and still be healthy.
+    pub fn with_sync_tolerance(mut self, ledgers: u32) -> Self {
+        self.sync_tolerance = ledgers;
+        self
+    }
+
+    /// Returns the number of ledgers an instance can be behind and still be healthy.
+    pub fn sync_tolerance(&self) -> u32 {
+        self.sync_tolerance
+    }
+
+    /// Returns the wrapped clients.
+    pub fn clients(&self) -> &[C] {
+        &self.clients
+    }
+
+    /// Returns `true` if the client at `index` is healthy.
+    pub fn is_healthy(&self, index: usize) -> bool {
+        self.healthy
+            .get(index)
+            .map(|healthy| healthy.load(Ordering::SeqCst))
+            .unwrap_or(false)
+    }
+
+    /// Checks the health of all the instances once.
+    pub async fn check_health(&self) {
+        let checks = self.clients.iter().map(|client| async move {
+            let (_, health) = client.request(api::root::health()).await?;
+            let (_, root) = client.request(api::root::root()).await?;
+            let ledger = i64::from(root.history_latest_ledger);
+            let core_ledger = i64::from(root.core_latest_ledger);
+            let synced =
+                health.core_synced && core_ledger - ledger <= i64::from(self.sync_tolerance);
+            Ok(if synced { Some(ledger) } else { None })
+        });
+        let ledgers: Vec<Option<i64>> = future::join_all(checks)
+            .await
+            .into_iter()
+            .map(|check: Result<Option<i64>>| check.ok().flatten())
+            .collect();
+        let latest = ledgers.iter().flatten().max().copied();
+        for (healthy, ledger) in self.healthy.iter().zip(ledgers) {
+            let synced = match (ledger, latest) {
+                (Some(ledger), Some(latest)) => latest - ledger <= i64::from(self.sync_tolerance),
+                _ => false,
+            };
+            healthy.store(synced, Ordering::SeqCst);
+        }
+    }
+
+    /// Checks the health of all the instances every health check interval.
+    ///
+    /// The returned future never completes, spawn it on the runtime.
+    pub async fn run_health_checks(&self) {
+        loop {
+            self.check_health().await;
+            tokio::time::delay_for(self.health_check_interval).await;
+        }
+    }
+
+    /// Returns the index of the next client to use.
+    fn select(&self) -> Result<usize> {
+        let count = self.clients.len();
+        if count == 0 {
+            return Err(Error::NoClientAvailable);
+        }
+        let start = self.next.fetch_add(1, Ordering::SeqCst) % count;
+        let index = (0..count)
+            .map(|offset| (start + offset) % count)
+            .find(|index| self.is_healthy(*index))
+            .unwrap_or(start);
+        Ok(index)
+    }
+}
+
+impl<C> Clone for LoadBalancedClient<C> {
+    fn clone(&self) -> Self {
+        LoadBalancedClient {
+            clients: self.clients.clone(),
+            healthy: self.healthy.clone(),
+            next: self.next.clone(),
+            health_check_interval: self.health_check_interval,
+            sync_tolerance: self.sync_tolerance,
+        }
+    }
+}
+
+impl<C> HorizonClient for LoadBalancedClient<C>
+where
+    C: HorizonClient + Send + Sync,
+{
+    fn request<'a, R: Request + 'a>(
+        &'a self,
+        req: R,
+    ) -> BoxFuture<'a, Result<(HeaderMap, R::Response)>> {
+        Box::pin(async move {
+            let index = self.select()?;
+            let response = self.clients[index].request(req).await;
+            if let Err(err) = &response {
+                if err.is_recoverable() {
+                    self.healthy[index].store(false, Ordering::SeqCst);
+                }
+            }
+            response
+        })
+    }
+
+    fn stream<'a, R: StreamRequest + 'static>(
+        &'a self,
+        req: R,
+    ) -> Result<Box<dyn Stream<Item = Result<R::Resource>> + 'static + Send + Unpin>> {
+        self.clients[self.select()?].stream(req)
+    }
+
+    fn stream_durable<'a, R: StreamRequest + 'static, S: CursorStore + Send + 'static>(
+        &'a self,
+        req: R,
+        store: S,
+    ) -> Result<Box<dyn Stream<Item = Result<R::Resource>> + 'static + Send + Unpin>> {
+        self.clients[self.select()?].stream_durable(req, store)
+    }
+
+    fn stream_events<R: StreamRequest + 'static>(
+        &self,
+        req: R,
+    ) -> Result<BoxEventStream<R::Resource>> {
+        self.clients[self.select()?].stream_events(req)
+    }
+
+    fn stream_with_meta<R: StreamRequest + 'static>(
+        &self,
+        req: R,
+    ) -> Result<BoxMetaStream<R::Resource>> {
+        self.clients[self.select()?].stream_with_meta(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LoadBalancedClient;
+    use crate::api;
+    use crate::client::HorizonClient;
+    use crate::error::{Error, Result};
+    use crate::testing::MockClient;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use url::Url;
+
+    const ROOT: &str = include_str!("../tests/fixtures/root.json");
+
+    /// A Horizon instance with its latest ledgers and health.
+    struct Instance {
+        ledger: i32,
+        core_ledger: i32,
+        core_synced: bool,
+        down: AtomicBool,
+    }
+
+    impl Instance {
+        fn new(ledger: i32) -> Instance {
+            Instance {
+                ledger,
+                core_ledger: ledger,
+                core_synced: true,
+                down: AtomicBool::new(false),
+            }
+        }
+
+        fn respond(&self, uri: &Url) -> Result<String> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(Error::UnexpectedStatus {
+                    status: 503,
+                    request_id: None,
+                });
+            }
+            if uri.path() == "/health" {
+                return Ok(serde_json::json!({
+                    "database_connected": true,
+                    "core_up": true,
+                    "core_synced": self.core_synced,
+                })
+                .to_string());
+            }
+            let mut root: serde_json::Value = serde_json::from_str(ROOT).unwrap();
+            root["history_latest_ledger"] = self.ledger.into();
+            root["core_latest_ledger"] = self.core_ledger.into();
+            Ok(root.to_string())
+        }
+
+        fn client(self: Arc<Self>) -> MockClient {
+            MockClient::new(move |uri| self.respond(uri))
+        }
+    }
+
+    fn instance_client(ledger: i32) -> MockClient {
+        Arc::new(Instance::new(ledger)).client()
+    }
+
+    /// Sends `count` requests, returns the number of requests received by each client.
+    async fn route(client: &LoadBalancedClient<MockClient>, count: usize) -> Vec<usize> {
+        for instance in client.clients() {
+            instance.uris.lock().unwrap().clear();
+        }
+        for _ in 0..count {
+            let _ = client.request(api::root::root()).await;
+        }
+        client
+            .clients()
+            .iter()
+            .map(|instance| instance.uris.lock().unwrap().len())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_between_healthy_clients() {
+        let client = LoadBalancedClient::new(vec![
+            instance_client(100),
+            instance_client(99),
+            instance_client(100),
+        ]);
+        client.check_health().await;
+        assert_eq!(vec![2, 2, 2], route(&client, 6).await);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_client_is_removed() {
+        let client = LoadBalancedClient::new(vec![
+            instance_client(100),
+            instance_client(80),
+            instance_client(100),
+        ])
+        .with_sync_tolerance(10);
+        client.check_health().await;
+        assert!(!client.is_healthy(1));
+        assert_eq!(vec![2, 0, 2], route(&client, 4).await);
+    }
+
+    #[tokio::test]
+    async fn test_client_behind_its_core_is_removed() {
+        let mut behind = Instance::new(100);
+        behind.core_ledger = 120;
+        let mut not_synced = Instance::new(100);
+        not_synced.core_synced = false;
+        let client = LoadBalancedClient::new(vec![
+            Arc::new(behind).client(),
+            Arc::new(not_synced).client(),
+            instance_client(100),
+        ]);
+        client.check_health().await;
+        assert_eq!(vec![0, 0, 3], route(&client, 3).await);
+    }
+
+    #[tokio::test]
+    async fn test_failed_client_is_removed_until_next_check() {
+        let first = Arc::new(Instance::new(100));
+        let client = LoadBalancedClient::new(vec![first.clone().client(), instance_client(100)]);
+        first.down.store(true, Ordering::SeqCst);
+        assert_eq!(vec![1, 3], route(&client, 4).await);
+        assert!(!client.is_healthy(0));
+
+        first.down.store(false, Ordering::SeqCst);
+        client.check_health().await;
+        assert!(client.is_healthy(0));
+        assert_eq!(vec![2, 2], route(&client, 4).await);
+    }
+
+    #[tokio::test]
+    async fn test_no_healthy_client_uses_all_clients() {
+        let mut first = Instance::new(100);
+        first.core_synced = false;
+        let mut second = Instance::new(100);
+        second.core_synced = false;
+        let client =
+            LoadBalancedClient::new(vec![Arc::new(first).client(), Arc::new(second).client()]);
+        client.check_health().await;
+        assert_eq!(vec![2, 2], route(&client, 4).await);
+    }
+
+    #[tokio::test]
+    async fn test_no_client() {
+        let client: LoadBalancedClient<MockClient> = LoadBalancedClient::new(Vec::new());
+        let response = client.request(api::root::root()).await;
+        assert!(matches!(response, Err(Error::NoClientAvailable)));
+    }
+
+    #[test]
+    fn test_configuration() {
+        let client = LoadBalancedClient::new(vec![instance_client(1)])
+            .with_health_check_interval(std::time::Duration::from_secs(5))
+            .with_sync_tolerance(2);
+        assert_eq!(
+            std::time::Duration::from_secs(5),
+            *client.health_check_interval()
+        );
+        assert_eq!(2, client.sync_tolerance());
+    }
+}
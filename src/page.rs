@@ -1,7 +1,9 @@
 //! Pagination page.
 use crate::link::Link;
+use crate::resources::PagingToken;
 use serde::de::{Deserialize, DeserializeOwned, Deserializer};
 use serde::ser::{Serialize, Serializer};
+use url::Url;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Page<T>
@@ -21,6 +23,39 @@ pub struct PageLinks {
     pub previous: Link,
 }
 
+impl_links!(PageLinks { "self" => self_, "next" => next, "prev" => previous });
+
+impl<T> Page<T>
+where
+    T: DeserializeOwned + Serialize + Clone,
+{
+    /// Returns the cursor of the `next` link, if any.
+    pub fn next_cursor(&self) -> Option<String> {
+        let links = self.links.as_ref()?;
+        let url = Url::parse(&links.next.href).ok()?;
+        url.query_pairs()
+            .find(|(key, _)| key == "cursor")
+            .map(|(_, value)| value.into_owned())
+            .filter(|cursor| !cursor.is_empty())
+    }
+}
+
+impl<T> Page<T>
+where
+    T: DeserializeOwned + Serialize + Clone + PagingToken,
+{
+    /// Returns the cursor after the last record of the page.
+    ///
+    /// This is the paging token of the last record, or the cursor of
+    /// the `next` link if the page is empty.
+    pub fn last_cursor(&self) -> Option<String> {
+        match self.records.last() {
+            Some(record) => Some(record.paging_token().to_string()),
+            None => self.next_cursor(),
+        }
+    }
+}
+
 impl<'de, T> Deserialize<'de> for Page<T>
 where
     T: DeserializeOwned + Serialize + Clone,
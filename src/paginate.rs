@@ -0,0 +1,349 @@
+//! Helpers to fetch all the pages of a request.
+use crate::client::HorizonClient;
+use crate::error::{Error, Result};
+use crate::headers::HeaderMap;
+use crate::page::Page;
+use crate::request::{Order, PageRequest, Request};
+use crate::resources::PagingToken;
+use crate::toid::Toid;
+use futures::future::BoxFuture;
+use futures::stream::{self, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Returns a stream of all the records of `request`.
+///
+/// The stream starts from the cursor of `request` and ends on the
+/// first empty page, or after an error. By default the following page
+/// is fetched only after all the records of the current page are
+/// consumed, use `Paginate::with_prefetch` to fetch pages ahead.
+pub fn paginate<'a, C, R, T>(client: &'a C, request: R) -> Paginate<'a, C, R, T>
+where
+    C: HorizonClient,
+    R: PageRequest<Response = Page<T>> + Clone + 'a,
+    T: PagingToken + DeserializeOwned + Serialize + Clone,
+{
+    Paginate {
+        client,
+        next_request: Some(request),
+        pending: None,
+        pages: VecDeque::new(),
+        error: None,
+        prefetch: 1,
+    }
+}
+
+type ResponseFuture<'a, R> = BoxFuture<'a, Result<(HeaderMap, <R as Request>::Response)>>;
+
+/// A `Stream` over all the records of a page request.
+#[must_use = "Streams are lazy and do nothing unless polled"]
+pub struct Paginate<'a, C, R, T>
+where
+    R: Request,
+{
+    client: &'a C,
+    next_request: Option<R>,
+    pending: Option<(R, ResponseFuture<'a, R>)>,
+    pages: VecDeque<VecDeque<T>>,
+    /// The error of a prefetched page, returned after the buffered records.
+    error: Option<Error>,
+    prefetch: usize,
+}
+
+impl<'a, C, R, T> Paginate<'a, C, R, T>
+where
+    R: Request,
+{
+    /// Sets the number of pages to buffer, including the page being consumed.
+    ///
+    /// With a `depth` greater than 1 the request for the next page is
+    /// sent as soon as the current page is received, while its records
+    /// are still being consumed. Defaults to 1, values lower than 1 are
+    /// treated as 1.
+    pub fn with_prefetch(mut self, depth: usize) -> Self {
+        self.prefetch = depth.max(1);
+        self
+    }
+
+    /// Returns the number of pages buffered.
+    pub fn prefetch(&self) -> usize {
+        self.prefetch
+    }
+}
+
+// The fields are never pinned.
+impl<'a, C, R, T> Unpin for Paginate<'a, C, R, T> where R: Request {}
+
+impl<'a, C, R, T> Stream for Paginate<'a, C, R, T>
+where
+    C: HorizonClient,
+    R: PageRequest<Response = Page<T>> + Clone + 'a,
+    T: PagingToken + DeserializeOwned + Serialize + Clone,
+{
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some((request, mut response)) = this.pending.take() {
+                match response.as_mut().poll(cx) {
+                    Poll::Pending => this.pending = Some((request, response)),
+                    Poll::Ready(Err(err)) => {
+                        this.next_request = None;
+                        this.error = Some(err);
+                    }
+                    Poll::Ready(Ok((_, page))) => {
+                        if let Some(last) = page.records.last() {
+                            this.next_request = Some(request.with_cursor(last.paging_token()));
+                            this.pages.push_back(page.records.into());
+                        }
+                    }
+                }
+            }
+            if this.pending.is_none() && this.pages.len() < this.prefetch {
+                if let Some(request) = this.next_request.take() {
+                    let response = this.client.request(request.clone());
+                    this.pending = Some((request, response));
+                    continue;
+                }
+            }
+            if let Some(page) = this.pages.front_mut() {
+                let record = page.pop_front();
+                if page.is_empty() {
+                    this.pages.pop_front();
+                }
+                if let Some(record) = record {
+                    return Poll::Ready(Some(Ok(record)));
+                }
+                continue;
+            }
+            if let Some(err) = this.error.take() {
+                return Poll::Ready(Some(Err(err)));
+            }
+            if this.pending.is_some() {
+                return Poll::Pending;
+            }
+            return Poll::Ready(None);
+        }
+    }
+}
+
+/// Merges `streams` into one stream ordered by total order id.
+///
+/// Each stream must already be sorted by paging token in `order`, and
+/// the paging tokens must be total order ids, like the ones of
+/// transactions and operations. Records with the same paging token in
+/// more than one stream, for example a payment between two of the
+/// streams' accounts, are returned once.
+///
+/// Only the next record of each stream is kept, so when merging
+/// streams created with `paginate` at most one page per stream is
+/// buffered.
+pub fn merge_by_toid<T, S>(streams: Vec<S>, order: Order) -> impl Stream<Item = Result<T>>
+where
+    T: PagingToken,
+    S: Stream<Item = Result<T>> + Unpin,
+{
+    let sources: Vec<MergeSource<T, S>> = streams
+        .into_iter()
+        .map(|stream| MergeSource {
+            stream,
+            next: None,
+            done: false,
+        })
+        .collect();
+    stream::unfold(sources, move |mut sources| async move {
+        for source in sources.iter_mut() {
+            if source.next.is_some() || source.done {
+                continue;
+            }
+            match source.stream.next().await {
+                None => source.done = true,
+                Some(Err(err)) => return Some((Err(err), sources)),
+                Some(Ok(record)) => match record.paging_token().parse::<Toid>() {
+                    Ok(toid) => source.next = Some((toid, record)),
+                    Err(err) => return Some((Err(err), sources)),
+                },
+            }
+        }
+        let toids = sources
+            .iter()
+            .filter_map(|source| source.next.as_ref().map(|(toid, _)| *toid));
+        let toid = match order {
+            Order::Ascending => toids.min()?,
+            Order::Descending => toids.max()?,
+        };
+        let mut record = None;
+        for source in sources.iter_mut() {
+            if source.next.as_ref().map(|(t, _)| *t) == Some(toid) {
+                record = source.next.take().map(|(_, r)| r);
+            }
+        }
+        record.map(|record| (Ok(record), sources))
+    })
+}
+
+struct MergeSource<T, S> {
+    stream: S,
+    next: Option<(Toid, T)>,
+    done: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_by_toid, paginate};
+    use crate::api;
+    use crate::error::{Error, Result};
+    use crate::request::Order;
+    use crate::resources::PagingToken;
+    use crate::testing::MockClient;
+    use crate::toid::Toid;
+    use futures::stream::{self, StreamExt, TryStreamExt};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const LEDGERS_PAGE: &str = include_str!("../tests/fixtures/all_ledgers.json");
+
+    #[derive(Debug, PartialEq)]
+    struct Record {
+        paging_token: String,
+    }
+
+    impl PagingToken for Record {
+        fn paging_token(&self) -> &str {
+            &self.paging_token
+        }
+    }
+
+    fn records(toids: &[Toid]) -> Vec<Result<Record>> {
+        toids
+            .iter()
+            .map(|toid| {
+                Ok(Record {
+                    paging_token: toid.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    async fn merged_toids(streams: Vec<Vec<Result<Record>>>, order: Order) -> Vec<Toid> {
+        let streams = streams.into_iter().map(stream::iter).collect();
+        merge_by_toid(streams, order)
+            .map(|record| record.unwrap().paging_token.parse::<Toid>().unwrap())
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_merge_by_toid_ascending() {
+        let first = records(&[Toid::new(1, 1, 1), Toid::new(3, 1, 1), Toid::new(3, 2, 1)]);
+        let second = records(&[Toid::new(2, 1, 1), Toid::new(3, 1, 2)]);
+        let toids = merged_toids(vec![first, second, Vec::new()], Order::Ascending).await;
+        assert_eq!(
+            vec![
+                Toid::new(1, 1, 1),
+                Toid::new(2, 1, 1),
+                Toid::new(3, 1, 1),
+                Toid::new(3, 1, 2),
+                Toid::new(3, 2, 1),
+            ],
+            toids
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_by_toid_descending() {
+        let first = records(&[Toid::new(3, 1, 1), Toid::new(1, 1, 1)]);
+        let second = records(&[Toid::new(2, 1, 1)]);
+        let toids = merged_toids(vec![first, second], Order::Descending).await;
+        assert_eq!(
+            vec![Toid::new(3, 1, 1), Toid::new(2, 1, 1), Toid::new(1, 1, 1)],
+            toids
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_by_toid_skips_duplicates() {
+        let first = records(&[Toid::new(1, 1, 1), Toid::new(2, 1, 1)]);
+        let second = records(&[Toid::new(2, 1, 1), Toid::new(3, 1, 1)]);
+        let toids = merged_toids(vec![first, second], Order::Ascending).await;
+        assert_eq!(
+            vec![Toid::new(1, 1, 1), Toid::new(2, 1, 1), Toid::new(3, 1, 1)],
+            toids
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_by_toid_invalid_paging_token() {
+        let invalid = vec![Ok(Record {
+            paging_token: "now".to_string(),
+        })];
+        let mut merged = Box::pin(merge_by_toid(vec![stream::iter(invalid)], Order::Ascending));
+        assert!(matches!(merged.next().await, Some(Err(Error::InvalidToid))));
+    }
+
+    #[tokio::test]
+    async fn test_paginate_follows_cursor() {
+        let client = MockClient::pages(vec![LEDGERS_PAGE, LEDGERS_PAGE]);
+        let ledgers: Vec<_> = paginate(&client, api::ledgers::all())
+            .try_collect()
+            .await
+            .unwrap();
+        let uris = client.uris.lock().unwrap();
+        assert_eq!(3, uris.len());
+        let query: HashMap<_, _> = uris[1].query_pairs().into_owned().collect();
+        let last = &ledgers[ledgers.len() / 2 - 1];
+        assert_eq!(Some(&last.paging_token), query.get("cursor"));
+    }
+
+    #[tokio::test]
+    async fn test_paginate_fetches_next_page_when_current_is_consumed() {
+        let client = MockClient::pages(vec![LEDGERS_PAGE, LEDGERS_PAGE]);
+        let mut ledgers = paginate(&client, api::ledgers::all());
+        assert_eq!(1, ledgers.prefetch());
+        assert!(ledgers.next().await.unwrap().is_ok());
+        assert_eq!(1, client.uris.lock().unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_paginate_with_prefetch() {
+        let client = MockClient::pages(vec![LEDGERS_PAGE, LEDGERS_PAGE, LEDGERS_PAGE]);
+        let mut ledgers = paginate(&client, api::ledgers::all()).with_prefetch(2);
+        assert!(ledgers.next().await.unwrap().is_ok());
+        assert_eq!(2, client.uris.lock().unwrap().len());
+        let remaining: Vec<_> = ledgers.try_collect().await.unwrap();
+        let page: crate::page::Page<crate::resources::Ledger> =
+            serde_json::from_str(LEDGERS_PAGE).unwrap();
+        assert_eq!(3 * page.records.len() - 1, remaining.len());
+        assert_eq!(4, client.uris.lock().unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_paginate_with_prefetch_returns_buffered_records_before_error() {
+        let requests = AtomicUsize::new(0);
+        let client = MockClient::new(move |_| match requests.fetch_add(1, Ordering::SeqCst) {
+            0 => Ok(LEDGERS_PAGE.to_string()),
+            _ => Err(Error::UnexpectedStatus {
+                status: 503,
+                request_id: None,
+            }),
+        });
+        let ledgers: Vec<_> = paginate(&client, api::ledgers::all())
+            .with_prefetch(2)
+            .collect()
+            .await;
+        let page: crate::page::Page<crate::resources::Ledger> =
+            serde_json::from_str(LEDGERS_PAGE).unwrap();
+        assert_eq!(page.records.len() + 1, ledgers.len());
+        let (last, records) = ledgers.split_last().unwrap();
+        assert!(records.iter().all(|ledger| ledger.is_ok()));
+        assert!(matches!(
+            last,
+            Err(Error::UnexpectedStatus { status: 503, .. })
+        ));
+        assert_eq!(2, client.uris.lock().unwrap().len());
+    }
+}
@@ -0,0 +1,29 @@
+//! Commonly used traits and types.
+//!
+//! The prelude re-exports the client, the request traits needed to
+//! call builder methods such as `with_cursor`, and the types needed to
+//! create requests and handle responses:
+//!
+//!  * `HorizonClient` and `HorizonHttpClient`
+//!  * `Request`, `PageRequest`, `StreamRequest`, and `Order`
+//!  * `Page`
+//!  * The `api` module with all request builders
+//!  * `Error`
+//!  * `Asset` and `PublicKey` from `stellar-base`, used to build requests
+//!
+//! ```rust
+//! use stellar_horizon::prelude::*;
+//!
+//! # async fn run() -> Result<(), Error> {
+//! let client = HorizonHttpClient::public()?;
+//! let request = api::ledgers::all().with_order(&Order::Descending).with_limit(1);
+//! let (_, page) = client.request(request).await?;
+//! # Ok(())
+//! # }
+//! ```
+pub use crate::api;
+pub use crate::client::{HorizonClient, HorizonHttpClient};
+pub use crate::error::Error;
+pub use crate::page::Page;
+pub use crate::request::{Order, PageRequest, Request, RequestExt, StreamRequest};
+pub use stellar_base::{Asset, PublicKey};
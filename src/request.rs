@@ -1,10 +1,16 @@
 //! Request traits.
 use crate::api::Join;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::page::Page;
+use crate::resources::PagingToken;
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 use stellar_base::asset::{Asset, CreditAssetType};
 use url::Url;
 
+/// The maximum number of records Horizon returns in a page.
+pub const MAX_LIMIT: u64 = 200;
+
 /// Records order.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Order {
@@ -19,19 +25,84 @@ pub trait Request: Send + Sync {
     /// The type of this request response.
     type Response: DeserializeOwned;
 
+    /// Returns the form urlencoded body of `POST` requests.
+    ///
+    /// Requests with a body are sent as `POST` with the
+    /// `application/x-www-form-urlencoded` content type, the others as
+    /// `GET`. Defaults to no body.
     fn post_body(&self) -> Result<Option<String>> {
         Ok(None)
     }
 
+    /// Returns additional headers to send with the request.
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Checks that the request parameters are accepted by Horizon.
+    ///
+    /// Clients call it before sending the request, or opening the
+    /// stream, to fail without a round trip to Horizon. Defaults to
+    /// accepting all requests.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Returns the request uri.
+    ///
+    /// `host` is the Horizon base url and its path ends with `/`. Join
+    /// relative paths, such as `accounts`, to keep the path prefix of
+    /// Horizon instances served behind a reverse proxy.
     fn uri(&self, host: &Url) -> Result<Url>;
 }
 
 /// Horizon page request trait.
 pub trait PageRequest: Request {
     /// Set the request cursor.
+    ///
+    /// Records are returned starting after the cursor, in the request
+    /// order. If no order is set Horizon defaults to ascending order, so
+    /// the records returned are the ones *newer* than the cursor. Use
+    /// `with_order_desc` to get the records before the cursor.
     fn with_cursor(self, cursor: &str) -> Self;
 
+    /// Set the request cursor to `now`.
+    ///
+    /// When streaming, this only returns events created after the
+    /// stream is opened, instead of replaying the history.
+    fn with_cursor_now(self) -> Self
+    where
+        Self: Sized,
+    {
+        self.with_cursor("now")
+    }
+
+    /// Set the request cursor after the last record of `page`.
+    ///
+    /// Use it to switch from paging through the history to streaming
+    /// the new records: the stream starts with the record following
+    /// the last record of `page`, so there is no gap or overlap between
+    /// the two. The page must be in ascending order, and all its
+    /// records processed, for the boundary to be exactly-once. If the
+    /// page is empty the cursor of its `next` link is used, and the
+    /// request is returned unchanged if there is none.
+    ///
+    /// Across restarts the guarantee depends on when the application
+    /// saves its cursor: saving it after processing a record, like
+    /// `HorizonClient::stream_durable`, is at-least-once, the record is
+    /// delivered again if the application stops in between, while
+    /// saving it before is at-most-once.
+    fn with_cursor_after<T>(self, page: &Page<T>) -> Self
+    where
+        Self: Sized,
+        T: DeserializeOwned + Serialize + Clone + PagingToken,
+    {
+        match page.last_cursor() {
+            Some(cursor) => self.with_cursor(&cursor),
+            None => self,
+        }
+    }
+
     /// Returns the request cursor.
     fn cursor(&self) -> &Option<String>;
 
@@ -44,6 +115,22 @@ pub trait PageRequest: Request {
     /// Set the request order.
     fn with_order(self, direction: &Order) -> Self;
 
+    /// Set the request order to ascending, the Horizon default.
+    fn with_order_asc(self) -> Self
+    where
+        Self: Sized,
+    {
+        self.with_order(&Order::Ascending)
+    }
+
+    /// Set the request order to descending.
+    fn with_order_desc(self) -> Self
+    where
+        Self: Sized,
+    {
+        self.with_order(&Order::Descending)
+    }
+
     /// Returns the request order.
     fn order(&self) -> &Option<Order>;
 }
@@ -52,10 +139,151 @@ pub trait PageRequest: Request {
 pub trait StreamRequest: Request + Unpin {
     /// The type of streamed resources.
     type Resource: DeserializeOwned + Send + Sync;
+
+    /// Checks that the request parameters can be streamed.
+    ///
+    /// Clients call it before opening the stream. Defaults to
+    /// `Request::validate`, page requests also reject the descending
+    /// order.
+    fn validate_stream(&self) -> Result<()> {
+        self.validate()
+    }
+}
+
+/// Extension methods for all requests.
+pub trait RequestExt: Request + Sized {
+    /// Sends the request with the additional header `name`.
+    ///
+    /// The header replaces the headers with the same name set by the
+    /// request or the client, such as `X-Client-Name`.
+    fn with_header<S: Into<String>>(self, name: &'static str, value: S) -> WithHeaders<Self> {
+        WithHeaders {
+            request: self,
+            headers: Vec::new(),
+        }
+        .with_header(name, value)
+    }
+}
+
+impl<R: Request> RequestExt for R {}
+
+/// A request sent with additional headers, see `RequestExt::with_header`.
+#[derive(Debug, Clone)]
+pub struct WithHeaders<R> {
+    request: R,
+    headers: Vec<(&'static str, String)>,
+}
+
+impl<R> WithHeaders<R> {
+    /// Adds the header `name`, replacing the previous value of `name`.
+    pub fn with_header<S: Into<String>>(mut self, name: &'static str, value: S) -> Self {
+        self.headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// Returns the wrapped request.
+    pub fn request(&self) -> &R {
+        &self.request
+    }
+
+    /// Returns the wrapped request, without the additional headers.
+    pub fn into_request(self) -> R {
+        self.request
+    }
+}
+
+impl<R: Request> Request for WithHeaders<R> {
+    type Response = R::Response;
+
+    fn validate(&self) -> Result<()> {
+        self.request.validate()
+    }
+
+    fn post_body(&self) -> Result<Option<String>> {
+        self.request.post_body()
+    }
+
+    fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = self.request.headers();
+        headers.retain(|(name, _)| {
+            !self
+                .headers
+                .iter()
+                .any(|(other, _)| other.eq_ignore_ascii_case(name))
+        });
+        headers.extend(self.headers.iter().cloned());
+        headers
+    }
+
+    fn uri(&self, host: &Url) -> Result<Url> {
+        self.request.uri(host)
+    }
+}
+
+impl<R: PageRequest> PageRequest for WithHeaders<R> {
+    fn with_cursor(mut self, cursor: &str) -> Self {
+        self.request = self.request.with_cursor(cursor);
+        self
+    }
+
+    fn cursor(&self) -> &Option<String> {
+        self.request.cursor()
+    }
+
+    fn with_limit(mut self, limit: u64) -> Self {
+        self.request = self.request.with_limit(limit);
+        self
+    }
+
+    fn limit(&self) -> &Option<u64> {
+        self.request.limit()
+    }
+
+    fn with_order(mut self, order: &Order) -> Self {
+        self.request = self.request.with_order(order);
+        self
+    }
+
+    fn order(&self) -> &Option<Order> {
+        self.request.order()
+    }
+}
+
+impl<R: StreamRequest> StreamRequest for WithHeaders<R> {
+    type Resource = R::Resource;
+
+    fn validate_stream(&self) -> Result<()> {
+        self.request.validate_stream()
+    }
+}
+
+/// Checks that `limit` is between 1 and `MAX_LIMIT`, the limits accepted by Horizon.
+pub(crate) fn validate_limit(limit: u64) -> Result<()> {
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(Error::InvalidRequest {
+            field: "limit",
+            reason: format!("must be between 1 and {}", MAX_LIMIT),
+        });
+    }
+    Ok(())
+}
+
+/// Checks that `order` is ascending, Horizon closes descending streams
+/// once it sent the existing resources.
+pub(crate) fn validate_stream_order(order: &Option<Order>) -> Result<()> {
+    if *order == Some(Order::Descending) {
+        return Err(Error::InvalidRequest {
+            field: "order",
+            reason: "streams must be in ascending order".to_string(),
+        });
+    }
+    Ok(())
 }
 
 pub(crate) trait UrlPageRequestExt: Sized {
-    fn append_pagination_params<R: PageRequest>(self, req: &R) -> Self;
+    fn append_pagination_params<R: PageRequest>(self, req: &R) -> Result<Self>;
     fn append_asset_params(self, asset: &Asset, prefix: Option<&str>) -> Self;
     fn append_canonical_asset_params(self, key: &str, asset: &Asset) -> Self;
     fn append_query_param(self, key: &str, value: &str) -> Self;
@@ -64,7 +292,7 @@ pub(crate) trait UrlPageRequestExt: Sized {
 }
 
 impl UrlPageRequestExt for Url {
-    fn append_pagination_params<R: PageRequest>(mut self, req: &R) -> Self {
+    fn append_pagination_params<R: PageRequest>(mut self, req: &R) -> Result<Self> {
         {
             let mut query = self.query_pairs_mut();
             if let Some(cursor) = req.cursor() {
@@ -72,6 +300,7 @@ impl UrlPageRequestExt for Url {
             }
 
             if let Some(limit) = req.limit() {
+                validate_limit(*limit)?;
                 query.append_pair("limit", &limit.to_string());
             }
 
@@ -80,7 +309,7 @@ impl UrlPageRequestExt for Url {
                 query.append_pair("order", &order);
             }
         }
-        self
+        Ok(self)
     }
 
     fn append_asset_params(mut self, asset: &Asset, prefix: Option<&str>) -> Self {
@@ -198,6 +427,19 @@ macro_rules! impl_page_request {
     };
 }
 
+macro_rules! impl_page_stream_request {
+    ($name:path, $resource:ty) => {
+        impl StreamRequest for $name {
+            type Resource = $resource;
+
+            fn validate_stream(&self) -> Result<()> {
+                self.validate()?;
+                crate::request::validate_stream_order(PageRequest::order(self))
+            }
+        }
+    };
+}
+
 macro_rules! impl_include_failed {
     () => {
         pub fn with_include_failed(mut self, include_failed: bool) -> Self {
@@ -1,8 +1,13 @@
-use crate::link::Link;
-use crate::resources::Asset;
+use crate::error::Result;
+use crate::link::{Link, LinkPageRequest, LinkRequest};
+use crate::resources::{
+    Asset, Effect, LedgerEntry, LedgerId, Offer, Operation, Payment, Trade, Transaction,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap as Map;
+use std::convert::TryFrom;
+use stellar_base::amount::Amount;
 
 /// User accounts on the network.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -48,6 +53,107 @@ pub struct Account {
     pub num_sponsored: i64,
 }
 
+impl Account {
+    /// Returns the account data entries, decoded from base64.
+    ///
+    /// Each entry is decoded independently, so a malformed value only
+    /// results in an error for its own key.
+    pub fn data_entries(&self) -> Map<String, Result<Vec<u8>>> {
+        self.data
+            .iter()
+            .map(|(key, value)| (key.clone(), base64::decode(value).map_err(Into::into)))
+            .collect()
+    }
+
+    /// Returns the balance of the native asset.
+    pub fn native_balance(&self) -> Option<&Balance> {
+        self.balances
+            .iter()
+            .find(|balance| balance.asset.asset_type == "native")
+    }
+
+    /// Returns the balance of `asset`, if the account has a trustline to it.
+    pub fn balance_for(&self, asset: &Asset) -> Option<&Balance> {
+        self.balances
+            .iter()
+            .filter(|balance| !balance.is_liquidity_pool_shares())
+            .find(|balance| balance.asset == *asset)
+    }
+
+    /// Returns the weight of the signer with `key`, if it is a signer of this account.
+    ///
+    /// The master key is included in the signers, with the account id as key.
+    pub fn signing_weight_for(&self, key: &str) -> Option<u8> {
+        self.signers
+            .iter()
+            .find(|signer| signer.key == key)
+            .map(Signer::weight)
+    }
+
+    /// Returns `true` if the signatures of `signers_present` meet the `threshold` of this account.
+    ///
+    /// Keys that are not signers of this account are ignored, and each
+    /// signer is counted once. Like Stellar Core, at least one signer
+    /// of the account must be present even if the threshold is 0.
+    pub fn can_meet_threshold(&self, threshold: Threshold, signers_present: &[&str]) -> bool {
+        let mut present = self
+            .signers
+            .iter()
+            .filter(|signer| signers_present.contains(&signer.key.as_str()))
+            .peekable();
+        if present.peek().is_none() {
+            return false;
+        }
+        let weight: u32 = present.map(|signer| u32::from(signer.weight())).sum();
+        weight >= u32::from(self.thresholds.threshold(threshold))
+    }
+
+    /// Returns a request for this account's transactions, following its link.
+    pub fn transactions_request(&self) -> Result<LinkPageRequest<Transaction>> {
+        LinkPageRequest::from_link(&self.links.transactions)
+    }
+
+    /// Returns a request for this account's operations, following its link.
+    pub fn operations_request(&self) -> Result<LinkPageRequest<Operation>> {
+        LinkPageRequest::from_link(&self.links.operations)
+    }
+
+    /// Returns a request for this account's payments, following its link.
+    pub fn payments_request(&self) -> Result<LinkPageRequest<Payment>> {
+        LinkPageRequest::from_link(&self.links.payments)
+    }
+
+    /// Returns a request for this account's effects, following its link.
+    pub fn effects_request(&self) -> Result<LinkPageRequest<Effect>> {
+        LinkPageRequest::from_link(&self.links.effects)
+    }
+
+    /// Returns a request for this account's offers, following its link.
+    pub fn offers_request(&self) -> Result<LinkPageRequest<Offer>> {
+        LinkPageRequest::from_link(&self.links.offers)
+    }
+
+    /// Returns a request for this account's trades, following its link.
+    pub fn trades_request(&self) -> Result<LinkPageRequest<Trade>> {
+        LinkPageRequest::from_link(&self.links.trades)
+    }
+
+    /// Returns a request for this account's data entry `key`, following its link.
+    pub fn data_request(&self, key: &str) -> Result<LinkRequest<AccountData>> {
+        LinkRequest::from_link(&self.links.data, &[("key", key)])
+    }
+}
+
+impl LedgerEntry for Account {
+    fn last_modified_ledger(&self) -> Option<LedgerId> {
+        LedgerId::try_from(self.last_modified_ledger).ok()
+    }
+
+    fn last_modified_time(&self) -> Option<DateTime<Utc>> {
+        self.last_modified_time
+    }
+}
+
 /// Links for an Account.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AccountLinks {
@@ -70,6 +176,17 @@ pub struct AccountLinks {
     pub data: Link,
 }
 
+impl_links!(AccountLinks {
+    "self" => self_,
+    "transactions" => transactions,
+    "operations" => operations,
+    "payments" => payments,
+    "effects" => effects,
+    "offers" => offers,
+    "trades" => trades,
+    "data" => data,
+});
+
 /// Thresholds for different access levels.
 ///
 ///  * Low Threshold: allow trust, bump sequence
@@ -88,6 +205,28 @@ pub struct AccountThresholds {
     pub high_threshold: u8,
 }
 
+/// The access levels of operations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Threshold {
+    /// Low threshold.
+    Low,
+    /// Medium threshold.
+    Medium,
+    /// High threshold.
+    High,
+}
+
+impl AccountThresholds {
+    /// Returns the weight required for the `threshold` access level.
+    pub fn threshold(&self, threshold: Threshold) -> u8 {
+        match threshold {
+            Threshold::Low => self.low_threshold,
+            Threshold::Medium => self.medium_threshold,
+            Threshold::High => self.high_threshold,
+        }
+    }
+}
+
 /// Enabling/disabling of certain asset issuet privilege.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AccountFlags {
@@ -98,6 +237,9 @@ pub struct AccountFlags {
     pub auth_revocable: bool,
     /// If `true`, this account can freeze the balance of a holder of an asset issued by this account.
     pub auth_immutable: bool,
+    /// If `true`, trustlines created for assets issued by this account can be clawed back.
+    #[serde(default)]
+    pub auth_clawback_enabled: bool,
 }
 
 /// Asset balance.
@@ -108,22 +250,119 @@ pub struct Balance {
     /// The maximum amount of the asset the account is willing to accept.
     pub limit: Option<String>,
     /// The sum of all buy offers owned by this account for this asset.
-    pub buying_liabilities: String,
+    ///
+    /// `None` for liquidity pool shares balances.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buying_liabilities: Option<String>,
     /// The sum of all sell offers owned by this account for this asset.
-    pub selling_liabilities: String,
+    ///
+    /// `None` for liquidity pool shares balances.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selling_liabilities: Option<String>,
     /// Ledger when the balance was last changed.
     pub last_modified_ledger: Option<u32>,
     /// Flag to indicate if the account is authorized to hold asset.
     pub is_authorized: Option<bool>,
     /// Flag to indicate if the account is authorized to maintain liabilities.
     pub is_authorized_to_maintain_liabilities: Option<bool>,
+    /// Flag to indicate if the issuer can clawback the asset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_clawback_enabled: Option<bool>,
     /// The account sponsoring this trustline.
     pub sponsor: Option<String>,
+    /// The liquidity pool id, if this balance holds liquidity pool shares.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liquidity_pool_id: Option<String>,
     /// The asset.
+    ///
+    /// The asset type is `liquidity_pool_shares` for liquidity pool
+    /// shares balances, and has no code or issuer.
     #[serde(flatten)]
     pub asset: Asset,
 }
 
+impl LedgerEntry for Balance {
+    fn last_modified_ledger(&self) -> Option<LedgerId> {
+        self.last_modified_ledger
+            .and_then(|ledger| LedgerId::try_from(ledger).ok())
+    }
+}
+
+/// What a balance holds, see `Balance::kind`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BalanceKind<'a> {
+    /// Native lumens.
+    Native { balance: &'a str },
+    /// A credit asset held through a trustline.
+    Credit {
+        asset: &'a Asset,
+        balance: &'a str,
+        limit: Option<&'a str>,
+    },
+    /// Liquidity pool shares held through a pool share trustline.
+    LiquidityPoolShares {
+        liquidity_pool_id: &'a str,
+        balance: &'a str,
+        limit: Option<&'a str>,
+    },
+}
+
+impl Balance {
+    /// Returns `true` if this balance holds liquidity pool shares.
+    pub fn is_liquidity_pool_shares(&self) -> bool {
+        self.liquidity_pool_id.is_some()
+    }
+
+    /// Returns what this balance holds.
+    pub fn kind(&self) -> BalanceKind<'_> {
+        let balance = self.balance.as_str();
+        let limit = self.limit.as_deref();
+        if let Some(liquidity_pool_id) = &self.liquidity_pool_id {
+            return BalanceKind::LiquidityPoolShares {
+                liquidity_pool_id,
+                balance,
+                limit,
+            };
+        }
+        if self.asset.asset_type == "native" {
+            return BalanceKind::Native { balance };
+        }
+        BalanceKind::Credit {
+            asset: &self.asset,
+            balance,
+            limit,
+        }
+    }
+
+    /// Returns the number of units the account holds.
+    pub fn balance_amount(&self) -> Result<Amount> {
+        Ok(self.balance.parse()?)
+    }
+
+    /// Returns the trustline limit, native balances have no limit.
+    pub fn limit_amount(&self) -> Result<Option<Amount>> {
+        Ok(self.limit.as_deref().map(str::parse).transpose()?)
+    }
+
+    /// Returns the buying liabilities, if any.
+    pub fn buying_liabilities_amount(&self) -> Result<Option<Amount>> {
+        Ok(self
+            .buying_liabilities
+            .as_deref()
+            .map(str::parse)
+            .transpose()?)
+    }
+
+    /// Returns the selling liabilities, if any.
+    pub fn selling_liabilities_amount(&self) -> Result<Option<Amount>> {
+        Ok(self
+            .selling_liabilities
+            .as_deref()
+            .map(str::parse)
+            .transpose()?)
+    }
+}
+
 /// A valid signer for the account.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AccountSigner {
@@ -156,6 +395,8 @@ pub struct AccountSignerLinks {
     pub account: Link,
 }
 
+impl_links!(AccountSignerLinks { "account" => account });
+
 /// Account signer
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Signer {
@@ -165,7 +406,28 @@ pub struct Signer {
     pub key: String,
     /// The signer type.
     #[serde(rename = "type")]
-    pub type_: String,
+    pub type_: SignerType,
     /// The account sponsoring this signer base reserve.
     pub sponsor: Option<String>,
 }
+
+impl Signer {
+    /// Returns the signer weight, clamped to the `0..=255` range of weights.
+    pub fn weight(&self) -> u8 {
+        self.weight.max(0).min(i32::from(u8::MAX)) as u8
+    }
+}
+
+impl_str_enum! {
+    /// The type of a signer key.
+    SignerType {
+        /// An ed25519 public key, the key is an account id.
+        Ed25519PublicKey => "ed25519_public_key",
+        /// The hash of a pre-authorized transaction.
+        PreAuthTx => "preauth_tx",
+        /// A sha256 hash whose preimage is the signature.
+        Sha256Hash => "sha256_hash",
+        /// An ed25519 public key with a signed payload.
+        Ed25519SignedPayload => "ed25519_signed_payload",
+    }
+}
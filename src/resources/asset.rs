@@ -19,3 +19,5 @@ pub struct AssetStat {
 pub struct AssetStatLinks {
     pub toml: Link,
 }
+
+impl_links!(AssetStatLinks { "toml" => toml });
@@ -1,10 +1,15 @@
 use crate::error::{Error, Result};
+use crate::link::Links;
+use crate::resources::{LedgerEntry, LedgerId};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use stellar_base::claim::ClaimPredicate;
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ClaimableBalance {
+    #[serde(rename = "_links", default)]
+    pub links: Links,
     pub id: String,
     pub paging_token: String,
     pub asset: String,
@@ -14,6 +19,12 @@ pub struct ClaimableBalance {
     pub claimants: Vec<Claimant>,
 }
 
+impl LedgerEntry for ClaimableBalance {
+    fn last_modified_ledger(&self) -> Option<LedgerId> {
+        LedgerId::try_from(self.last_modified_ledger).ok()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Claimant {
     pub destination: String,
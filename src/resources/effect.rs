@@ -1,11 +1,73 @@
-use crate::link::Link;
+use crate::client::HorizonClient;
+use crate::error::Result;
+use crate::link::{Link, LinkRequest};
 use crate::resources::trade::{BoughtAsset, SoldAsset};
-use crate::resources::Asset;
 use crate::resources::Predicate;
+use crate::resources::{Asset, Operation, Transaction};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+impl_type_i_enum! {
+    /// The type of an effect, as identified by its `type` and `type_i`.
+    EffectType {
+        AccountCreated => (0, "account_created"),
+        AccountRemoved => (1, "account_removed"),
+        AccountCredited => (2, "account_credited"),
+        AccountDebited => (3, "account_debited"),
+        AccountThresholdsUpdated => (4, "account_thresholds_updated"),
+        AccountHomeDomainUpdated => (5, "account_home_domain_updated"),
+        AccountFlagsUpdated => (6, "account_flags_updated"),
+        AccountInflationDestinationUpdated => (7, "account_inflation_destination_updated"),
+        SignerCreated => (10, "signer_created"),
+        SignerRemoved => (11, "signer_removed"),
+        SignerUpdated => (12, "signer_updated"),
+        TrustLineCreated => (20, "trustline_created"),
+        TrustLineRemoved => (21, "trustline_removed"),
+        TrustLineUpdated => (22, "trustline_updated"),
+        TrustLineAuthorized => (23, "trustline_authorized"),
+        TrustLineDeauthorized => (24, "trustline_deauthorized"),
+        TrustLineAuthorizedToMaintainLiabilities => (25, "trustline_authorized_to_maintain_liabilities"),
+        TrustLineFlagsUpdated => (26, "trustline_flags_updated"),
+        OfferCreated => (30, "offer_created"),
+        OfferRemoved => (31, "offer_removed"),
+        OfferUpdated => (32, "offer_updated"),
+        Trade => (33, "trade"),
+        DataCreated => (40, "data_created"),
+        DataRemoved => (41, "data_removed"),
+        DataUpdated => (42, "data_updated"),
+        SequenceBumped => (43, "sequence_bumped"),
+        ClaimableBalanceCreated => (50, "claimable_balance_created"),
+        ClaimableBalanceClaimantCreated => (51, "claimable_balance_claimant_created"),
+        ClaimableBalanceClaimed => (52, "claimable_balance_claimed"),
+        AccountSponsorshipCreated => (60, "account_sponsorship_created"),
+        AccountSponsorshipUpdated => (61, "account_sponsorship_updated"),
+        AccountSponsorshipRemoved => (62, "account_sponsorship_removed"),
+        TrustLineSponsorshipCreated => (63, "trustline_sponsorship_created"),
+        TrustLineSponsorshipUpdated => (64, "trustline_sponsorship_updated"),
+        TrustLineSponsorshipRemoved => (65, "trustline_sponsorship_removed"),
+        DataSponsorshipCreated => (66, "data_sponsorship_created"),
+        DataSponsorshipUpdated => (67, "data_sponsorship_updated"),
+        DataSponsorshipRemoved => (68, "data_sponsorship_removed"),
+        ClaimableBalanceSponsorshipCreated => (69, "claimable_balance_sponsorship_created"),
+        ClaimableBalanceSponsorshipUpdated => (70, "claimable_balance_sponsorship_updated"),
+        ClaimableBalanceSponsorshipRemoved => (71, "claimable_balance_sponsorship_removed"),
+        SignerSponsorshipCreated => (72, "signer_sponsorship_created"),
+        SignerSponsorshipUpdated => (73, "signer_sponsorship_updated"),
+        SignerSponsorshipRemoved => (74, "signer_sponsorship_removed"),
+        ClaimableBalanceClawedBack => (80, "claimable_balance_clawed_back"),
+        LiquidityPoolDeposited => (90, "liquidity_pool_deposited"),
+        LiquidityPoolWithdrew => (91, "liquidity_pool_withdrew"),
+        LiquidityPoolTrade => (92, "liquidity_pool_trade"),
+        LiquidityPoolCreated => (93, "liquidity_pool_created"),
+        LiquidityPoolRemoved => (94, "liquidity_pool_removed"),
+        LiquidityPoolRevoked => (95, "liquidity_pool_revoked"),
+        ContractCredited => (96, "contract_credited"),
+        ContractDebited => (97, "contract_debited"),
+    }
+}
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
 pub enum Effect {
@@ -58,6 +120,57 @@ pub enum Effect {
     SignerSponsorshipCreated(SignerSponsorshipCreatedEffect),
     SignerSponsorshipUpdated(SignerSponsorshipUpdatedEffect),
     SignerSponsorshipRemoved(SignerSponsorshipRemovedEffect),
+    /// An effect type not supported by this version of the crate.
+    ///
+    /// Effects of a supported type that don't match the expected fields
+    /// fail to deserialize instead.
+    #[serde(untagged)]
+    Unknown(UnknownEffect),
+}
+
+impl_type_i_checked_deserialize! {
+    Effect, EffectType {
+        AccountCreated,
+        AccountRemoved,
+        AccountCredited,
+        AccountDebited,
+        AccountThresholdsUpdated,
+        AccountHomeDomainUpdated,
+        AccountFlagsUpdated,
+        AccountInflationDestinationUpdated,
+        SignerCreated,
+        SignerRemoved,
+        SignerUpdated,
+        TrustLineCreated,
+        TrustLineRemoved,
+        TrustLineUpdated,
+        TrustLineAuthorized,
+        TrustLineAuthorizedToMaintainLiabilities,
+        TrustLineDeauthorized,
+        OfferCreated,
+        OfferRemoved,
+        OfferUpdated,
+        Trade,
+        DataCreated,
+        DataRemoved,
+        DataUpdated,
+        SequenceBumped,
+        ClaimableBalanceCreated,
+        ClaimableBalanceClaimantCreated,
+        ClaimableBalanceClaimed,
+        AccountSponsorshipCreated,
+        AccountSponsorshipUpdated,
+        AccountSponsorshipRemoved,
+        TrustLineSponsorshipCreated,
+        TrustLineSponsorshipUpdated,
+        TrustLineSponsorshipRemoved,
+        ClaimableBalanceSponsorshipCreated,
+        ClaimableBalanceSponsorshipUpdated,
+        ClaimableBalanceSponsorshipRemoved,
+        SignerSponsorshipCreated,
+        SignerSponsorshipUpdated,
+        SignerSponsorshipRemoved,
+    } else Unknown
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -66,11 +179,25 @@ pub struct EffectBase {
     pub links: EffectLinks,
     pub id: String,
     pub paging_token: String,
-    pub account: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
     pub type_i: i32,
     pub created_at: DateTime<Utc>,
 }
 
+/// An effect with a type not supported by this version of the crate.
+///
+/// The fields not part of `EffectBase` are kept as raw json values.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct UnknownEffect {
+    #[serde(flatten)]
+    pub base: EffectBase,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AccountCreatedEffect {
     #[serde(flatten)]
@@ -397,6 +524,12 @@ pub struct EffectLinks {
     pub precedes: Link,
 }
 
+impl_links!(EffectLinks {
+    "operation" => operation,
+    "succeeds" => succeeds,
+    "precedes" => precedes,
+});
+
 impl Effect {
     pub fn base(&self) -> &EffectBase {
         match self {
@@ -440,6 +573,27 @@ impl Effect {
             Effect::SignerSponsorshipCreated(op) => &op.base,
             Effect::SignerSponsorshipUpdated(op) => &op.base,
             Effect::SignerSponsorshipRemoved(op) => &op.base,
+            Effect::Unknown(op) => &op.base,
         }
     }
+
+    /// Returns the type of this effect, from its `type_i`.
+    pub fn effect_type(&self) -> EffectType {
+        EffectType::from_i(self.base().type_i)
+    }
+
+    /// Returns the transaction of the operation of this effect.
+    ///
+    /// Requests the operation following its link, then its transaction.
+    pub async fn fetch_transaction<C: HorizonClient>(&self, client: &C) -> Result<Transaction> {
+        let (_, operation) = client.request(self.base().operation_request()?).await?;
+        operation.fetch_transaction(client).await
+    }
+}
+
+impl EffectBase {
+    /// Returns a request for the operation of this effect, following its link.
+    pub fn operation_request(&self) -> Result<LinkRequest<Operation>> {
+        LinkRequest::from_link(&self.links.operation, &[])
+    }
 }
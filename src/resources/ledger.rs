@@ -3,6 +3,8 @@ use crate::resources::LedgerId;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::rust::display_fromstr;
+use std::collections::BTreeMap as Map;
+use stellar_base::amount::Stroops;
 
 /// Store the state of network at a point in time.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -93,6 +95,49 @@ pub struct FeeDistribution {
     /// 99th percentile fee charged over the last 5 ledger.
     #[serde(with = "display_fromstr")]
     pub p99: i64,
+    /// Other statistics sent by Horizon, as they were received.
+    #[serde(flatten)]
+    pub other: Map<String, serde_json::Value>,
+}
+
+macro_rules! impl_fee_accessors {
+    ($($name:ident),*) => {
+        $(
+            #[doc = concat!("Returns the `", stringify!($name), "` fee.")]
+            pub fn $name(&self) -> Stroops {
+                Stroops::new(self.$name)
+            }
+        )*
+    };
+}
+
+impl FeeDistribution {
+    impl_fee_accessors!(max, min, mode, p10, p20, p30, p40, p50, p60, p70, p80, p90, p95, p99);
+
+    /// Returns the fee of the `percentile`th percentile, if Horizon sent it.
+    ///
+    /// Percentiles not known by this crate are looked up in `other`.
+    pub fn percentile(&self, percentile: u8) -> Option<Stroops> {
+        let fee = match percentile {
+            10 => self.p10,
+            20 => self.p20,
+            30 => self.p30,
+            40 => self.p40,
+            50 => self.p50,
+            60 => self.p60,
+            70 => self.p70,
+            80 => self.p80,
+            90 => self.p90,
+            95 => self.p95,
+            99 => self.p99,
+            _ => self
+                .other
+                .get(&format!("p{}", percentile))
+                .and_then(|value| value.as_str())
+                .and_then(|value| value.parse().ok())?,
+        };
+        Some(Stroops::new(fee))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -112,6 +157,38 @@ pub struct FeeStats {
     pub max_fee: FeeDistribution,
 }
 
+/// How much the network is expected to be congested when submitting a transaction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Congestion {
+    /// Bid the 50th percentile max fee.
+    Low,
+    /// Bid the 90th percentile max fee.
+    Medium,
+    /// Bid the 99th percentile max fee.
+    High,
+}
+
+impl FeeStats {
+    /// Returns the last ledger base fee.
+    pub fn base_fee(&self) -> Stroops {
+        Stroops::new(self.last_ledger_base_fee)
+    }
+
+    /// Returns the max fee to bid for a transaction with `operation_count` operations.
+    ///
+    /// The fee per operation is the max fee bid percentile for the given
+    /// `congestion`, but never less than the last ledger base fee.
+    pub fn recommended_max_fee(&self, operation_count: u32, congestion: Congestion) -> Stroops {
+        let fee_per_operation = match congestion {
+            Congestion::Low => self.max_fee.p50,
+            Congestion::Medium => self.max_fee.p90,
+            Congestion::High => self.max_fee.p99,
+        };
+        let fee_per_operation = fee_per_operation.max(self.last_ledger_base_fee);
+        Stroops::new(fee_per_operation.saturating_mul(operation_count as i64))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct LedgerLinks {
     /// Link to this ledger.
@@ -126,3 +203,11 @@ pub struct LedgerLinks {
     /// Link to the effects in the ledger.
     pub effects: Link,
 }
+
+impl_links!(LedgerLinks {
+    "self" => self_,
+    "transactions" => transactions,
+    "operations" => operations,
+    "payments" => payments,
+    "effects" => effects,
+});
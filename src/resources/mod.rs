@@ -5,6 +5,162 @@
 // https://github.com/stellar/go/blob/master/protocols/horizon/
 //
 // When updating, use that as your source of truth.
+
+macro_rules! impl_str_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$variant_meta:meta])* $variant:ident => $code:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value not known by this crate.
+            Unknown(String),
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                Ok(match s {
+                    $($code => $name::$variant,)*
+                    other => $name::Unknown(other.to_string()),
+                })
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $($name::$variant => write!(f, $code),)*
+                    $name::Unknown(code) => write!(f, "{}", code),
+                }
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                let code = String::deserialize(deserializer)?;
+                Ok(code.parse().unwrap_or_else(|never| match never {}))
+            }
+        }
+    };
+}
+
+macro_rules! impl_type_i_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => ($code:literal, $type_name:literal),)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)*
+            /// A type not known by this crate, with its `type_i`.
+            Unknown(i32),
+        }
+
+        impl $name {
+            /// Returns the type with `type_i` code.
+            pub fn from_i(type_i: i32) -> $name {
+                match type_i {
+                    $($code => $name::$variant,)*
+                    other => $name::Unknown(other),
+                }
+            }
+
+            /// Returns the type with `type` name, if known.
+            pub fn from_name(name: &str) -> Option<$name> {
+                match name {
+                    $($type_name => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the `type_i` code of the type.
+            pub fn as_i(&self) -> i32 {
+                match self {
+                    $($name::$variant => $code,)*
+                    $name::Unknown(code) => *code,
+                }
+            }
+
+            /// Returns the `type` name of the type, `unknown` for unknown types.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $type_name,)*
+                    $name::Unknown(_) => "unknown",
+                }
+            }
+
+            /// Returns all the known types.
+            pub fn known() -> &'static [$name] {
+                &[$($name::$variant,)*]
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+/// Implements `Deserialize` for an enum tagged by `type`, checking that
+/// the `type_i` of records agrees with their `type` using `$type_enum`.
+///
+/// Each variant is named after its `$type_enum` variant. Records of a
+/// type without a variant are deserialized as the `else` variant when
+/// there is one, records of a type with a variant fail to deserialize
+/// if they don't match it. The enum derives `Serialize` with
+/// `#[serde(tag = "type")]`.
+macro_rules! impl_type_i_checked_deserialize {
+    (@other $value:ident, $type_name:ident) => {
+        Err(serde_json::Error::custom(format!("unknown variant `{}`", $type_name)))
+    };
+    (@other $value:ident, $type_name:ident, $other:path) => {
+        serde::Deserialize::deserialize(&$value).map($other)
+    };
+    ($name:ident, $type_enum:ident { $($variant:ident),* $(,)? } $(else $other:ident)?) => {
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<$name, D::Error> {
+                use serde::de::Error as _;
+                let strict_path = crate::json::strict_path();
+                // Like serde does for internally tagged enums, buffer the
+                // record since `type` is not its first field.
+                let value = serde_json::Value::deserialize(deserializer)?;
+                let type_name = match value.get("type") {
+                    Some(serde_json::Value::String(type_name)) => type_name,
+                    _ => return Err(D::Error::missing_field("type")),
+                };
+                let type_ = $type_enum::from_name(type_name);
+                let type_i = value.get("type_i").and_then(serde_json::Value::as_i64);
+                if let (Some(type_i), Some(type_)) = (type_i, type_) {
+                    if type_i != i64::from(type_.as_i()) {
+                        return Err(D::Error::custom(format!(
+                            "type_i {} does not match type `{}`",
+                            type_i, type_
+                        )));
+                    }
+                }
+                let result = match type_ {
+                    $(Some($type_enum::$variant) => serde::Deserialize::deserialize(&value)
+                        .map($name::$variant),)*
+                    _ => impl_type_i_checked_deserialize!(@other value, type_name $(, $name::$other)?),
+                };
+                if let (Some(path), Ok(record)) = (strict_path, &result) {
+                    crate::json::check_record(&path, &value, record);
+                }
+                result.map_err(D::Error::custom)
+            }
+        }
+    };
+}
+
 pub mod account;
 pub mod asset;
 pub mod book;
@@ -29,6 +185,13 @@ pub use root::*;
 pub use trade::*;
 pub use transaction::*;
 
+use crate::cursor::Cursor;
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use std::convert::TryFrom;
+use stellar_base::asset::CreditAssetType;
+use stellar_base::crypto::PublicKey;
+
 /// Alias type for offer's id.
 pub type OfferId = i64;
 
@@ -38,6 +201,68 @@ pub type LedgerId = i32;
 /// Alias type for claimable balance id.
 pub type ClaimableBalanceId = String;
 
+/// A ledger entry, tracking the ledger in which it was last modified.
+pub trait LedgerEntry {
+    /// The sequence of the ledger that last modified this entry, if known.
+    fn last_modified_ledger(&self) -> Option<LedgerId>;
+
+    /// The time this entry was last modified, if known.
+    fn last_modified_time(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+}
+
+/// A resource with a paging token, the cursor to fetch the records after it.
+pub trait PagingToken {
+    /// Returns the paging token.
+    fn paging_token(&self) -> &str;
+
+    /// Returns the paging token as a cursor, to compare it with other cursors.
+    fn cursor(&self) -> Cursor {
+        Cursor::new(self.paging_token())
+    }
+}
+
+macro_rules! impl_paging_token {
+    ($($name:ty),*) => {
+        $(
+            impl PagingToken for $name {
+                fn paging_token(&self) -> &str {
+                    &self.paging_token
+                }
+            }
+        )*
+    };
+}
+
+impl_paging_token!(
+    Account,
+    AssetStat,
+    ClaimableBalance,
+    Ledger,
+    Offer,
+    Trade,
+    Transaction
+);
+
+impl PagingToken for Operation {
+    fn paging_token(&self) -> &str {
+        &self.base().paging_token
+    }
+}
+
+impl PagingToken for Payment {
+    fn paging_token(&self) -> &str {
+        &self.base().paging_token
+    }
+}
+
+impl PagingToken for Effect {
+    fn paging_token(&self) -> &str {
+        &self.base().paging_token
+    }
+}
+
 /// Price as rational number.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Price {
@@ -49,6 +274,47 @@ pub struct Price {
     pub denominator: i32,
 }
 
+impl Price {
+    /// Returns the price as a floating point number.
+    ///
+    /// The conversion can lose precision, use the numerator and
+    /// denominator for exact computations.
+    pub fn to_f64(&self) -> f64 {
+        f64::from(self.numerator) / f64::from(self.denominator)
+    }
+
+    /// Returns the price with the numerator and denominator divided by
+    /// their greatest common divisor.
+    pub fn reduced(&self) -> Price {
+        let divisor = gcd(
+            self.numerator.unsigned_abs(),
+            self.denominator.unsigned_abs(),
+        );
+        if divisor <= 1 {
+            return self.clone();
+        }
+        Price {
+            numerator: self.numerator / divisor as i32,
+            denominator: self.denominator / divisor as i32,
+        }
+    }
+}
+
+impl From<&Price> for stellar_base::amount::Price {
+    fn from(price: &Price) -> stellar_base::amount::Price {
+        stellar_base::amount::Price::new(price.numerator, price.denominator)
+    }
+}
+
+fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let rest = a % b;
+        a = b;
+        b = rest;
+    }
+    a
+}
+
 /// An asset, either the native asset or a credit asset.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Asset {
@@ -57,6 +323,81 @@ pub struct Asset {
     pub asset_issuer: Option<String>,
 }
 
+impl Asset {
+    /// Checks that `base` and `counter` can be traded against each other.
+    ///
+    /// Returns an error if either asset is invalid, or if `base` and
+    /// `counter` are the same asset.
+    pub fn is_valid_pair(base: &Asset, counter: &Asset) -> Result<()> {
+        base.validate()?;
+        counter.validate()?;
+        if base == counter {
+            return Err(Error::InvalidAssetPair);
+        }
+        Ok(())
+    }
+
+    /// Checks that the asset type, code, and issuer are consistent.
+    pub fn validate(&self) -> Result<()> {
+        let code_len = match self.asset_type.as_str() {
+            "native" => {
+                if self.asset_code.is_some() || self.asset_issuer.is_some() {
+                    return Err(Error::InvalidAsset);
+                }
+                return Ok(());
+            }
+            "credit_alphanum4" => 1..=4,
+            "credit_alphanum12" => 5..=12,
+            _ => return Err(Error::InvalidAsset),
+        };
+        let code = self.asset_code.as_ref().ok_or(Error::InvalidAsset)?;
+        if !code_len.contains(&code.len()) || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(Error::InvalidAsset);
+        }
+        let issuer = self.asset_issuer.as_ref().ok_or(Error::InvalidAsset)?;
+        PublicKey::from_account_id(issuer).map_err(|_| Error::InvalidAsset)?;
+        Ok(())
+    }
+}
+
+impl From<&stellar_base::Asset> for Asset {
+    fn from(asset: &stellar_base::Asset) -> Asset {
+        match asset {
+            stellar_base::Asset::Native => Asset {
+                asset_type: "native".to_string(),
+                asset_code: None,
+                asset_issuer: None,
+            },
+            stellar_base::Asset::Credit(credit) => {
+                let asset_type = match credit.asset_type() {
+                    CreditAssetType::CreditAlphaNum4(_) => "credit_alphanum4",
+                    CreditAssetType::CreditAlphaNum12(_) => "credit_alphanum12",
+                };
+                Asset {
+                    asset_type: asset_type.to_string(),
+                    asset_code: Some(credit.code().to_string()),
+                    asset_issuer: Some(credit.issuer().account_id()),
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<&Asset> for stellar_base::Asset {
+    type Error = Error;
+
+    fn try_from(asset: &Asset) -> Result<stellar_base::Asset> {
+        asset.validate()?;
+        match (&asset.asset_code, &asset.asset_issuer) {
+            (Some(code), Some(issuer)) => {
+                let issuer = PublicKey::from_account_id(issuer)?;
+                Ok(stellar_base::Asset::new_credit(code.clone(), issuer)?)
+            }
+            _ => Ok(stellar_base::Asset::new_native()),
+        }
+    }
+}
+
 /// Represent
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Path {
@@ -91,3 +432,76 @@ struct DestinationAsset {
     #[serde(rename = "destination_asset_issuer")]
     asset_issuer: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Asset, Price};
+    use crate::error::Error;
+    use stellar_base::crypto::PublicKey;
+
+    fn credit_asset(code: &str) -> Asset {
+        let issuer =
+            PublicKey::from_account_id("GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623")
+                .unwrap();
+        let asset = stellar_base::Asset::new_credit(code, issuer).unwrap();
+        (&asset).into()
+    }
+
+    fn native_asset() -> Asset {
+        (&stellar_base::Asset::new_native()).into()
+    }
+
+    #[test]
+    fn test_native_native_is_not_valid_pair() {
+        let result = Asset::is_valid_pair(&native_asset(), &native_asset());
+        assert!(matches!(result, Err(Error::InvalidAssetPair)));
+    }
+
+    #[test]
+    fn test_same_credit_is_not_valid_pair() {
+        let result = Asset::is_valid_pair(&credit_asset("ABCD"), &credit_asset("ABCD"));
+        assert!(matches!(result, Err(Error::InvalidAssetPair)));
+    }
+
+    #[test]
+    fn test_valid_pair() {
+        assert!(Asset::is_valid_pair(&native_asset(), &credit_asset("ABCD")).is_ok());
+        assert!(Asset::is_valid_pair(&credit_asset("ABCD"), &credit_asset("ABCDEFG")).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_asset_code_is_not_valid_pair() {
+        let mut invalid = credit_asset("ABCD");
+        invalid.asset_code = Some("AB-D".to_string());
+        let result = Asset::is_valid_pair(&native_asset(), &invalid);
+        assert!(matches!(result, Err(Error::InvalidAsset)));
+
+        let mut invalid = credit_asset("ABCD");
+        invalid.asset_code = Some("ABCDEFG".to_string());
+        let result = Asset::is_valid_pair(&invalid, &native_asset());
+        assert!(matches!(result, Err(Error::InvalidAsset)));
+    }
+
+    #[test]
+    fn test_price() {
+        let price = Price {
+            numerator: 6,
+            denominator: 4,
+        };
+        assert_eq!(1.5, price.to_f64());
+        let reduced = price.reduced();
+        assert_eq!(3, reduced.numerator);
+        assert_eq!(2, reduced.denominator);
+        assert_eq!(reduced, reduced.reduced());
+
+        let price = Price {
+            numerator: 0,
+            denominator: 5,
+        };
+        assert_eq!(1, price.reduced().denominator);
+
+        let price: stellar_base::amount::Price = (&reduced).into();
+        assert_eq!(3, price.numerator());
+        assert_eq!(2, price.denominator());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::link::Link;
-use crate::resources::{Asset, LedgerId, OfferId, Price};
+use crate::resources::{Asset, LedgerEntry, LedgerId, OfferId, Price};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::rust::display_fromstr;
@@ -34,6 +34,16 @@ pub struct Offer {
     pub sponsor: Option<String>,
 }
 
+impl LedgerEntry for Offer {
+    fn last_modified_ledger(&self) -> Option<LedgerId> {
+        Some(self.last_modified_ledger)
+    }
+
+    fn last_modified_time(&self) -> Option<DateTime<Utc>> {
+        self.last_modified_time
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct OfferLinks {
     #[serde(rename = "self")]
@@ -41,3 +51,5 @@ pub struct OfferLinks {
     /// Link to the account making the offer.
     pub offer_maker: Link,
 }
+
+impl_links!(OfferLinks { "self" => self_, "offer_maker" => offer_maker });
@@ -1,9 +1,12 @@
-use crate::link::Link;
-use crate::resources::{Asset, Claimant, Price, SourceAsset, Transaction};
+use crate::api::transactions::SingleTransactionRequest;
+use crate::client::HorizonClient;
+use crate::error::Result;
+use crate::link::{Link, LinkRequest};
+use crate::resources::{AccountFlags, Asset, Claimant, Price, SourceAsset, Transaction};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
 pub enum Operation {
@@ -28,7 +31,31 @@ pub enum Operation {
     RevokeSponsorship(RevokeSponsorshipOperation),
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+impl_type_i_checked_deserialize! {
+    Operation, OperationType {
+        BumpSequence,
+        CreateAccount,
+        Payment,
+        PathPaymentStrictReceive,
+        PathPaymentStrictSend,
+        ManageData,
+        CreatePassiveSellOffer,
+        ManageSellOffer,
+        ManageBuyOffer,
+        SetOptions,
+        ChangeTrust,
+        AllowTrust,
+        AccountMerge,
+        Inflation,
+        CreateClaimableBalance,
+        ClaimClaimableBalance,
+        BeginSponsoringFutureReserves,
+        EndSponsoringFutureReserves,
+        RevokeSponsorship,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
 pub enum Payment {
@@ -39,6 +66,49 @@ pub enum Payment {
     AccountMerge(AccountMergeOperation),
 }
 
+impl_type_i_checked_deserialize! {
+    Payment, OperationType {
+        CreateAccount,
+        Payment,
+        PathPaymentStrictReceive,
+        PathPaymentStrictSend,
+        AccountMerge,
+    }
+}
+
+impl_type_i_enum! {
+    /// The type of an operation, as identified by its `type` and `type_i`.
+    OperationType {
+        CreateAccount => (0, "create_account"),
+        Payment => (1, "payment"),
+        PathPaymentStrictReceive => (2, "path_payment_strict_receive"),
+        ManageSellOffer => (3, "manage_sell_offer"),
+        CreatePassiveSellOffer => (4, "create_passive_sell_offer"),
+        SetOptions => (5, "set_options"),
+        ChangeTrust => (6, "change_trust"),
+        AllowTrust => (7, "allow_trust"),
+        AccountMerge => (8, "account_merge"),
+        Inflation => (9, "inflation"),
+        ManageData => (10, "manage_data"),
+        BumpSequence => (11, "bump_sequence"),
+        ManageBuyOffer => (12, "manage_buy_offer"),
+        PathPaymentStrictSend => (13, "path_payment_strict_send"),
+        CreateClaimableBalance => (14, "create_claimable_balance"),
+        ClaimClaimableBalance => (15, "claim_claimable_balance"),
+        BeginSponsoringFutureReserves => (16, "begin_sponsoring_future_reserves"),
+        EndSponsoringFutureReserves => (17, "end_sponsoring_future_reserves"),
+        RevokeSponsorship => (18, "revoke_sponsorship"),
+        Clawback => (19, "clawback"),
+        ClawbackClaimableBalance => (20, "clawback_claimable_balance"),
+        SetTrustLineFlags => (21, "set_trust_line_flags"),
+        LiquidityPoolDeposit => (22, "liquidity_pool_deposit"),
+        LiquidityPoolWithdraw => (23, "liquidity_pool_withdraw"),
+        InvokeHostFunction => (24, "invoke_host_function"),
+        ExtendFootprintTtl => (25, "extend_footprint_ttl"),
+        RestoreFootprint => (26, "restore_footprint"),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct OperationBase {
     #[serde(rename = "_links")]
@@ -76,6 +146,8 @@ pub struct PaymentOperation {
     pub from: String,
     pub to: String,
     pub amount: String,
+    #[serde(flatten)]
+    pub asset: Asset,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -85,6 +157,8 @@ pub struct PathPaymentStrictReceiveOperation {
     pub from: String,
     pub to: String,
     pub amount: String,
+    #[serde(flatten)]
+    pub asset: Asset,
     pub path: Vec<Asset>,
     pub source_amount: String,
     pub source_max: String,
@@ -99,6 +173,8 @@ pub struct PathPaymentStrictSendOperation {
     pub from: String,
     pub to: String,
     pub amount: String,
+    #[serde(flatten)]
+    pub asset: Asset,
     pub path: Vec<Asset>,
     pub source_amount: String,
     pub destination_min: String,
@@ -182,6 +258,82 @@ pub struct SetOptionsOperation {
     pub high_threshold: Option<i32>,
 }
 
+/// A signer added, updated, or removed by a set options operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerUpdate {
+    /// The signer key.
+    pub key: String,
+    /// The new weight of the signer.
+    pub weight: i32,
+}
+
+impl SignerUpdate {
+    /// Returns `true` if the signer is removed, that is its weight is 0.
+    pub fn is_removal(&self) -> bool {
+        self.weight == 0
+    }
+}
+
+/// The thresholds changed by a set options operation.
+///
+/// Thresholds not changed by the operation are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThresholdsUpdate {
+    pub low: Option<i32>,
+    pub medium: Option<i32>,
+    pub high: Option<i32>,
+}
+
+impl ThresholdsUpdate {
+    /// Returns `true` if no threshold is changed.
+    pub fn is_empty(&self) -> bool {
+        self.low.is_none() && self.medium.is_none() && self.high.is_none()
+    }
+}
+
+impl SetOptionsOperation {
+    /// Returns the signer added, updated, or removed by the operation.
+    pub fn signer_update(&self) -> Option<SignerUpdate> {
+        match (self.signer_key.as_ref(), self.signer_weight) {
+            (Some(key), Some(weight)) => Some(SignerUpdate {
+                key: key.clone(),
+                weight,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the thresholds changed by the operation.
+    pub fn thresholds_update(&self) -> ThresholdsUpdate {
+        ThresholdsUpdate {
+            low: self.low_threshold,
+            medium: self.medium_threshold,
+            high: self.high_threshold,
+        }
+    }
+
+    /// Returns the account flags set by the operation.
+    pub fn set_account_flags(&self) -> AccountFlags {
+        account_flags(&self.set_flags)
+    }
+
+    /// Returns the account flags cleared by the operation.
+    pub fn cleared_account_flags(&self) -> AccountFlags {
+        account_flags(&self.clear_flags)
+    }
+}
+
+/// Converts the flags of a set options operation to `AccountFlags`.
+fn account_flags(flags: &[i32]) -> AccountFlags {
+    let flags = flags.iter().fold(0, |acc, flag| acc | flag);
+    AccountFlags {
+        auth_required: flags & 0x1 != 0,
+        auth_revocable: flags & 0x2 != 0,
+        auth_immutable: flags & 0x4 != 0,
+        auth_clawback_enabled: flags & 0x8 != 0,
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ChangeTrustOperation {
     #[serde(flatten)]
@@ -210,7 +362,15 @@ pub struct AccountMergeOperation {
     #[serde(flatten)]
     pub base: OperationBase,
     pub account: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_muxed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_muxed_id: Option<String>,
     pub into: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub into_muxed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub into_muxed_id: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -275,6 +435,110 @@ pub struct OperationLinks {
     pub precedes: Link,
 }
 
+impl_links!(OperationLinks {
+    "self" => self_,
+    "transaction" => transaction,
+    "effects" => effects,
+    "succeeds" => succeeds,
+    "precedes" => precedes,
+});
+
+impl OperationBase {
+    /// Returns a request for the transaction of this operation, by hash.
+    pub fn transaction_request(&self) -> SingleTransactionRequest {
+        SingleTransactionRequest::new(self.transaction_hash.as_str())
+    }
+
+    /// Returns a request for the transaction of this operation, following its link.
+    pub fn transaction_link_request(&self) -> Result<LinkRequest<Transaction>> {
+        LinkRequest::from_link(&self.links.transaction, &[])
+    }
+
+    /// Returns the transaction of this operation.
+    ///
+    /// Returns the joined transaction if the operation was requested
+    /// with `Join::Transactions`, otherwise requests the transaction by
+    /// hash, or following the `transaction` link if the hash is empty.
+    pub async fn fetch_transaction<C: HorizonClient>(&self, client: &C) -> Result<Transaction> {
+        if let Some(transaction) = &self.transaction {
+            return Ok(transaction.clone());
+        }
+        let (_, transaction) = if self.transaction_hash.is_empty() {
+            client.request(self.transaction_link_request()?).await?
+        } else {
+            client.request(self.transaction_request()).await?
+        };
+        Ok(transaction)
+    }
+}
+
+impl Payment {
+    pub fn base(&self) -> &OperationBase {
+        match self {
+            Payment::CreateAccount(op) => &op.base,
+            Payment::Payment(op) => &op.base,
+            Payment::PathPaymentStrictReceive(op) => &op.base,
+            Payment::PathPaymentStrictSend(op) => &op.base,
+            Payment::AccountMerge(op) => &op.base,
+        }
+    }
+
+    /// Returns the account sending the payment.
+    ///
+    /// This is the funder of created accounts, and the merged account of
+    /// account merges.
+    pub fn from(&self) -> &str {
+        match self {
+            Payment::CreateAccount(op) => &op.funder,
+            Payment::Payment(op) => &op.from,
+            Payment::PathPaymentStrictReceive(op) => &op.from,
+            Payment::PathPaymentStrictSend(op) => &op.from,
+            Payment::AccountMerge(op) => &op.account,
+        }
+    }
+
+    /// Returns the account receiving the payment.
+    pub fn to(&self) -> &str {
+        match self {
+            Payment::CreateAccount(op) => &op.account,
+            Payment::Payment(op) => &op.to,
+            Payment::PathPaymentStrictReceive(op) => &op.to,
+            Payment::PathPaymentStrictSend(op) => &op.to,
+            Payment::AccountMerge(op) => &op.into,
+        }
+    }
+
+    /// Returns the amount received by `to`.
+    ///
+    /// Horizon doesn't include the amount of account merges, it's in
+    /// the `account_credited` effect of the operation.
+    pub fn amount(&self) -> Option<&str> {
+        match self {
+            Payment::CreateAccount(op) => Some(&op.starting_balance),
+            Payment::Payment(op) => Some(&op.amount),
+            Payment::PathPaymentStrictReceive(op) => Some(&op.amount),
+            Payment::PathPaymentStrictSend(op) => Some(&op.amount),
+            Payment::AccountMerge(_) => None,
+        }
+    }
+
+    /// Returns the asset received by `to`.
+    ///
+    /// Created accounts and account merges are always in the native asset.
+    pub fn asset(&self) -> Asset {
+        match self {
+            Payment::Payment(op) => op.asset.clone(),
+            Payment::PathPaymentStrictReceive(op) => op.asset.clone(),
+            Payment::PathPaymentStrictSend(op) => op.asset.clone(),
+            Payment::CreateAccount(_) | Payment::AccountMerge(_) => Asset {
+                asset_type: "native".to_string(),
+                asset_code: None,
+                asset_issuer: None,
+            },
+        }
+    }
+}
+
 impl Operation {
     pub fn base(&self) -> &OperationBase {
         match self {
@@ -299,6 +563,21 @@ impl Operation {
             Operation::RevokeSponsorship(op) => &op.base,
         }
     }
+
+    /// Returns the type of this operation, from its `type_i`.
+    pub fn operation_type(&self) -> OperationType {
+        OperationType::from_i(self.base().type_i)
+    }
+
+    /// Returns the hash of the transaction of this operation.
+    pub fn transaction_hash(&self) -> &str {
+        &self.base().transaction_hash
+    }
+
+    /// Returns the transaction of this operation, see `OperationBase::fetch_transaction`.
+    pub async fn fetch_transaction<C: HorizonClient>(&self, client: &C) -> Result<Transaction> {
+        self.base().fetch_transaction(client).await
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -322,3 +601,76 @@ struct SellingAsset {
     #[serde(rename = "selling_asset_issuer")]
     asset_issuer: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Operation;
+    use crate::page::Page;
+    use crate::resources::Effect;
+    use crate::testing::MockClient;
+
+    const OPERATION: &str = include_str!("../../tests/fixtures/operation_set_options.json");
+    const TRANSACTION: &str = include_str!("../../tests/fixtures/transaction_fee_bump.json");
+
+    #[tokio::test]
+    async fn test_fetch_transaction_by_hash() {
+        let operation: Operation = serde_json::from_str(OPERATION).unwrap();
+        assert_eq!(
+            "9f3a74bc5b5c9c7a29b3a8d45a8fbe5c2c9ab4ddeb2cd7d654547c23b7373f31",
+            operation.transaction_hash()
+        );
+        let client = MockClient::pages(vec![TRANSACTION]);
+        let transaction = operation.fetch_transaction(&client).await.unwrap();
+        assert!(!transaction.hash.is_empty());
+        let uris = client.uris.lock().unwrap();
+        assert_eq!(
+            format!("/transactions/{}", operation.transaction_hash()),
+            uris[0].path()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_transaction_following_link() {
+        let mut operation: Operation = serde_json::from_str(OPERATION).unwrap();
+        if let Operation::SetOptions(op) = &mut operation {
+            op.base.transaction_hash = String::new();
+        }
+        let client = MockClient::pages(vec![TRANSACTION]);
+        operation.fetch_transaction(&client).await.unwrap();
+        let uris = client.uris.lock().unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/transactions/9f3a74bc5b5c9c7a29b3a8d45a8fbe5c2c9ab4ddeb2cd7d654547c23b7373f31",
+            uris[0].as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_joined_transaction() {
+        let operation: Operation = serde_json::from_str(include_str!(
+            "../../tests/fixtures/operation_with_transaction.json"
+        ))
+        .unwrap();
+        let client = MockClient::empty();
+        let transaction = operation.fetch_transaction(&client).await.unwrap();
+        assert_eq!(operation.transaction_hash(), transaction.hash);
+        assert!(client.uris.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_effect_transaction() {
+        let effects: Page<Effect> =
+            serde_json::from_str(include_str!("../../tests/fixtures/all_effects.json")).unwrap();
+        let effect = &effects.records[0];
+        let client = MockClient::pages(vec![OPERATION, TRANSACTION]);
+        effect.fetch_transaction(&client).await.unwrap();
+        let uris = client.uris.lock().unwrap();
+        assert_eq!(
+            "https://horizon.stellar.org/operations/12884905985",
+            uris[0].as_str()
+        );
+        assert_eq!(
+            "/transactions/9f3a74bc5b5c9c7a29b3a8d45a8fbe5c2c9ab4ddeb2cd7d654547c23b7373f31",
+            uris[1].path()
+        );
+    }
+}
@@ -42,3 +42,39 @@ pub struct RootLinks {
     pub transaction: Link,
     pub transactions: Link,
 }
+
+impl_links!(RootLinks {
+    "account" => account,
+    "accounts" => accounts,
+    "account_transactions" => account_transactions,
+    "assets" => assets,
+    "effects" => effects,
+    "fee_stats" => fee_stats,
+    "friendbot" => friendbot,
+    "ledger" => ledger,
+    "ledgers" => ledgers,
+    "offer" => offer,
+    "offers" => offers,
+    "operation" => operation,
+    "operations" => operations,
+    "order_book" => order_book,
+    "payments" => payments,
+    "self" => self_,
+    "strict_receive_paths" => strict_receive_paths,
+    "strict_send_paths" => strict_send_paths,
+    "trade_aggregations" => trade_aggregations,
+    "trades" => trades,
+    "transaction" => transaction,
+    "transactions" => transactions,
+});
+
+/// Health status of an Horizon instance.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Health {
+    /// `true` if Horizon is connected to its database.
+    pub database_connected: bool,
+    /// `true` if the Stellar Core instance of Horizon is up.
+    pub core_up: bool,
+    /// `true` if the Stellar Core instance of Horizon is synced with the network.
+    pub core_synced: bool,
+}
@@ -16,21 +16,29 @@ pub struct Trade {
     pub paging_token: String,
     /// When the ledger with this trade was closed.
     pub ledger_close_time: DateTime<Utc>,
+    /// The trade type, either `orderbook` or `liquidity_pool`.
+    pub trade_type: Option<String>,
+    /// The fee charged by the liquidity pool, in basis points.
+    pub liquidity_pool_fee_bp: Option<u32>,
     /// The sell offer ID.
-    pub offer_id: String,
+    pub offer_id: Option<String>,
     /// The base offer ID. If this offer was immediately and fully consumed, this will be a synethic ID.
-    pub base_offer_id: String,
+    pub base_offer_id: Option<String>,
     /// The account ID of the base party for this trade.
-    pub base_account: String,
+    pub base_account: Option<String>,
+    /// The liquidity pool ID of the base party for this trade.
+    pub base_liquidity_pool_id: Option<String>,
     /// The amount of the `base_asset` that was moved from `base_account` to `counter_account`.
     pub base_amount: String,
     /// The base asset.
     #[serde(flatten, with = "BaseAsset")]
     pub base_asset: Asset,
     /// The counter offer ID. If this offer was immediately and fully consumed, this will be a synethic ID.
-    pub counter_offer_id: String,
+    pub counter_offer_id: Option<String>,
     /// The account ID of the counter party for this trade.
-    pub counter_account: String,
+    pub counter_account: Option<String>,
+    /// The liquidity pool ID of the counter party for this trade.
+    pub counter_liquidity_pool_id: Option<String>,
     /// The amount of the `counter_asset` that was moved from `counter_account` to `base_account`.
     pub counter_amount: String,
     /// The counter asset.
@@ -124,6 +132,13 @@ pub struct TradeLinks {
     pub operation: Link,
 }
 
+impl_links!(TradeLinks {
+    "self" => self_,
+    "base" => base,
+    "counter" => counter,
+    "operation" => operation,
+});
+
 /// Trade effectt links.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct TradeEffectLinks {
@@ -138,6 +153,13 @@ pub struct TradeEffectLinks {
     pub operation: Link,
 }
 
+impl_links!(TradeEffectLinks {
+    "self" => self_,
+    "seller" => seller,
+    "buyer" => buyer,
+    "operation" => operation,
+});
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(remote = "Asset")]
 struct BaseAsset {
@@ -1,7 +1,13 @@
+#[cfg(feature = "xdr")]
+use crate::error::Result;
 use crate::link::Link;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::rust::display_fromstr;
+#[cfg(feature = "xdr")]
+use stellar_base::xdr;
+#[cfg(feature = "xdr")]
+use xdr_rs_serialize::de::XDRIn;
 
 /// Transactions are commands that modify the ledger state and consist of one or more operations.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -59,6 +65,137 @@ pub struct Transaction {
     pub fee_bump_transaction: Option<FeeBumpTransaction>,
     /// The fee bump inner transaction.
     pub inner_transaction: Option<InnerTransaction>,
+    /// The conditions for the transaction to be valid, in Horizon 2.14 and later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preconditions: Option<Preconditions>,
+}
+
+/// The conditions for a transaction to be valid.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Preconditions {
+    /// The time range the transaction is valid in.
+    #[serde(
+        rename = "timebounds",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub time_bounds: Option<TimeBounds>,
+    /// The ledger range the transaction is valid in.
+    #[serde(
+        rename = "ledgerbounds",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ledger_bounds: Option<LedgerBounds>,
+    /// The minimum sequence number of the source account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_account_sequence: Option<String>,
+    /// The minimum number of seconds since the source account sequence number changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_account_sequence_age: Option<String>,
+    /// The minimum number of ledgers since the source account sequence number changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_account_sequence_ledger_gap: Option<u32>,
+    /// The additional signers required for the transaction to be valid.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_signers: Vec<String>,
+}
+
+/// A time range, as unix timestamps in seconds.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct TimeBounds {
+    /// The start of the range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_time: Option<String>,
+    /// The end of the range, `0` if the range has no end.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_time: Option<String>,
+}
+
+/// A ledger range.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct LedgerBounds {
+    /// The first ledger of the range.
+    pub min_ledger: u32,
+    /// The end of the range, `0` if the range has no end.
+    #[serde(default)]
+    pub max_ledger: u32,
+}
+
+impl TimeBounds {
+    /// Returns the time the transaction is valid after, if any.
+    pub fn valid_after(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.min_time.as_deref()?)
+    }
+
+    /// Returns the time the transaction is valid before, `None` if it
+    /// doesn't expire.
+    pub fn valid_before(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.max_time.as_deref()?)
+    }
+
+    /// Returns `true` if `time` is within the time range.
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        let after_min = self.valid_after().map(|min| time >= min).unwrap_or(true);
+        let before_max = self.valid_before().map(|max| time <= max).unwrap_or(true);
+        after_min && before_max
+    }
+}
+
+impl LedgerBounds {
+    /// Returns `true` if `ledger` is within the ledger range.
+    pub fn contains(&self, ledger: u32) -> bool {
+        ledger >= self.min_ledger && (self.max_ledger == 0 || ledger < self.max_ledger)
+    }
+}
+
+/// Parses a unix timestamp, `0` and malformed timestamps are `None`.
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    match timestamp.parse::<i64>() {
+        Ok(0) | Err(_) => None,
+        Ok(seconds) => Utc.timestamp_opt(seconds, 0).single(),
+    }
+}
+
+impl Transaction {
+    /// Returns `true` if this transaction is a fee bump transaction.
+    ///
+    /// The `hash` and `signatures` of fee bump transactions are the ones
+    /// of the fee bump wrapper, and `fee_account` is the account paying
+    /// the fee. The inner transaction is in `inner_transaction`.
+    pub fn is_fee_bump(&self) -> bool {
+        self.fee_bump_transaction.is_some()
+    }
+}
+
+#[cfg(feature = "xdr")]
+impl Transaction {
+    /// Decodes `envelope_xdr`, the transaction as submitted.
+    pub fn decode_envelope(&self) -> Result<xdr::TransactionEnvelope> {
+        decode_xdr(&self.envelope_xdr)
+    }
+
+    /// Decodes `result_xdr`, the fee charged and the operations results.
+    pub fn decode_result(&self) -> Result<xdr::TransactionResult> {
+        decode_xdr(&self.result_xdr)
+    }
+
+    /// Decodes `result_meta_xdr`, the ledger changes of the operations.
+    pub fn decode_result_meta(&self) -> Result<xdr::TransactionMeta> {
+        decode_xdr(&self.result_meta_xdr)
+    }
+
+    /// Decodes `fee_meta_xdr`, the ledger changes of charging the fee.
+    pub fn decode_fee_meta(&self) -> Result<xdr::LedgerEntryChanges> {
+        decode_xdr(&self.fee_meta_xdr)
+    }
+}
+
+#[cfg(feature = "xdr")]
+fn decode_xdr<T: XDRIn>(encoded: &str) -> Result<T> {
+    let bytes = base64::decode(encoded)?;
+    let (value, _) = T::read_xdr(&bytes).map_err(stellar_base::error::Error::XdrError)?;
+    Ok(value)
 }
 
 /// Fee bump transaction.
@@ -86,9 +223,154 @@ pub struct InnerTransaction {
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct TransactionResultCodes {
     /// The transaction.
-    pub transaction: String,
+    pub transaction: TransactionResultCode,
+    /// The inner transaction of fee bump transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner_transaction: Option<TransactionResultCode>,
     /// The operations.
-    pub operations: Vec<String>,
+    ///
+    /// Empty when the transaction failed before its operations were applied.
+    #[serde(default)]
+    pub operations: Vec<OperationResultCode>,
+}
+
+impl_str_enum! {
+    /// The result code of a transaction.
+    TransactionResultCode {
+        /// The fee bump inner transaction succeeded.
+        FeeBumpInnerSuccess => "tx_fee_bump_inner_success",
+        /// The fee bump inner transaction failed.
+        FeeBumpInnerFailed => "tx_fee_bump_inner_failed",
+        /// All operations succeeded.
+        Success => "tx_success",
+        /// One of the operations failed.
+        Failed => "tx_failed",
+        /// The ledger close time was before the `min_time`.
+        TooEarly => "tx_too_early",
+        /// The ledger close time was after the `max_time`.
+        TooLate => "tx_too_late",
+        /// No operation was specified.
+        MissingOperation => "tx_missing_operation",
+        /// The sequence number does not match the source account.
+        BadSeq => "tx_bad_seq",
+        /// Too few valid signatures or wrong network.
+        BadAuth => "tx_bad_auth",
+        /// The fee would bring the account below the reserve.
+        InsufficientBalance => "tx_insufficient_balance",
+        /// The source account was not found.
+        NoSourceAccount => "tx_no_source_account",
+        /// The fee is too small.
+        InsufficientFee => "tx_insufficient_fee",
+        /// Unused signatures attached to the transaction.
+        BadAuthExtra => "tx_bad_auth_extra",
+        /// An unknown error occurred.
+        InternalError => "tx_internal_error",
+        /// The transaction type is not supported.
+        NotSupported => "tx_not_supported",
+        /// The sponsorship is not confirmed.
+        BadSponsorship => "tx_bad_sponsorship",
+        /// The `min_seq_age` or `min_seq_ledger_gap` conditions are not met.
+        BadMinSeqAgeOrGap => "tx_bad_min_seq_age_or_gap",
+        /// The precondition is invalid.
+        Malformed => "tx_malformed",
+    }
+}
+
+impl_str_enum! {
+    /// The result code of an operation.
+    ///
+    /// Operation specific codes are shared between operations, for example
+    /// `op_underfunded` is returned by both payments and path payments.
+    OperationResultCode {
+        /// The operation succeeded.
+        Success => "op_success",
+        /// The inner operation failed, see the transaction result XDR.
+        Inner => "op_inner",
+        /// Too few valid signatures or wrong network.
+        BadAuth => "op_bad_auth",
+        /// The source account was not found.
+        NoSourceAccount => "op_no_source_account",
+        /// The operation is not supported.
+        NotSupported => "op_not_supported",
+        /// The source account has too many subentries.
+        TooManySubentries => "op_too_many_subentries",
+        /// The operation exceeded the work limit.
+        ExceededWorkLimit => "op_exceeded_work_limit",
+        /// The account is sponsoring too many entries.
+        TooManySponsoring => "op_too_many_sponsoring",
+        /// The operation is malformed.
+        Malformed => "op_malformed",
+        /// Not enough funds in the source account.
+        Underfunded => "op_underfunded",
+        /// The account would go below the minimum reserve.
+        LowReserve => "op_low_reserve",
+        /// The account or entry already exists.
+        AlreadyExists => "op_already_exists",
+        /// The source account is missing a trustline.
+        SrcNoTrust => "op_src_no_trust",
+        /// The source account is not authorized to send the asset.
+        SrcNotAuthorized => "op_src_not_authorized",
+        /// The destination account does not exist.
+        NoDestination => "op_no_destination",
+        /// The destination account is missing a trustline.
+        NoTrust => "op_no_trust",
+        /// The destination account is not authorized to hold the asset.
+        NotAuthorized => "op_not_authorized",
+        /// The destination account trustline limit would be exceeded.
+        LineFull => "op_line_full",
+        /// The asset issuer does not exist.
+        NoIssuer => "op_no_issuer",
+        /// Not enough offers to satisfy the path.
+        TooFewOffers => "op_too_few_offers",
+        /// The path would cross an offer from the source account.
+        OfferCrossSelf => "op_offer_cross_self",
+        /// The path would cost more than the send max.
+        OverSourceMax => "op_over_source_max",
+        /// The path would deliver less than the destination min.
+        UnderDestMin => "op_under_dest_min",
+        /// The account is missing a trustline for the selling asset.
+        SellNoTrust => "op_sell_no_trust",
+        /// The account is missing a trustline for the buying asset.
+        BuyNoTrust => "op_buy_no_trust",
+        /// The account is not authorized to sell the asset.
+        SellNotAuthorized => "op_sell_not_authorized",
+        /// The account is not authorized to buy the asset.
+        BuyNotAuthorized => "op_buy_not_authorized",
+        /// The offer would cross an offer from the same account.
+        CrossSelf => "op_cross_self",
+        /// The selling asset issuer does not exist.
+        SellNoIssuer => "op_sell_no_issuer",
+        /// The buying asset issuer does not exist.
+        BuyNoIssuer => "op_buy_no_issuer",
+        /// The offer, account, or entry was not found.
+        NotFound => "op_not_found",
+        /// The trustline limit is invalid.
+        InvalidLimit => "op_invalid_limit",
+        /// The account flags cannot be changed.
+        CantRevoke => "op_cant_revoke",
+        /// The issuer does not require authorization.
+        TrustNotRequired => "op_trust_not_required",
+        /// The account cannot trust itself.
+        SelfNotAllowed => "op_self_not_allowed",
+        /// The account has immutable flags set.
+        ImmutableSet => "op_immutable_set",
+        /// The account has subentries and cannot be merged.
+        HasSubEntries => "op_has_sub_entries",
+        /// The account sequence number is too far in the future to be merged.
+        SeqNumTooFar => "op_seq_num_too_far",
+        /// The destination account balance would overflow.
+        DestFull => "op_dest_full",
+        /// The bump sequence target is invalid.
+        BadSeq => "op_bad_seq",
+        /// The claimable balance cannot be claimed.
+        CannotClaim => "op_cannot_claim",
+        /// The entry is already sponsored.
+        AlreadySponsored => "op_already_sponsored",
+        /// The entry is not sponsored.
+        NotSponsored => "op_not_sponsored",
+        /// The sponsorship is invalid.
+        BadSponsorship => "op_bad_sponsorship",
+    }
 }
 
 /// Transaction links.
@@ -112,3 +394,79 @@ pub struct TransactionLinks {
     /// Link to the transaction.
     pub transaction: Link,
 }
+
+impl_links!(TransactionLinks {
+    "self" => self_,
+    "account" => account,
+    "ledger" => ledger,
+    "operations" => operations,
+    "effects" => effects,
+    "precedes" => precedes,
+    "succeeds" => succeeds,
+    "transaction" => transaction,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{OperationResultCode, TransactionResultCode};
+
+    #[test]
+    fn test_transaction_result_code_from_str() {
+        let code: TransactionResultCode = "tx_success".parse().unwrap();
+        assert_eq!(TransactionResultCode::Success, code);
+        let code: TransactionResultCode = "tx_bad_seq".parse().unwrap();
+        assert_eq!(TransactionResultCode::BadSeq, code);
+        let code: TransactionResultCode = "tx_too_late".parse().unwrap();
+        assert_eq!(TransactionResultCode::TooLate, code);
+    }
+
+    #[test]
+    fn test_transaction_result_code_unknown() {
+        let code: TransactionResultCode = "tx_from_the_future".parse().unwrap();
+        assert_eq!(
+            TransactionResultCode::Unknown("tx_from_the_future".to_string()),
+            code
+        );
+        assert_eq!("tx_from_the_future", code.to_string());
+    }
+
+    #[test]
+    fn test_transaction_result_code_display() {
+        assert_eq!("tx_failed", TransactionResultCode::Failed.to_string());
+        assert_eq!(
+            "tx_insufficient_fee",
+            TransactionResultCode::InsufficientFee.to_string()
+        );
+    }
+
+    #[test]
+    fn test_transaction_result_code_serde() {
+        let code: TransactionResultCode = serde_json::from_str("\"tx_failed\"").unwrap();
+        assert_eq!(TransactionResultCode::Failed, code);
+        assert_eq!("\"tx_failed\"", serde_json::to_string(&code).unwrap());
+    }
+
+    #[test]
+    fn test_operation_result_code_from_str() {
+        let code: OperationResultCode = "op_underfunded".parse().unwrap();
+        assert_eq!(OperationResultCode::Underfunded, code);
+        let code: OperationResultCode = "op_no_destination".parse().unwrap();
+        assert_eq!(OperationResultCode::NoDestination, code);
+        let code: OperationResultCode = "op_line_full".parse().unwrap();
+        assert_eq!(OperationResultCode::LineFull, code);
+        assert_eq!("op_no_trust", OperationResultCode::NoTrust.to_string());
+    }
+
+    #[test]
+    fn test_operation_result_code_unknown() {
+        let code: OperationResultCode = "op_from_the_future".parse().unwrap();
+        assert_eq!(
+            OperationResultCode::Unknown("op_from_the_future".to_string()),
+            code
+        );
+        assert_eq!(
+            "\"op_from_the_future\"",
+            serde_json::to_string(&code).unwrap()
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Server-sent events decoder.
+use futures::io::AsyncBufRead;
+use std::io;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// An event decoded by `Decoder`, borrowing the decoder buffers.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Event<'a> {
+    /// A message, with the last event id received on the connection.
+    Message {
+        id: Option<&'a str>,
+        name: &'a str,
+        data: &'a [u8],
+    },
+    /// The reconnection time requested by the server.
+    Retry(Duration),
+}
+
+/// A server-sent events decoder.
+///
+/// The decoder keeps its buffers between events, and between
+/// connections with `reset`, so that decoding an event only allocates
+/// when it's larger than the previous ones. Events borrow the decoder
+/// buffers until the next call to `poll_event`.
+#[derive(Debug, Default)]
+pub(crate) struct Decoder {
+    /// The bytes of the current line.
+    line: Vec<u8>,
+    /// Was the byte order mark removed?
+    processed_bom: bool,
+    /// The last event id received on the connection.
+    last_event_id: Option<String>,
+    /// The type of the current event.
+    event_type: Option<String>,
+    /// The data of the current event.
+    data: Vec<u8>,
+    /// Was the current event returned?
+    dispatched: bool,
+    /// Did the last line end with a `\r` at the end of the buffer?
+    ///
+    /// The `\n` of a `\r\n` line ending can be in the next chunk, it's
+    /// skipped instead of being read as an empty line.
+    pending_cr: bool,
+}
+
+/// What a line adds to the event being decoded.
+enum Line {
+    Field,
+    Dispatch,
+    /// The reconnection time, in milliseconds.
+    Retry(u64),
+}
+
+impl Decoder {
+    /// Creates a new decoder.
+    pub fn new() -> Decoder {
+        Decoder::default()
+    }
+
+    /// Resets the decoder state for a new connection, keeping its buffers.
+    pub fn reset(&mut self) {
+        self.line.clear();
+        self.processed_bom = false;
+        self.last_event_id = None;
+        self.event_type = None;
+        self.data.clear();
+        self.dispatched = false;
+        self.pending_cr = false;
+    }
+
+    /// Polls the next event from `reader`.
+    ///
+    /// Returns `None` at the end of `reader`, an event that is not
+    /// terminated by an empty line is dropped.
+    pub fn poll_event<R>(
+        &mut self,
+        mut reader: Pin<&mut R>,
+        cx: &mut Context,
+    ) -> Poll<Option<io::Result<Event<'_>>>>
+    where
+        R: AsyncBufRead + ?Sized,
+    {
+        if self.dispatched {
+            self.dispatched = false;
+            self.event_type = None;
+            self.data.clear();
+        }
+        loop {
+            match self.poll_line(reader.as_mut(), cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(err)) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(Ok(false)) => return Poll::Ready(None),
+                Poll::Ready(Ok(true)) => {}
+            }
+            let line = mem::take(&mut self.line);
+            let result = self.process_line(&line);
+            self.line = line;
+            self.line.clear();
+            match result {
+                Err(err) => return Poll::Ready(Some(Err(err))),
+                Ok(Line::Field) => {}
+                Ok(Line::Retry(time)) => {
+                    return Poll::Ready(Some(Ok(Event::Retry(Duration::from_millis(time)))));
+                }
+                Ok(Line::Dispatch) => {
+                    self.dispatched = true;
+                    return Poll::Ready(Some(Ok(Event::Message {
+                        id: self.last_event_id.as_deref(),
+                        name: self.event_type.as_deref().unwrap_or("message"),
+                        data: &self.data,
+                    })));
+                }
+            }
+        }
+    }
+
+    /// Reads the next line, with its terminator, in `self.line`.
+    ///
+    /// Lines end with `\n`, `\r`, or `\r\n`. Returns `false` at the end
+    /// of `reader`.
+    fn poll_line<R>(&mut self, mut reader: Pin<&mut R>, cx: &mut Context) -> Poll<io::Result<bool>>
+    where
+        R: AsyncBufRead + ?Sized,
+    {
+        loop {
+            let available = match reader.as_mut().poll_fill_buf(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Ready(Ok(available)) => available,
+            };
+            if available.is_empty() {
+                return Poll::Ready(Ok(!self.line.is_empty()));
+            }
+            if mem::take(&mut self.pending_cr) && available[0] == b'\n' {
+                reader.as_mut().consume(1);
+                continue;
+            }
+            match memchr::memchr2(b'\r', b'\n', available) {
+                Some(i) => {
+                    self.line.extend_from_slice(&available[..=i]);
+                    let used = match available.get(i + 1) {
+                        Some(b'\n') if available[i] == b'\r' => i + 2,
+                        None if available[i] == b'\r' => {
+                            self.pending_cr = true;
+                            i + 1
+                        }
+                        _ => i + 1,
+                    };
+                    reader.as_mut().consume(used);
+                    return Poll::Ready(Ok(true));
+                }
+                None => {
+                    self.line.extend_from_slice(available);
+                    let used = available.len();
+                    reader.as_mut().consume(used);
+                }
+            }
+        }
+    }
+
+    /// Adds the field in `line` to the current event.
+    fn process_line(&mut self, line: &[u8]) -> io::Result<Line> {
+        let line = std::str::from_utf8(line).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })?;
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let line = match line.strip_prefix('\u{feff}') {
+            Some(rest) if !self.processed_bom => {
+                self.processed_bom = true;
+                rest
+            }
+            _ => line,
+        };
+        let mut parts = line.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some("retry"), Some(value)) => {
+                let value = strip_leading_space(value);
+                if value.chars().all(|c| c.is_ascii_digit()) {
+                    if let Ok(time) = value.parse::<u64>() {
+                        return Ok(Line::Retry(time));
+                    }
+                }
+            }
+            (Some("event"), Some(value)) => {
+                self.event_type = Some(strip_leading_space(value).to_string());
+            }
+            (Some("data"), value) => {
+                if let Some(value) = value {
+                    self.data
+                        .extend_from_slice(strip_leading_space(value).as_bytes());
+                }
+                self.data.push(b'\n');
+            }
+            (Some("id"), Some(value)) if !value.contains('\0') => {
+                let last_event_id = self.last_event_id.get_or_insert_with(String::new);
+                last_event_id.clear();
+                last_event_id.push_str(strip_leading_space(value));
+            }
+            (Some(""), None) => {
+                if self.data.is_empty() {
+                    self.event_type = None;
+                } else {
+                    if self.data.ends_with(b"\n") {
+                        self.data.pop();
+                    }
+                    return Ok(Line::Dispatch);
+                }
+            }
+            _ => {}
+        }
+        Ok(Line::Field)
+    }
+}
+
+/// Removes a leading space from `value`.
+fn strip_leading_space(value: &str) -> &str {
+    value.strip_prefix(' ').unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decoder, Event};
+    use futures::executor::block_on;
+    use futures::future::poll_fn;
+    use futures::io::{AsyncBufRead, BufReader};
+    use futures::stream::{self, TryStreamExt};
+    use std::io;
+    use std::pin::Pin;
+    use std::time::Duration;
+
+    #[derive(Debug, PartialEq)]
+    enum Owned {
+        Message(Option<String>, String, String),
+        Retry(Duration),
+    }
+
+    fn reader(chunks: &[&str]) -> impl AsyncBufRead {
+        let chunks: Vec<io::Result<Vec<u8>>> = chunks
+            .iter()
+            .map(|chunk| Ok(chunk.as_bytes().to_vec()))
+            .collect();
+        stream::iter(chunks).into_async_read()
+    }
+
+    fn decode_with(decoder: &mut Decoder, chunks: &[&str]) -> io::Result<Vec<Owned>> {
+        decode_from(decoder, Box::pin(reader(chunks)))
+    }
+
+    fn decode_from(
+        decoder: &mut Decoder,
+        mut reader: Pin<Box<dyn AsyncBufRead>>,
+    ) -> io::Result<Vec<Owned>> {
+        let mut events = Vec::new();
+        loop {
+            let event = block_on(poll_fn(|cx| {
+                decoder.poll_event(reader.as_mut(), cx).map(|event| {
+                    event.map(|event| {
+                        event.map(|event| match event {
+                            Event::Message { id, name, data } => Owned::Message(
+                                id.map(|id| id.to_string()),
+                                name.to_string(),
+                                String::from_utf8(data.to_vec()).unwrap(),
+                            ),
+                            Event::Retry(duration) => Owned::Retry(duration),
+                        })
+                    })
+                })
+            }));
+            match event {
+                None => return Ok(events),
+                Some(event) => events.push(event?),
+            }
+        }
+    }
+
+    fn decode(chunks: &[&str]) -> io::Result<Vec<Owned>> {
+        decode_with(&mut Decoder::new(), chunks)
+    }
+
+    fn message(id: Option<&str>, name: &str, data: &str) -> Owned {
+        Owned::Message(
+            id.map(|id| id.to_string()),
+            name.to_string(),
+            data.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_decode_messages() {
+        let events = decode(&["id: 1\ndata: {\"a\":1}\n\nid: 2\ndata: {}\n\n"]).unwrap();
+        assert_eq!(
+            vec![
+                message(Some("1"), "message", "{\"a\":1}"),
+                message(Some("2"), "message", "{}"),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn test_decode_messages_split_across_chunks() {
+        let events = decode(&["i", "d: 1\r\nda", "ta: a\r\ndata:b\r", "\n", "\r\n"]).unwrap();
+        assert_eq!(vec![message(Some("1"), "message", "a\nb")], events);
+    }
+
+    #[test]
+    fn test_decode_crlf_split_across_chunks() {
+        let events = decode(&["data: a\r", "\ndata: b\r", "\n\r", "\ndata: c\r\n\r\n"]).unwrap();
+        assert_eq!(
+            vec![
+                message(None, "message", "a\nb"),
+                message(None, "message", "c"),
+            ],
+            events
+        );
+        let events = decode(&["data: a\r", "\r", "data: b\n\n"]).unwrap();
+        assert_eq!(
+            vec![message(None, "message", "a"), message(None, "message", "b")],
+            events
+        );
+    }
+
+    #[test]
+    fn test_decode_with_small_read_buffer() {
+        let chunks = ["id: 1\r\ndata: {\"a\":1}\r\n\r\nid: 2\ndata: {}\n\n"];
+        for capacity in 1..=8 {
+            let reader = BufReader::with_capacity(capacity, reader(&chunks));
+            let events = decode_from(&mut Decoder::new(), Box::pin(reader)).unwrap();
+            assert_eq!(
+                vec![
+                    message(Some("1"), "message", "{\"a\":1}"),
+                    message(Some("2"), "message", "{}"),
+                ],
+                events,
+                "capacity {}",
+                capacity
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_fields() {
+        let events = decode(&[
+            "\u{feff}: comment\nretry:1000\nretry: 10\nretry: 1s\nevent: open\ndata\n\n",
+            "data: \"hello\"\nunknown: field\n\n",
+            "id: \0\n\n\ndata: last\n",
+        ])
+        .unwrap();
+        assert_eq!(
+            vec![
+                Owned::Retry(Duration::from_millis(1000)),
+                Owned::Retry(Duration::from_millis(10)),
+                message(None, "open", ""),
+                message(None, "message", "\"hello\""),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn test_last_event_id_persists() {
+        let events = decode(&["id: 1\ndata: a\n\ndata: b\n\n"]).unwrap();
+        assert_eq!(
+            vec![
+                message(Some("1"), "message", "a"),
+                message(Some("1"), "message", "b"),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut decoder = Decoder::new();
+        let events = decode_with(&mut decoder, &["id: 1\nevent: open\ndata: a"]).unwrap();
+        assert!(events.is_empty());
+        decoder.reset();
+        let events = decode_with(&mut decoder, &["data: b\n\n"]).unwrap();
+        assert_eq!(vec![message(None, "message", "b")], events);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let chunks: Vec<io::Result<Vec<u8>>> = vec![Ok(b"data: \xff\n\n".to_vec())];
+        let mut reader = stream::iter(chunks).into_async_read();
+        let mut decoder = Decoder::new();
+        let event = block_on(poll_fn(|cx| {
+            decoder
+                .poll_event(Pin::new(&mut reader), cx)
+                .map(|event| event.map(|event| event.map(|_| ())))
+        }));
+        let err = event.unwrap().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
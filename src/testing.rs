@@ -0,0 +1,93 @@
+//! Test helpers shared by the unit tests.
+use crate::client::HorizonClient;
+use crate::error::Result;
+use crate::headers::HeaderMap;
+use crate::request::{Request, StreamRequest};
+use futures::future::BoxFuture;
+use futures::Stream;
+use std::sync::Mutex;
+use url::Url;
+
+const EMPTY_PAGE: &str = r#"{"_embedded": {"records": []}}"#;
+
+type Respond = Box<dyn Fn(&Url) -> Result<String> + Send + Sync>;
+
+/// A client that answers requests with the body returned by `respond`
+/// for their uri, and records the uris.
+pub(crate) struct MockClient {
+    respond: Respond,
+    pub(crate) uris: Mutex<Vec<Url>>,
+}
+
+impl MockClient {
+    pub(crate) fn new<F>(respond: F) -> MockClient
+    where
+        F: Fn(&Url) -> Result<String> + Send + Sync + 'static,
+    {
+        MockClient {
+            respond: Box::new(respond),
+            uris: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a client that returns empty pages.
+    pub(crate) fn empty() -> MockClient {
+        MockClient::pages(Vec::<String>::new())
+    }
+
+    /// Returns a client that returns `pages` in order, then empty pages.
+    pub(crate) fn pages<S: Into<String>>(pages: Vec<S>) -> MockClient {
+        let pages = Mutex::new(pages.into_iter().map(Into::into).collect::<Vec<String>>());
+        MockClient::new(move |_| {
+            let mut pages = pages.lock().unwrap();
+            if pages.is_empty() {
+                Ok(EMPTY_PAGE.to_string())
+            } else {
+                Ok(pages.remove(0))
+            }
+        })
+    }
+}
+
+impl HorizonClient for MockClient {
+    fn request<'a, R: Request + 'a>(
+        &'a self,
+        req: R,
+    ) -> BoxFuture<'a, Result<(HeaderMap, R::Response)>> {
+        let host: Url = "https://horizon.stellar.org".parse().unwrap();
+        let uri = req.uri(&host).unwrap();
+        let body = (self.respond)(&uri);
+        self.uris.lock().unwrap().push(uri);
+        Box::pin(async move {
+            let response = serde_json::from_str(&body?)?;
+            Ok((HeaderMap::new(), response))
+        })
+    }
+
+    fn stream<'a, R: StreamRequest + 'static>(
+        &'a self,
+        _req: R,
+    ) -> Result<Box<dyn Stream<Item = Result<R::Resource>> + 'static + Send + Unpin>> {
+        unimplemented!()
+    }
+}
+
+/// Returns a page of operations with the ids and paging tokens `ids`.
+///
+/// The operations are copies of the first operation of the
+/// `all_operations.json` fixture.
+pub(crate) fn operations_page<T: ToString>(ids: &[T]) -> String {
+    let operations: serde_json::Value =
+        serde_json::from_str(include_str!("../tests/fixtures/all_operations.json")).unwrap();
+    let template = &operations["_embedded"]["records"][0];
+    let records: Vec<serde_json::Value> = ids
+        .iter()
+        .map(|id| {
+            let mut record = template.clone();
+            record["id"] = id.to_string().into();
+            record["paging_token"] = id.to_string().into();
+            record
+        })
+        .collect();
+    serde_json::json!({ "_embedded": { "records": records } }).to_string()
+}
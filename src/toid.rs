@@ -0,0 +1,109 @@
+//! Total order ids.
+use crate::error::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+const LEDGER_SEQUENCE_SHIFT: u32 = 32;
+const TRANSACTION_ORDER_SHIFT: u32 = 12;
+const TRANSACTION_ORDER_MASK: i64 = (1 << 20) - 1;
+const OPERATION_INDEX_MASK: i64 = (1 << 12) - 1;
+
+/// A total order id.
+///
+/// Horizon uses total order ids as id and paging token of transactions
+/// and operations. They sort by ledger, then by the transaction order
+/// in the ledger, and then by the operation index in the transaction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Toid {
+    ledger_sequence: i32,
+    transaction_order: i32,
+    operation_index: i32,
+}
+
+impl Toid {
+    /// Creates a new total order id.
+    pub fn new(ledger_sequence: i32, transaction_order: i32, operation_index: i32) -> Toid {
+        Toid {
+            ledger_sequence,
+            transaction_order,
+            operation_index,
+        }
+    }
+
+    /// Creates a total order id from its `i64` representation.
+    pub fn from_i64(id: i64) -> Toid {
+        Toid {
+            ledger_sequence: (id >> LEDGER_SEQUENCE_SHIFT) as i32,
+            transaction_order: ((id >> TRANSACTION_ORDER_SHIFT) & TRANSACTION_ORDER_MASK) as i32,
+            operation_index: (id & OPERATION_INDEX_MASK) as i32,
+        }
+    }
+
+    /// Returns the `i64` representation of the total order id.
+    pub fn to_i64(&self) -> i64 {
+        ((self.ledger_sequence as i64) << LEDGER_SEQUENCE_SHIFT)
+            | ((self.transaction_order as i64 & TRANSACTION_ORDER_MASK) << TRANSACTION_ORDER_SHIFT)
+            | (self.operation_index as i64 & OPERATION_INDEX_MASK)
+    }
+
+    /// Returns the ledger sequence.
+    pub fn ledger_sequence(&self) -> i32 {
+        self.ledger_sequence
+    }
+
+    /// Returns the order of the transaction in the ledger.
+    pub fn transaction_order(&self) -> i32 {
+        self.transaction_order
+    }
+
+    /// Returns the index of the operation in the transaction.
+    pub fn operation_index(&self) -> i32 {
+        self.operation_index
+    }
+}
+
+impl FromStr for Toid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Toid> {
+        let id = i64::from_str(s).map_err(|_| Error::InvalidToid)?;
+        Ok(Toid::from_i64(id))
+    }
+}
+
+impl fmt::Display for Toid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_i64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Toid;
+    use crate::error::Error;
+
+    #[test]
+    fn test_toid_from_i64() {
+        let toid = Toid::from_i64(12884905985);
+        assert_eq!(3, toid.ledger_sequence());
+        assert_eq!(1, toid.transaction_order());
+        assert_eq!(1, toid.operation_index());
+        assert_eq!(12884905985, toid.to_i64());
+    }
+
+    #[test]
+    fn test_toid_parse_and_display() {
+        let toid: Toid = "12884905985".parse().unwrap();
+        assert_eq!(Toid::new(3, 1, 1), toid);
+        assert_eq!("12884905985", toid.to_string());
+        assert!(matches!("now".parse::<Toid>(), Err(Error::InvalidToid)));
+    }
+
+    #[test]
+    fn test_toid_ordering() {
+        assert!(Toid::new(3, 1, 1) < Toid::new(3, 1, 2));
+        assert!(Toid::new(3, 1, 2) < Toid::new(3, 2, 0));
+        assert!(Toid::new(3, 2, 0) < Toid::new(4, 0, 0));
+        assert!(Toid::new(3, 1, 2).to_i64() < Toid::new(3, 2, 0).to_i64());
+    }
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "blocking")]
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use stellar_horizon::api;
+use stellar_horizon::blocking::HorizonHttpClient;
+use stellar_horizon::request::PageRequest;
+
+/// Starts a server that answers the n-th connection with the n-th
+/// response, repeating the last response for the following connections.
+///
+/// Connections are kept open after the response is sent. Returns the
+/// server url and a channel with the requests received.
+fn serve(responses: Vec<String>) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut sockets = Vec::new();
+        for connection in 0.. {
+            let response = &responses[connection.min(responses.len() - 1)];
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = socket.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let _ = tx.send(String::from_utf8_lossy(&request).to_lowercase());
+            socket.write_all(response.as_bytes()).unwrap();
+            sockets.push(socket);
+        }
+    });
+    (format!("http://{}", addr), rx)
+}
+
+fn json_response(body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+#[test]
+fn test_blocking_request() {
+    let ledger = json::parse(include_str!("./fixtures/ledger.json"))
+        .unwrap()
+        .dump();
+    let (host, requests) = serve(vec![json_response(&ledger)]);
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let ledger = client.request(api::ledgers::single(7)).unwrap();
+    assert!(!ledger.hash.is_empty());
+    assert!(requests.recv().unwrap().starts_with("get /ledgers/7 "));
+}
+
+#[test]
+fn test_blocking_fetch_all() {
+    let ledgers = include_str!("./fixtures/all_ledgers.json");
+    let empty = r#"{"_embedded": {"records": []}}"#;
+    let (host, requests) = serve(vec![json_response(ledgers), json_response(empty)]);
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let ledgers = client
+        .fetch_all(api::ledgers::all().with_limit(10))
+        .unwrap();
+    assert!(!ledgers.is_empty());
+    assert!(!requests.recv().unwrap().contains("cursor="));
+    let second = requests.recv().unwrap();
+    let cursor = format!("cursor={}", ledgers.last().unwrap().paging_token);
+    assert!(second.contains(&cursor));
+}
+
+#[test]
+fn test_blocking_stream() {
+    let ledger = json::parse(include_str!("./fixtures/ledger.json"))
+        .unwrap()
+        .dump();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\nid: 1-1\ndata: {}\n\nid: 1-2\ndata: {}\n\n",
+        ledger, ledger
+    );
+    let (host, _requests) = serve(vec![response]);
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let ledgers: Vec<_> = client
+        .stream(api::ledgers::all())
+        .unwrap()
+        .take(2)
+        .collect();
+    assert_eq!(2, ledgers.len());
+    assert!(ledgers.iter().all(|ledger| ledger.is_ok()));
+}
+
+#[test]
+fn test_blocking_request_while_stream_waits() {
+    let ledger = json::parse(include_str!("./fixtures/ledger.json"))
+        .unwrap()
+        .dump();
+    let events = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n".to_string();
+    let (host, requests) = serve(vec![events, json_response(&ledger)]);
+    let client = Arc::new(HorizonHttpClient::new_from_str(&host).unwrap());
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    thread::spawn(move || stream.next());
+    assert!(requests
+        .recv()
+        .unwrap()
+        .contains("accept: text/event-stream"));
+
+    let (tx, rx) = mpsc::channel();
+    let request_client = client.clone();
+    thread::spawn(move || {
+        let ledger = request_client.request(api::ledgers::single(7));
+        tx.send(ledger.is_ok()).unwrap();
+    });
+    assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+}
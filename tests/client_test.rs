@@ -10,6 +10,7 @@ use stellar_base::{Asset, KeyPair, Network, Operation, PublicKey, Transaction};
 use stellar_horizon::api;
 use stellar_horizon::api::aggregations::Resolution;
 use stellar_horizon::client::{HorizonClient, HorizonHttpClient};
+use stellar_horizon::cursor::{CursorStore, FileCursorStore};
 use stellar_horizon::headers::{rate_limit_limit, rate_limit_remaining, rate_limit_reset};
 use stellar_horizon::request::{Order, PageRequest};
 use tokio::stream::StreamExt;
@@ -45,6 +46,26 @@ async fn test_root() {
     assert!(!response.horizon_version.is_empty());
 }
 
+#[tokio::test]
+async fn test_public_client() {
+    let client = HorizonHttpClient::public().unwrap();
+    let (_, response) = client.request(api::root::root()).await.unwrap();
+    assert_eq!(
+        "Public Global Stellar Network ; September 2015",
+        response.network_passphrase
+    );
+}
+
+#[tokio::test]
+async fn test_testnet_client() {
+    let client = HorizonHttpClient::testnet().unwrap();
+    let (_, response) = client.request(api::root::root()).await.unwrap();
+    assert_eq!(
+        "Test SDF Network ; September 2015",
+        response.network_passphrase
+    );
+}
+
 #[tokio::test]
 async fn test_headers() {
     let client = new_client();
@@ -81,13 +102,41 @@ async fn test_all_ledgers() {
 #[tokio::test]
 async fn test_stream_all_ledgers() {
     let client = new_client();
-    let req = api::ledgers::all().with_order(&Order::Descending);
+    let req = api::ledgers::all().with_order(&Order::Ascending);
     let mut stream = client.stream(req).unwrap().take(10);
     while let Some(event) = stream.next().await {
         assert!(!event.unwrap().paging_token.is_empty());
     }
 }
 
+#[tokio::test]
+async fn test_stream_all_ledgers_with_small_read_buffer() {
+    let client = new_client().with_sse_read_buffer_size(16);
+    assert_eq!(16, client.sse_read_buffer_size());
+    let req = api::ledgers::all().with_order(&Order::Ascending);
+    let mut stream = client.stream(req).unwrap().take(3);
+    while let Some(event) = stream.next().await {
+        assert!(!event.unwrap().paging_token.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_stream_durable_all_ledgers() {
+    let client = new_client();
+    let path = env::temp_dir().join("stellar-horizon-test-stream-durable");
+    let _ = std::fs::remove_file(&path);
+    let req = api::ledgers::all().with_cursor_now();
+    let mut stream = client
+        .stream_durable(req, FileCursorStore::new(&path))
+        .unwrap()
+        .take(2);
+    let ledger = stream.next().await.unwrap().unwrap();
+    let store = FileCursorStore::new(&path);
+    assert_eq!(None, store.load().unwrap());
+    stream.next().await.unwrap().unwrap();
+    assert_eq!(Some(ledger.paging_token), store.load().unwrap());
+}
+
 #[tokio::test]
 async fn test_single_account() {
     let client = new_client();
@@ -131,7 +180,7 @@ async fn test_all_transactions() {
 #[tokio::test]
 async fn test_stream_all_transactions() {
     let client = new_client();
-    let req = api::transactions::all().with_order(&Order::Descending);
+    let req = api::transactions::all().with_order(&Order::Ascending);
     let mut stream = client.stream(req).unwrap().take(10);
     while let Some(event) = stream.next().await {
         assert!(!event.unwrap().paging_token.is_empty());
@@ -193,7 +242,7 @@ async fn test_all_trades() {
 #[tokio::test]
 async fn test_stream_all_trades() {
     let client = new_client();
-    let req = api::trades::all().with_order(&Order::Descending);
+    let req = api::trades::all().with_order(&Order::Ascending);
     let mut stream = client.stream(req).unwrap().take(10);
     while let Some(event) = stream.next().await {
         assert!(!event.unwrap().paging_token.is_empty());
@@ -1,4 +1,6 @@
+use stellar_horizon::api;
 use stellar_horizon::page::Page;
+use stellar_horizon::request::PageRequest;
 use stellar_horizon::resources::Ledger;
 
 #[test]
@@ -28,3 +30,26 @@ fn test_serialize_page_to_json() {
         back.links.as_ref().unwrap().previous
     );
 }
+
+#[test]
+fn test_page_last_cursor() {
+    let json = include_str!("./fixtures/all_ledgers.json");
+    let mut page: Page<Ledger> = serde_json::from_str(json).unwrap();
+    assert_eq!(Some("12884901888".to_string()), page.next_cursor());
+    assert_eq!(Some("12884901888".to_string()), page.last_cursor());
+
+    let req = api::ledgers::all().with_cursor_after(&page);
+    assert_eq!(&Some("12884901888".to_string()), req.cursor());
+
+    page.records.clear();
+    page.links.as_mut().unwrap().next.href =
+        "https://horizon.stellar.org/ledgers?cursor=42&limit=3&order=asc".to_string();
+    assert_eq!(Some("42".to_string()), page.last_cursor());
+
+    page.links = None;
+    assert_eq!(None, page.last_cursor());
+    let req = api::ledgers::all()
+        .with_cursor("7")
+        .with_cursor_after(&page);
+    assert_eq!(&Some("7".to_string()), req.cursor());
+}
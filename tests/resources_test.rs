@@ -1,3 +1,5 @@
+use stellar_horizon::horizon_error::HorizonError;
+use stellar_horizon::link::Links;
 use stellar_horizon::page::Page;
 use stellar_horizon::resources::*;
 
@@ -21,6 +23,11 @@ impl_serde_test!(
     Account,
     "./fixtures/account_with_sponsor.json"
 );
+impl_serde_test!(
+    test_account_with_liquidity_pool_serde,
+    Account,
+    "./fixtures/account_with_liquidity_pool.json"
+);
 impl_serde_test!(
     test_account_data_serde,
     AccountData,
@@ -51,7 +58,23 @@ impl_serde_test!(
     "./fixtures/all_offers_with_sponsor.json"
 );
 impl_serde_test!(test_root_serde, Root, "./fixtures/root.json");
+impl_serde_test!(
+    test_transaction_with_preconditions_serde,
+    Transaction,
+    "./fixtures/transaction_with_preconditions.json"
+);
+impl_serde_test!(
+    test_transaction_fee_bump_serde,
+    Transaction,
+    "./fixtures/transaction_fee_bump.json"
+);
+impl_serde_test!(test_health_serde, Health, "./fixtures/health.json");
 impl_serde_test!(test_trades_serde, Page<Trade>, "./fixtures/all_trades.json");
+impl_serde_test!(
+    test_trade_liquidity_pool_serde,
+    Trade,
+    "./fixtures/trade_liquidity_pool.json"
+);
 impl_serde_test!(
     test_paths_strict_receive_serde,
     Page<Path>,
@@ -72,11 +95,36 @@ impl_serde_test!(
     Page<Effect>,
     "./fixtures/all_effects.json"
 );
+impl_serde_test!(
+    test_effects_trade_and_unknown_serde,
+    Page<Effect>,
+    "./fixtures/effects_trade_and_unknown.json"
+);
+impl_serde_test!(
+    test_effects_without_account_serde,
+    Page<Effect>,
+    "./fixtures/effects_without_account.json"
+);
 impl_serde_test!(
     test_operations_serde,
     Page<Operation>,
     "./fixtures/all_operations.json"
 );
+impl_serde_test!(
+    test_operation_with_transaction_serde,
+    Operation,
+    "./fixtures/operation_with_transaction.json"
+);
+impl_serde_test!(
+    test_set_options_operation_serde,
+    Operation,
+    "./fixtures/operation_set_options.json"
+);
+impl_serde_test!(
+    test_account_merge_operation_serde,
+    Operation,
+    "./fixtures/operation_account_merge.json"
+);
 impl_serde_test!(
     test_payments_serde,
     Page<Payment>,
@@ -98,6 +146,272 @@ fn test_operations_base() {
     }
 }
 
+#[test]
+fn test_operation_with_joined_transaction() {
+    let original_json_value =
+        json::parse(include_str!("./fixtures/operation_with_transaction.json")).unwrap();
+    let original_json = original_json_value.dump();
+    let operation: Operation = serde_json::from_str(&original_json).unwrap();
+    let transaction = operation.base().transaction.as_ref().unwrap();
+    assert_eq!(operation.base().transaction_hash, transaction.hash);
+}
+
+#[test]
+fn test_set_options_operation_fields() {
+    let operation: Operation =
+        serde_json::from_str(include_str!("./fixtures/operation_set_options.json")).unwrap();
+    let op = match operation {
+        Operation::SetOptions(op) => op,
+        other => panic!("expected set options, got {:?}", other),
+    };
+    assert_eq!(Some("example.com"), op.home_domain.as_deref());
+    assert_eq!(
+        Some("GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623"),
+        op.inflation_destination.as_deref()
+    );
+    assert_eq!(Some(1), op.master_key_weight);
+    let signer = op.signer_update().unwrap();
+    assert_eq!(
+        "GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623",
+        signer.key
+    );
+    assert_eq!(2, signer.weight);
+    assert!(!signer.is_removal());
+    assert_eq!(
+        ThresholdsUpdate {
+            low: Some(1),
+            medium: Some(2),
+            high: Some(3),
+        },
+        op.thresholds_update()
+    );
+    assert_eq!(
+        AccountFlags {
+            auth_required: true,
+            auth_revocable: true,
+            auth_immutable: false,
+            auth_clawback_enabled: false,
+        },
+        op.set_account_flags()
+    );
+    assert_eq!(vec!["auth_required", "auth_revocable"], op.set_flags_s);
+    assert!(op.cleared_account_flags().auth_clawback_enabled);
+    assert_eq!(vec!["auth_clawback_enabled"], op.clear_flags_s);
+}
+
+#[test]
+fn test_set_options_operation_without_signer() {
+    let operations: Page<Operation> =
+        serde_json::from_str(include_str!("./fixtures/all_operations.json")).unwrap();
+    let op = operations
+        .records
+        .into_iter()
+        .find_map(|operation| match operation {
+            Operation::SetOptions(op) if op.signer_key.is_none() => Some(op),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(None, op.signer_update());
+    assert!(!op.set_account_flags().auth_required);
+}
+
+#[test]
+fn test_account_merge_operation_fields() {
+    let operation: Operation =
+        serde_json::from_str(include_str!("./fixtures/operation_account_merge.json")).unwrap();
+    let op = match operation {
+        Operation::AccountMerge(op) => op,
+        other => panic!("expected account merge, got {:?}", other),
+    };
+    assert_eq!(
+        "GAYOLLLUIZE4DZMBB2ZBKGBUBZLIOYU6XFLW37GBP2VZD3ABNXCW4BVA",
+        op.account
+    );
+    assert_eq!(op.base.source_account, op.account);
+    assert_eq!(
+        "GDHCYXWSMCGPN7S5VBCSDVNXUMRI62MCRVK7DBULCDBBIEQE76DND623",
+        op.into
+    );
+    assert_eq!(None, op.into_muxed);
+    assert_eq!(None, op.account_muxed_id);
+}
+
+#[test]
+fn test_operation_types() {
+    let table = [
+        (0, "create_account"),
+        (1, "payment"),
+        (2, "path_payment_strict_receive"),
+        (3, "manage_sell_offer"),
+        (4, "create_passive_sell_offer"),
+        (5, "set_options"),
+        (6, "change_trust"),
+        (7, "allow_trust"),
+        (8, "account_merge"),
+        (9, "inflation"),
+        (10, "manage_data"),
+        (11, "bump_sequence"),
+        (12, "manage_buy_offer"),
+        (13, "path_payment_strict_send"),
+        (14, "create_claimable_balance"),
+        (15, "claim_claimable_balance"),
+        (16, "begin_sponsoring_future_reserves"),
+        (17, "end_sponsoring_future_reserves"),
+        (18, "revoke_sponsorship"),
+        (19, "clawback"),
+        (20, "clawback_claimable_balance"),
+        (21, "set_trust_line_flags"),
+        (22, "liquidity_pool_deposit"),
+        (23, "liquidity_pool_withdraw"),
+        (24, "invoke_host_function"),
+        (25, "extend_footprint_ttl"),
+        (26, "restore_footprint"),
+    ];
+    let known: Vec<_> = OperationType::known()
+        .iter()
+        .map(|type_| (type_.as_i(), type_.as_str()))
+        .collect();
+    assert_eq!(table.to_vec(), known);
+    for (code, name) in table.iter() {
+        let type_ = OperationType::from_i(*code);
+        assert_eq!(Some(type_), OperationType::from_name(name));
+        assert_eq!(*name, type_.to_string());
+    }
+    assert_eq!(OperationType::Unknown(99), OperationType::from_i(99));
+    assert_eq!(99, OperationType::Unknown(99).as_i());
+    assert_eq!(None, OperationType::from_name("unknown"));
+}
+
+#[test]
+fn test_effect_types() {
+    let table = [
+        (0, "account_created"),
+        (1, "account_removed"),
+        (2, "account_credited"),
+        (3, "account_debited"),
+        (4, "account_thresholds_updated"),
+        (5, "account_home_domain_updated"),
+        (6, "account_flags_updated"),
+        (7, "account_inflation_destination_updated"),
+        (10, "signer_created"),
+        (11, "signer_removed"),
+        (12, "signer_updated"),
+        (20, "trustline_created"),
+        (21, "trustline_removed"),
+        (22, "trustline_updated"),
+        (23, "trustline_authorized"),
+        (24, "trustline_deauthorized"),
+        (25, "trustline_authorized_to_maintain_liabilities"),
+        (26, "trustline_flags_updated"),
+        (30, "offer_created"),
+        (31, "offer_removed"),
+        (32, "offer_updated"),
+        (33, "trade"),
+        (40, "data_created"),
+        (41, "data_removed"),
+        (42, "data_updated"),
+        (43, "sequence_bumped"),
+        (50, "claimable_balance_created"),
+        (51, "claimable_balance_claimant_created"),
+        (52, "claimable_balance_claimed"),
+        (60, "account_sponsorship_created"),
+        (61, "account_sponsorship_updated"),
+        (62, "account_sponsorship_removed"),
+        (63, "trustline_sponsorship_created"),
+        (64, "trustline_sponsorship_updated"),
+        (65, "trustline_sponsorship_removed"),
+        (66, "data_sponsorship_created"),
+        (67, "data_sponsorship_updated"),
+        (68, "data_sponsorship_removed"),
+        (69, "claimable_balance_sponsorship_created"),
+        (70, "claimable_balance_sponsorship_updated"),
+        (71, "claimable_balance_sponsorship_removed"),
+        (72, "signer_sponsorship_created"),
+        (73, "signer_sponsorship_updated"),
+        (74, "signer_sponsorship_removed"),
+        (80, "claimable_balance_clawed_back"),
+        (90, "liquidity_pool_deposited"),
+        (91, "liquidity_pool_withdrew"),
+        (92, "liquidity_pool_trade"),
+        (93, "liquidity_pool_created"),
+        (94, "liquidity_pool_removed"),
+        (95, "liquidity_pool_revoked"),
+        (96, "contract_credited"),
+        (97, "contract_debited"),
+    ];
+    let known: Vec<_> = EffectType::known()
+        .iter()
+        .map(|type_| (type_.as_i(), type_.as_str()))
+        .collect();
+    assert_eq!(table.to_vec(), known);
+    for (code, name) in table.iter() {
+        let type_ = EffectType::from_i(*code);
+        assert_eq!(Some(type_), EffectType::from_name(name));
+    }
+    assert_eq!(EffectType::Unknown(8), EffectType::from_i(8));
+    assert_eq!("unknown", EffectType::Unknown(8).as_str());
+}
+
+#[test]
+fn test_fixture_types_match_type_i() {
+    let operations: Page<Operation> =
+        serde_json::from_str(include_str!("./fixtures/all_operations.json")).unwrap();
+    for operation in operations.records {
+        let json = serde_json::to_value(&operation).unwrap();
+        assert_eq!(json["type"], operation.operation_type().as_str());
+    }
+    for fixture in &[
+        include_str!("./fixtures/all_effects.json"),
+        include_str!("./fixtures/effects_trade_and_unknown.json"),
+        include_str!("./fixtures/effects_without_account.json"),
+    ] {
+        let effects: Page<Effect> = serde_json::from_str(fixture).unwrap();
+        for effect in effects.records {
+            let json = serde_json::to_value(&effect).unwrap();
+            assert_eq!(json["type"], effect.effect_type().as_str());
+        }
+    }
+}
+
+#[test]
+fn test_mismatched_type_i_is_rejected() {
+    let operations: serde_json::Value =
+        serde_json::from_str(include_str!("./fixtures/all_operations.json")).unwrap();
+    let mut operation = operations["_embedded"]["records"][0].clone();
+    let type_ = operation["type"].as_str().unwrap().to_string();
+    let type_i = operation["type_i"].as_i64().unwrap();
+    operation["type_i"] = (type_i + 1).into();
+    let err = serde_json::from_value::<Operation>(operation.clone()).unwrap_err();
+    assert_eq!(
+        format!("type_i {} does not match type `{}`", type_i + 1, type_),
+        err.to_string()
+    );
+    assert_eq!("create_account", type_);
+    assert!(serde_json::from_value::<Payment>(operation).is_err());
+
+    let effects: serde_json::Value =
+        serde_json::from_str(include_str!("./fixtures/all_effects.json")).unwrap();
+    let mut effect = effects["_embedded"]["records"][0].clone();
+    effect["type_i"] = 99.into();
+    assert!(serde_json::from_value::<Effect>(effect.clone()).is_err());
+    effect["type"] = "new_effect".into();
+    let effect: Effect = serde_json::from_value(effect).unwrap();
+    assert_eq!(EffectType::Unknown(99), effect.effect_type());
+}
+
+#[test]
+fn test_operation_errors_keep_their_cause_and_position() {
+    let mut operations: serde_json::Value =
+        serde_json::from_str(include_str!("./fixtures/all_operations.json")).unwrap();
+    operations["_embedded"]["records"][0]["starting_balance"] = 1.into();
+    let json = serde_json::to_string_pretty(&operations).unwrap();
+    let err = serde_json::from_str::<Page<Operation>>(&json).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("invalid type: integer `1`, expected a string"));
+    assert!(err.line() > 1);
+}
+
 #[test]
 fn test_effects_base() {
     let original_json_value = json::parse(include_str!("./fixtures/all_effects.json")).unwrap();
@@ -107,3 +421,535 @@ fn test_effects_base() {
         assert!(!effect.base().paging_token.is_empty());
     }
 }
+
+#[test]
+fn test_effects_without_account_base() {
+    let original_json_value =
+        json::parse(include_str!("./fixtures/effects_without_account.json")).unwrap();
+    let original_json = original_json_value.dump();
+    let effects: Page<Effect> = serde_json::from_str(&original_json).unwrap();
+    assert_eq!(2, effects.records.len());
+    assert!(effects.records[0].base().account.is_some());
+    assert!(effects.records[1].base().account.is_none());
+}
+
+#[test]
+fn test_account_liquidity_pool_balance() {
+    let original_json_value =
+        json::parse(include_str!("./fixtures/account_with_liquidity_pool.json")).unwrap();
+    let original_json = original_json_value.dump();
+    let account: Account = serde_json::from_str(&original_json).unwrap();
+    let pools: Vec<_> = account
+        .balances
+        .iter()
+        .filter(|b| b.is_liquidity_pool_shares())
+        .collect();
+    assert_eq!(1, pools.len());
+    assert_eq!("liquidity_pool_shares", pools[0].asset.asset_type);
+    assert_eq!(
+        Some("dd7b1ab831c273310ddbec6f97870aa83c2fbd78ce22aded37ecbf4f3380fac7".to_string()),
+        pools[0].liquidity_pool_id
+    );
+    assert_eq!("12.3456789", pools[0].balance);
+    assert_eq!(None, pools[0].buying_liabilities);
+    assert_eq!(None, pools[0].buying_liabilities_amount().unwrap());
+    assert!(pools[0].balance_amount().is_ok());
+    assert_eq!(
+        BalanceKind::LiquidityPoolShares {
+            liquidity_pool_id: "dd7b1ab831c273310ddbec6f97870aa83c2fbd78ce22aded37ecbf4f3380fac7",
+            balance: "12.3456789",
+            limit: pools[0].limit.as_deref(),
+        },
+        pools[0].kind()
+    );
+    assert!(matches!(
+        account.native_balance().unwrap().kind(),
+        BalanceKind::Native { .. }
+    ));
+
+    let back_json = serde_json::to_string(&account).unwrap();
+    let back: Account = serde_json::from_str(&back_json).unwrap();
+    assert_eq!(account, back);
+    let back_json = json::parse(&back_json).unwrap();
+    let pool = back_json["balances"]
+        .members()
+        .find(|balance| balance.has_key("liquidity_pool_id"))
+        .unwrap();
+    assert!(!pool.has_key("buying_liabilities"));
+}
+
+#[test]
+fn test_account_balances() {
+    let account: Account = serde_json::from_str(include_str!("./fixtures/account.json")).unwrap();
+    let native = account.native_balance().unwrap();
+    assert_eq!("13.4560991", native.balance_amount().unwrap().to_string());
+    assert!(native.limit_amount().unwrap().is_none());
+
+    let usd = Asset {
+        asset_type: "credit_alphanum4".to_string(),
+        asset_code: Some("USD".to_string()),
+        asset_issuer: Some("GB2O5PBQJDAFCNM2U2DIMVAEI7ISOYL4UJDTLN42JYYXAENKBWY6OBKZ".to_string()),
+    };
+    let balance = account.balance_for(&usd).unwrap();
+    assert_eq!("0.0100000", balance.balance_amount().unwrap().to_string());
+    assert_eq!(
+        "922337203685.4775807",
+        balance.limit_amount().unwrap().unwrap().to_string()
+    );
+    assert_eq!(
+        "0.0000000",
+        balance
+            .selling_liabilities_amount()
+            .unwrap()
+            .unwrap()
+            .to_string()
+    );
+    assert_eq!(None, balance.is_clawback_enabled);
+
+    let mut other = usd.clone();
+    other.asset_issuer = Some(account.account_id.clone());
+    assert!(account.balance_for(&other).is_none());
+}
+
+#[test]
+fn test_trade_liquidity_pool() {
+    let original_json_value =
+        json::parse(include_str!("./fixtures/trade_liquidity_pool.json")).unwrap();
+    let original_json = original_json_value.dump();
+    let trade: Trade = serde_json::from_str(&original_json).unwrap();
+    assert_eq!(Some("liquidity_pool".to_string()), trade.trade_type);
+    assert_eq!(Some(30), trade.liquidity_pool_fee_bp);
+    assert!(trade.base_liquidity_pool_id.is_some());
+    assert!(trade.base_account.is_none());
+    assert!(trade.counter_account.is_some());
+}
+
+#[test]
+fn test_account_data_entries() {
+    let original_json_value = json::parse(include_str!("./fixtures/account.json")).unwrap();
+    let original_json = original_json_value.dump();
+    let mut account: Account = serde_json::from_str(&original_json).unwrap();
+    let entries = account.data_entries();
+    assert_eq!(1, entries.len());
+    assert_eq!(b"Hello".to_vec(), *entries["Test"].as_ref().unwrap());
+
+    account
+        .data
+        .insert("Invalid".to_string(), "not base64!".to_string());
+    let entries = account.data_entries();
+    assert_eq!(2, entries.len());
+    assert!(entries["Test"].is_ok());
+    assert!(entries["Invalid"].is_err());
+}
+
+#[test]
+fn test_effects_trade_and_unknown() {
+    let original_json_value =
+        json::parse(include_str!("./fixtures/effects_trade_and_unknown.json")).unwrap();
+    let original_json = original_json_value.dump();
+    let effects: Page<Effect> = serde_json::from_str(&original_json).unwrap();
+    assert_eq!(3, effects.records.len());
+    match &effects.records[0] {
+        Effect::Trade(effect) => {
+            assert_eq!("9", effect.offer_id);
+            assert_eq!("native", effect.sold_asset.asset_type);
+            assert_eq!(Some("JPY".to_string()), effect.bought_asset.asset_code);
+        }
+        effect => panic!("expected trade effect, got {:?}", effect),
+    }
+    match &effects.records[1] {
+        Effect::TrustLineAuthorized(effect) => {
+            assert_eq!(
+                "GBB4JST32UWKOLGYYSCEYBHBCOFL2TGBHDVOMZP462ET4ZRD4ULA7S2L",
+                effect.trustor
+            );
+        }
+        effect => panic!("expected trustline authorized effect, got {:?}", effect),
+    }
+    match &effects.records[2] {
+        Effect::Unknown(effect) => {
+            assert_eq!("liquidity_pool_deposited", effect.type_);
+            assert_eq!(90, effect.base.type_i);
+            assert!(effect.fields.contains_key("shares_received"));
+            assert!(!effect.fields.contains_key("paging_token"));
+        }
+        effect => panic!("expected unknown effect, got {:?}", effect),
+    }
+    assert_eq!(
+        "162914809290600449-1",
+        effects.records[2].base().paging_token
+    );
+}
+
+#[test]
+fn test_effects_of_known_type_are_not_unknown() {
+    let effects: serde_json::Value =
+        serde_json::from_str(include_str!("./fixtures/effects_trade_and_unknown.json")).unwrap();
+    let mut trade = effects["_embedded"]["records"][0].clone();
+    trade.as_object_mut().unwrap().remove("offer_id");
+    let err = serde_json::from_value::<Effect>(trade).unwrap_err();
+    assert_eq!("missing field `offer_id`", err.to_string());
+}
+
+#[test]
+fn test_fee_stats_recommended_max_fee() {
+    let original_json_value = json::parse(include_str!("./fixtures/fee_stats.json")).unwrap();
+    let original_json = original_json_value.dump();
+    let mut fee_stats: FeeStats = serde_json::from_str(&original_json).unwrap();
+    assert_eq!(
+        240,
+        fee_stats.recommended_max_fee(2, Congestion::Low).to_i64()
+    );
+    assert_eq!(
+        2000,
+        fee_stats
+            .recommended_max_fee(2, Congestion::Medium)
+            .to_i64()
+    );
+    assert_eq!(
+        3000,
+        fee_stats.recommended_max_fee(3, Congestion::High).to_i64()
+    );
+
+    fee_stats.last_ledger_base_fee = 500;
+    assert_eq!(
+        500,
+        fee_stats.recommended_max_fee(1, Congestion::Low).to_i64()
+    );
+}
+
+#[test]
+fn test_fee_stats_accessors() {
+    let fee_stats: FeeStats =
+        serde_json::from_str(include_str!("./fixtures/fee_stats.json")).unwrap();
+    assert_eq!(100, fee_stats.base_fee().to_i64());
+    assert_eq!(120, fee_stats.max_fee.p50().to_i64());
+    assert_eq!(1000, fee_stats.max_fee.p99().to_i64());
+    assert_eq!(10000, fee_stats.max_fee.max().to_i64());
+    assert_eq!(
+        Some(300),
+        fee_stats.max_fee.percentile(70).map(|fee| fee.to_i64())
+    );
+    assert_eq!(None, fee_stats.max_fee.percentile(75));
+    assert!(fee_stats.fee_charged.other.is_empty());
+}
+
+#[test]
+fn test_fee_stats_keeps_unknown_fields() {
+    let mut json = json::parse(include_str!("./fixtures/fee_stats.json")).unwrap();
+    json["max_fee"]["p75"] = "400".into();
+    let fee_stats: FeeStats = serde_json::from_str(&json.dump()).unwrap();
+    assert_eq!(
+        Some(400),
+        fee_stats.max_fee.percentile(75).map(|fee| fee.to_i64())
+    );
+    let back = serde_json::to_value(&fee_stats).unwrap();
+    assert_eq!("400", back["max_fee"]["p75"]);
+}
+
+#[test]
+fn test_ledger_entries_last_modified() {
+    let account: Account = serde_json::from_str(include_str!("./fixtures/account.json")).unwrap();
+    assert_eq!(Some(30765658), account.last_modified_ledger());
+    assert!(LedgerEntry::last_modified_time(&account).is_some());
+    assert_eq!(Some(30684084), account.balances[0].last_modified_ledger());
+    assert_eq!(None, account.balances[3].last_modified_ledger());
+
+    let offers: Page<Offer> =
+        serde_json::from_str(include_str!("./fixtures/all_offers.json")).unwrap();
+    assert_eq!(Some(19967410), offers.records[0].last_modified_ledger());
+    assert!(LedgerEntry::last_modified_time(&offers.records[0]).is_some());
+
+    let balance: ClaimableBalance =
+        serde_json::from_str(include_str!("./fixtures/claimable_balance.json")).unwrap();
+    assert_eq!(Some(66835), balance.last_modified_ledger());
+    assert_eq!(None, balance.last_modified_time());
+}
+
+#[test]
+fn test_horizon_error_result_codes() {
+    let error: HorizonError =
+        serde_json::from_str(include_str!("./fixtures/horizon_error_tx_failed.json")).unwrap();
+    assert!(error.is_transaction_failed());
+    assert_eq!(
+        "https://stellar.org/horizon-errors/transaction_failed",
+        error.type_
+    );
+    let result_codes = error.result_codes().unwrap();
+    assert_eq!(TransactionResultCode::Failed, result_codes.transaction);
+    assert_eq!(
+        Some(
+            &[
+                OperationResultCode::Underfunded,
+                OperationResultCode::Unknown("op_from_the_future".to_string()),
+            ][..]
+        ),
+        error.operation_result_codes()
+    );
+}
+
+#[test]
+fn test_horizon_error_transaction_result_code_only() {
+    let error: HorizonError =
+        serde_json::from_str(include_str!("./fixtures/horizon_error_tx_bad_seq.json")).unwrap();
+    let result_codes = error.result_codes().unwrap();
+    assert_eq!(TransactionResultCode::BadSeq, result_codes.transaction);
+    assert_eq!(None, result_codes.inner_transaction);
+    assert_eq!(Some(&[][..]), error.operation_result_codes());
+
+    let fee_bump = r#"{
+        "transaction": "tx_fee_bump_inner_failed",
+        "inner_transaction": "tx_failed",
+        "operations": ["op_underfunded"]
+    }"#;
+    let result_codes: TransactionResultCodes = serde_json::from_str(fee_bump).unwrap();
+    assert_eq!(
+        TransactionResultCode::FeeBumpInnerFailed,
+        result_codes.transaction
+    );
+    assert_eq!(
+        Some(TransactionResultCode::Failed),
+        result_codes.inner_transaction
+    );
+}
+
+#[test]
+fn test_account_link_requests() {
+    use stellar_horizon::request::{PageRequest, Request};
+    use url::Url;
+
+    let account: Account = serde_json::from_str(include_str!("./fixtures/account.json")).unwrap();
+    let host = Url::parse("https://horizon.stellar.org/").unwrap();
+    let prefix = format!("/accounts/{}", account.account_id);
+
+    let path = |uri: Url| uri.path().to_string();
+    assert_eq!(
+        format!("{}/transactions", prefix),
+        path(account.transactions_request().unwrap().uri(&host).unwrap())
+    );
+    assert_eq!(
+        format!("{}/payments", prefix),
+        path(account.payments_request().unwrap().uri(&host).unwrap())
+    );
+    assert_eq!(
+        format!("{}/effects", prefix),
+        path(account.effects_request().unwrap().uri(&host).unwrap())
+    );
+    assert_eq!(
+        format!("{}/offers", prefix),
+        path(account.offers_request().unwrap().uri(&host).unwrap())
+    );
+    assert_eq!(
+        format!("{}/trades", prefix),
+        path(account.trades_request().unwrap().uri(&host).unwrap())
+    );
+    assert_eq!(
+        format!("{}/data/config", prefix),
+        path(account.data_request("config").unwrap().uri(&host).unwrap())
+    );
+
+    let uri = account
+        .operations_request()
+        .unwrap()
+        .with_limit(5)
+        .uri(&host)
+        .unwrap();
+    assert_eq!(Some("horizon.stellar.org"), uri.host_str());
+    assert_eq!(format!("{}/operations", prefix), uri.path());
+    assert_eq!(Some("limit=5"), uri.query());
+
+    let other = Url::parse("https://horizon.example.org/").unwrap();
+    assert!(account.operations_request().unwrap().uri(&other).is_err());
+}
+
+#[test]
+fn test_claimable_balance_links() {
+    let balance: ClaimableBalance =
+        serde_json::from_str(include_str!("./fixtures/claimable_balance.json")).unwrap();
+    let url = balance.links.expand("self", &[]).unwrap();
+    assert_eq!(format!("/claimable_balances/{}", balance.id), url.path());
+}
+
+#[test]
+fn test_resource_links() {
+    let root: Root = serde_json::from_str(include_str!("./fixtures/root.json")).unwrap();
+    let links = Links::from(&root.links);
+    assert_eq!(Some(&root.links.ledgers), links.get("ledgers"));
+    assert_eq!(root.links.friendbot.as_ref(), links.get("friendbot"));
+    let url = links.expand("ledger", &[("sequence", "7")]).unwrap();
+    assert_eq!("/ledger/7", url.path());
+
+    let transaction: Transaction =
+        serde_json::from_str(include_str!("./fixtures/transaction_fee_bump.json")).unwrap();
+    let links = Links::from(&transaction.links);
+    assert_eq!(8, links.iter().count());
+    assert_eq!(Some(&transaction.links.self_), links.get("self"));
+}
+
+#[test]
+fn test_account_signers_and_thresholds() {
+    let mut account: Account =
+        serde_json::from_str(include_str!("./fixtures/account_with_sponsor.json")).unwrap();
+    let master = "GAXHU2XHSMTZYAKFCVTULAYUL34BFPPLRVJYZMEOHP7IWPZJKSVY67RJ";
+    let hash = "XAMF7DNTEJY74JPVMGTPZE4LFYTEGBXMGBHNUUMAA7IXMSBGHAMWSND6";
+    assert_eq!(SignerType::Ed25519PublicKey, account.signers[0].type_);
+    assert_eq!(SignerType::Sha256Hash, account.signers[1].type_);
+    assert_eq!(Some(1), account.signing_weight_for(master));
+    assert_eq!(None, account.signing_weight_for("GB"));
+    assert!(!account.flags.auth_clawback_enabled);
+
+    account.thresholds.medium_threshold = 2;
+    assert_eq!(2, account.thresholds.threshold(Threshold::Medium));
+    assert!(account.can_meet_threshold(Threshold::Low, &[master]));
+    assert!(!account.can_meet_threshold(Threshold::Low, &["GB"]));
+    assert!(!account.can_meet_threshold(Threshold::Medium, &[master, master]));
+    assert!(account.can_meet_threshold(Threshold::Medium, &[master, hash]));
+}
+
+#[test]
+fn test_signer_unknown_type() {
+    let json = r#"{"weight": 300, "key": "K", "type": "future_key"}"#;
+    let signer: Signer = serde_json::from_str(json).unwrap();
+    assert_eq!(SignerType::Unknown("future_key".to_string()), signer.type_);
+    assert_eq!(255, signer.weight());
+    assert_eq!("future_key", serde_json::to_value(&signer).unwrap()["type"]);
+}
+
+#[test]
+fn test_transaction_fee_bump() {
+    let transaction: Transaction =
+        serde_json::from_str(include_str!("./fixtures/transaction_fee_bump.json")).unwrap();
+    assert!(transaction.is_fee_bump());
+    assert_ne!(transaction.source_account, transaction.fee_account);
+    let inner = transaction.inner_transaction.as_ref().unwrap();
+    assert_eq!(300, inner.max_fee);
+    assert_ne!(transaction.hash, inner.hash);
+    assert_eq!(
+        transaction.hash,
+        transaction.fee_bump_transaction.as_ref().unwrap().hash
+    );
+
+    let transactions: Page<Transaction> =
+        serde_json::from_str(include_str!("./fixtures/all_transactions.json")).unwrap();
+    assert!(!transactions.records[0].is_fee_bump());
+    assert!(transactions.records[0].inner_transaction.is_none());
+}
+
+#[cfg(feature = "xdr")]
+#[test]
+fn test_transaction_decode_xdr() {
+    use stellar_base::xdr::{TransactionEnvelope, TransactionMeta, TransactionResultResult};
+
+    let transactions: Page<Transaction> =
+        serde_json::from_str(include_str!("./fixtures/all_transactions.json")).unwrap();
+    let transaction = &transactions.records[0];
+    let result = transaction.decode_result().unwrap();
+    assert_eq!(transaction.fee_charged, result.fee_charged.value);
+    match result.result {
+        TransactionResultResult::TxSuccess(results) => {
+            assert_eq!(transaction.operation_count as usize, results.len())
+        }
+        _ => panic!("expected a successful transaction result"),
+    }
+    match transaction.decode_envelope().unwrap() {
+        TransactionEnvelope::EnvelopeTypeTxV0(envelope) => assert_eq!(
+            transaction.operation_count as usize,
+            envelope.tx.operations.len()
+        ),
+        _ => panic!("expected a v0 transaction envelope"),
+    }
+    assert!(matches!(
+        transaction.decode_result_meta().unwrap(),
+        TransactionMeta::V0(_)
+    ));
+    transaction.decode_fee_meta().unwrap();
+
+    let mut invalid = transaction.clone();
+    invalid.result_xdr = "not base64!".to_string();
+    assert!(invalid.decode_result().is_err());
+}
+
+#[test]
+fn test_payment_accessors() {
+    let payments: Page<Payment> =
+        serde_json::from_str(include_str!("./fixtures/all_payments.json")).unwrap();
+    let create_account = &payments.records[0];
+    assert_eq!(
+        "GAAZI4TCR3TY5OJHCTJC2A4QSY6CJWJH5IAJTGKIN2ER7LBNVKOCCWN7",
+        create_account.from()
+    );
+    assert_eq!(
+        "GALPCCZN4YXA3YMJHKL6CVIECKPLJJCTVMSNYWBTKJW4K5HQLYLDMZTB",
+        create_account.to()
+    );
+    assert_eq!(Some("20.0000000"), create_account.amount());
+    assert_eq!("native", create_account.asset().asset_type);
+
+    let payment = &payments.records[1];
+    assert_eq!(create_account.from(), payment.from());
+    assert_eq!(create_account.to(), payment.to());
+    assert_eq!(Some("99999999959.9999700"), payment.amount());
+    assert_eq!("native", payment.asset().asset_type);
+    assert_eq!("12884905986", payment.base().paging_token);
+}
+
+#[test]
+fn test_transaction_preconditions() {
+    use chrono::{TimeZone, Utc};
+
+    let transaction: Transaction = serde_json::from_str(include_str!(
+        "./fixtures/transaction_with_preconditions.json"
+    ))
+    .unwrap();
+    let preconditions = transaction.preconditions.unwrap();
+    let time_bounds = preconditions.time_bounds.unwrap();
+    assert_eq!(
+        Some(Utc.timestamp_opt(1_600_000_000, 0).unwrap()),
+        time_bounds.valid_after()
+    );
+    assert!(time_bounds.contains(Utc.timestamp_opt(1_650_000_000, 0).unwrap()));
+    assert!(!time_bounds.contains(Utc.timestamp_opt(1_700_000_001, 0).unwrap()));
+    let ledger_bounds = preconditions.ledger_bounds.unwrap();
+    assert!(ledger_bounds.contains(100));
+    assert!(!ledger_bounds.contains(200));
+    assert_eq!(Some(5), preconditions.min_account_sequence_ledger_gap);
+    assert_eq!(1, preconditions.extra_signers.len());
+
+    let unbounded = TimeBounds {
+        min_time: Some("0".to_string()),
+        max_time: Some("0".to_string()),
+    };
+    assert_eq!(None, unbounded.valid_before());
+    assert!(unbounded.contains(Utc::now()));
+
+    let transactions: Page<Transaction> =
+        serde_json::from_str(include_str!("./fixtures/all_transactions.json")).unwrap();
+    assert!(transactions.records[0].preconditions.is_none());
+}
+
+#[test]
+fn test_paging_token_cursors_are_ordered() {
+    let trades: Page<Trade> =
+        serde_json::from_str(include_str!("./fixtures/all_trades.json")).unwrap();
+    for pair in trades.records.windows(2) {
+        assert!(pair[0].cursor() < pair[1].cursor());
+    }
+
+    let operations: Page<Operation> =
+        serde_json::from_str(include_str!("./fixtures/all_operations.json")).unwrap();
+    for pair in operations.records.windows(2) {
+        let ids: Vec<i64> = pair
+            .iter()
+            .map(|op| op.paging_token().parse().unwrap())
+            .collect();
+        assert_eq!(ids[0].cmp(&ids[1]), pair[0].cursor().cmp(&pair[1].cursor()));
+    }
+
+    let effects: Page<Effect> =
+        serde_json::from_str(include_str!("./fixtures/all_effects.json")).unwrap();
+    assert!(effects.records[0].cursor() < effects.records[1].cursor());
+    assert_eq!(
+        Some(12884905985),
+        effects.records[0].cursor().toid().map(|toid| toid.to_i64())
+    );
+}
@@ -0,0 +1,879 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use stellar_base::transaction::MIN_BASE_FEE;
+use stellar_base::xdr::XDRSerialize;
+use stellar_base::{KeyPair, Network, Operation, PublicKey, Transaction};
+use stellar_horizon::api;
+use stellar_horizon::cache::MemoryResponseCache;
+use stellar_horizon::client::{HorizonClient, HorizonHttpClient, StreamErrorPolicy, StreamEvent};
+use stellar_horizon::close::closeable;
+use stellar_horizon::cursor::{CursorStore, FileCursorStore};
+use stellar_horizon::error::Error;
+use stellar_horizon::horizon_error::HorizonErrorKind;
+use stellar_horizon::json::DeserializationMode;
+use stellar_horizon::request::{PageRequest, RequestExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::stream::StreamExt;
+use tokio::sync::mpsc;
+
+/// Starts a server that answers each connection with `response`, then
+/// keeps the connection open without sending more data.
+///
+/// Returns the server url and a channel with the requests received.
+async fn serve(response: String) -> (String, mpsc::UnboundedReceiver<String>) {
+    serve_many(vec![response.into_bytes()]).await
+}
+
+/// Like `serve`, but answers the n-th connection with the n-th response,
+/// repeating the last response for the following connections.
+async fn serve_many(responses: Vec<Vec<u8>>) -> (String, mpsc::UnboundedReceiver<String>) {
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        for connection in 0.. {
+            let response = &responses[connection.min(responses.len() - 1)];
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let _ = tx.send(String::from_utf8_lossy(&request).to_lowercase());
+            socket.write_all(response).await.unwrap();
+            tokio::spawn(async move {
+                tokio::time::delay_for(Duration::from_secs(60)).await;
+                drop(socket);
+            });
+        }
+    });
+    (format!("http://{}", addr), rx)
+}
+
+fn event_stream_response(body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n{}",
+        body
+    )
+}
+
+fn message(id: &str, data: &str) -> String {
+    format!("id: {}\ndata: {}\n\n", id, data)
+}
+
+fn hello() -> String {
+    "retry: 1000\nevent: open\ndata: \"hello\"\n\n".to_string()
+}
+
+fn ledger_json() -> String {
+    json::parse(include_str!("./fixtures/ledger.json"))
+        .unwrap()
+        .dump()
+}
+
+/// Returns a ledger with a field unknown to the crate.
+fn ledger_with_unknown_field_json() -> String {
+    let mut ledger = json::parse(include_str!("./fixtures/ledger.json")).unwrap();
+    ledger["new_field"] = "value".into();
+    ledger.dump()
+}
+
+#[tokio::test]
+async fn test_stream_reconnects_after_idle_timeout() {
+    let (host, mut requests) = serve(event_stream_response(&message("1-1", &ledger_json()))).await;
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_stream_idle_timeout(Duration::from_millis(200));
+    assert_eq!(
+        Some(Duration::from_millis(200)),
+        client.stream_idle_timeout()
+    );
+    let mut stream = client.stream(api::ledgers::all()).unwrap().take(2);
+    while let Some(event) = stream.next().await {
+        assert!(event.is_ok());
+    }
+    let first = requests.recv().await.unwrap();
+    assert!(!first.contains("last-event-id"));
+    let second = requests.recv().await.unwrap();
+    assert!(second.contains("last-event-id: 1-1"));
+}
+
+#[tokio::test]
+async fn test_stream_reconnects_with_backoff() {
+    let unavailable =
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    let (host, mut requests) = serve(unavailable.to_string()).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    assert_eq!(Duration::from_secs(1), client.stream_reconnect_delay());
+    let client = client.with_stream_reconnect_delay(Duration::from_millis(100));
+    assert_eq!(Duration::from_millis(100), client.stream_reconnect_delay());
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    let started = Instant::now();
+    for _ in 0..3 {
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(err, Error::UnexpectedStatus { status: 503, .. }));
+    }
+    // The second and third connections wait 100 and 200 milliseconds.
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    for _ in 0..3 {
+        requests.recv().await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_stream_reconnects_after_retry_time() {
+    let body = format!("retry: 300\n\n{}", message("1-1", &ledger_json()));
+    let (host, mut requests) = serve(event_stream_response(&body)).await;
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_stream_idle_timeout(Duration::from_millis(100))
+        .with_stream_reconnect_delay(Duration::from_millis(10));
+    let mut stream = client.stream_with_meta(api::ledgers::all()).unwrap();
+    let item = stream.next().await.unwrap().unwrap();
+    assert_eq!(Some(Duration::from_millis(300)), item.retry);
+    let started = Instant::now();
+    stream.next().await.unwrap().unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(400));
+    requests.recv().await.unwrap();
+    assert!(requests
+        .recv()
+        .await
+        .unwrap()
+        .contains("last-event-id: 1-1"));
+}
+
+#[tokio::test]
+async fn test_stream_reconnects_after_connect_timeout() {
+    let stalled_headers =
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n: partial".to_string();
+    let (host, mut requests) = serve_many(vec![
+        Vec::new(),
+        stalled_headers.into_bytes(),
+        event_stream_response(&message("1-1", &ledger_json())).into_bytes(),
+    ])
+    .await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    assert_eq!(None, client.stream_connect_timeout());
+    let client = client.with_stream_connect_timeout(Duration::from_millis(200));
+    assert_eq!(
+        Some(Duration::from_millis(200)),
+        client.stream_connect_timeout()
+    );
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    let ledger = tokio::time::timeout(Duration::from_secs(5), stream.next())
+        .await
+        .unwrap();
+    assert!(ledger.unwrap().is_ok());
+    for _ in 0..3 {
+        requests.recv().await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_stream_events_with_keep_alive() {
+    let body = format!("{}{}", hello(), message("1-1", &ledger_json()));
+    let (host, _requests) = serve(event_stream_response(&body)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let mut stream = client.stream_events(api::ledgers::all()).unwrap().take(2);
+    let event = stream.next().await.unwrap().unwrap();
+    assert_eq!(StreamEvent::KeepAlive, event);
+    let event = stream.next().await.unwrap().unwrap();
+    assert!(event.into_resource().is_some());
+}
+
+#[tokio::test]
+async fn test_stream_with_meta() {
+    let body = [
+        hello(),
+        message("1-1", &ledger_json()),
+        message("1-2", &ledger_json()),
+    ]
+    .concat();
+    let (host, _requests) = serve(event_stream_response(&body)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let mut stream = client.stream_with_meta(api::ledgers::all()).unwrap();
+    let item = stream.next().await.unwrap().unwrap();
+    assert_eq!(Some("1-1"), item.id.as_deref());
+    assert_eq!(Some(Duration::from_millis(1000)), item.retry);
+    assert!(!item.resource.hash.is_empty());
+    let item = stream.next().await.unwrap().unwrap();
+    assert_eq!(Some("1-2"), item.id.as_deref());
+}
+
+#[tokio::test]
+async fn test_stream_durable_saves_processed_cursors() {
+    let body = [
+        hello(),
+        message("1-1", &ledger_json()),
+        message("1-2", &ledger_json()),
+    ]
+    .concat();
+    let (host, mut requests) = serve(event_stream_response(&body)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let path = std::env::temp_dir().join(format!(
+        "stellar-horizon-stream-durable-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let store = FileCursorStore::new(&path);
+    let mut stream = client
+        .stream_durable(api::ledgers::all(), store.clone())
+        .unwrap();
+    stream.next().await.unwrap().unwrap();
+    assert_eq!(None, store.load().unwrap());
+    stream.next().await.unwrap().unwrap();
+    assert_eq!(Some("1-1".to_string()), store.load().unwrap());
+    drop(stream);
+    assert!(!requests.recv().await.unwrap().contains("last-event-id"));
+
+    let mut stream = client
+        .stream_durable(api::ledgers::all(), store.clone())
+        .unwrap();
+    stream.next().await.unwrap().unwrap();
+    assert!(requests
+        .recv()
+        .await
+        .unwrap()
+        .contains("last-event-id: 1-1"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_skips_keep_alive() {
+    let body = format!("{}{}", hello(), message("1-1", &ledger_json()));
+    let (host, _requests) = serve(event_stream_response(&body)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    let ledger = stream.next().await.unwrap().unwrap();
+    assert!(!ledger.paging_token.is_empty());
+}
+
+#[tokio::test]
+async fn test_stream_with_json_content_type() {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n{}",
+        ledger_json()
+    );
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    match stream.next().await {
+        Some(Err(Error::NotAnEventStream { content_type })) => {
+            assert_eq!(Some("application/json".to_string()), content_type);
+        }
+        result => panic!("expected not an event stream error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_stream_with_event_stream_content_type_parameters() {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream; charset=utf-8\r\nConnection: close\r\n\r\n{}",
+        message("1-1", &ledger_json())
+    );
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_stream_offers_for_account() {
+    let offers = json::parse(include_str!("./fixtures/all_offers.json")).unwrap();
+    let offer = offers["_embedded"]["records"][0].dump();
+    let (host, mut requests) = serve(event_stream_response(&message("1", &offer))).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let account =
+        PublicKey::from_account_id("GA73S4WXZG7EONFCIFDSZ6VOJKFC2PMV5574YDJC4V4UBDGPAYN4SPAC")
+            .unwrap();
+    let req = api::offers::for_account(&account);
+    let mut stream = client.stream(req).unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+    let request = requests.recv().await.unwrap();
+    assert!(request.starts_with(
+        "get /accounts/ga73s4wxzg7eonfcifdsz6vojkfc2pmv5574ydjc4v4ubdgpayn4spac/offers?"
+    ));
+}
+
+#[tokio::test]
+async fn test_stream_effects_resumes_from_last_event() {
+    let effects = json::parse(include_str!("./fixtures/all_effects.json")).unwrap();
+    let effect = effects["_embedded"]["records"][0].dump();
+    let paging_token = effects["_embedded"]["records"][0]["paging_token"].dump();
+    let paging_token = paging_token.trim_matches('"');
+    let body = message(paging_token, &effect);
+    let (host, mut requests) = serve(event_stream_response(&body)).await;
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_stream_idle_timeout(Duration::from_millis(200));
+
+    let mut stream = client.stream(api::effects::all()).unwrap().take(2);
+    while let Some(effect) = stream.next().await {
+        assert_eq!(paging_token, effect.unwrap().base().paging_token);
+    }
+    let first = requests.recv().await.unwrap();
+    assert!(first.starts_with("get /effects?"));
+    assert!(!first.contains("last-event-id"));
+    let second = requests.recv().await.unwrap();
+    assert!(second.starts_with("get /effects?"));
+    assert!(second.contains(&format!("last-event-id: {}", paging_token)));
+
+    let req = api::effects::for_ledger(123).with_cursor("now");
+    let mut stream = client.stream(req).unwrap().take(2);
+    while let Some(effect) = stream.next().await {
+        assert!(effect.is_ok());
+    }
+    let first = requests.recv().await.unwrap();
+    assert!(first.starts_with("get /ledgers/123/effects?cursor=now "));
+    assert!(!first.contains("last-event-id"));
+    let second = requests.recv().await.unwrap();
+    assert!(second.contains(&format!("last-event-id: {}", paging_token)));
+}
+
+#[tokio::test]
+async fn test_submit_transaction_with_idempotency_key() {
+    let transactions = json::parse(include_str!("./fixtures/all_transactions.json")).unwrap();
+    let transaction = transactions["_embedded"]["records"][0].dump();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        transaction.len(),
+        transaction
+    );
+    let (host, mut requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let key_pair = KeyPair::random().unwrap();
+    let mut tx = Transaction::builder(key_pair.public_key().clone(), 1, MIN_BASE_FEE)
+        .add_operation(
+            Operation::new_bump_sequence()
+                .with_bump_to(2)
+                .build()
+                .unwrap(),
+        )
+        .into_transaction()
+        .unwrap()
+        .into_envelope();
+    tx.sign(&key_pair, &Network::new_test()).unwrap();
+    let req = api::transactions::submit(&tx)
+        .unwrap()
+        .with_idempotency_key("key-1");
+    client.request(req).await.unwrap();
+    let request = requests.recv().await.unwrap();
+    assert!(request.starts_with("post /transactions "));
+    assert!(request.contains("idempotency-key: key-1\r\n"));
+    assert!(request.contains("content-type: application/x-www-form-urlencoded\r\n"));
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .append_pair("tx", &tx.xdr_base64().unwrap())
+        .finish();
+    assert!(request.contains(&format!("content-length: {}\r\n", body.len())));
+}
+
+fn json_response(status: &str, extra_headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}\r\n{}",
+        status,
+        body.len(),
+        extra_headers,
+        body
+    )
+}
+
+#[tokio::test]
+async fn test_request_rate_limited() {
+    let response = json_response(
+        "429 Too Many Requests",
+        "X-Ratelimit-Reset: 42\r\nRetry-After: 3\r\n",
+        "{}",
+    );
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    match client.request(api::ledgers::all()).await {
+        Err(err @ Error::RateLimited { .. }) => {
+            assert!(err.is_recoverable());
+            assert!(matches!(
+                err,
+                Error::RateLimited {
+                    reset: Some(42),
+                    ..
+                }
+            ));
+            assert_eq!(Some(Duration::from_secs(3)), err.retry_after());
+            assert_eq!(Some(429), err.status());
+        }
+        result => panic!("expected rate limited error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_request_unexpected_status() {
+    let response = json_response("503 Service Unavailable", "X-Request-Id: req-503\r\n", "{}");
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    match client.request(api::ledgers::all()).await {
+        Err(err @ Error::UnexpectedStatus { status: 503, .. }) => {
+            assert_eq!(Some(503), err.status());
+            assert_eq!(Some("req-503"), err.request_id());
+        }
+        result => panic!("expected unexpected status error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_request_timeout() {
+    let body = r#"{
+        "type": "https://stellar.org/horizon-errors/timeout",
+        "title": "Timeout",
+        "status": 504,
+        "detail": "The request has timed out."
+    }"#;
+    let response = json_response("504 Gateway Timeout", "X-Request-Id: req-504\r\n", body);
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    match client.request(api::ledgers::all()).await {
+        Err(err @ Error::Timeout { .. }) => {
+            assert!(err.is_recoverable());
+            assert_eq!(Some(504), err.status());
+            assert_eq!(Some("req-504"), err.request_id());
+            let horizon_error = err.horizon_error().unwrap();
+            assert_eq!(HorizonErrorKind::Timeout, horizon_error.kind());
+            assert_eq!(Some("req-504"), horizon_error.request_id.as_deref());
+        }
+        result => panic!("expected timeout error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_request_horizon_error() {
+    let body = json::parse(include_str!("./fixtures/horizon_error_tx_failed.json"))
+        .unwrap()
+        .dump();
+    let response = json_response("400 Bad Request", "X-Request-Id: req-400\r\n", &body);
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    match client.request(api::ledgers::all()).await {
+        Err(err @ Error::HorizonRequestError(_)) => {
+            assert!(!err.is_recoverable());
+            assert_eq!(Some(400), err.status());
+            assert_eq!(Some("req-400"), err.request_id());
+        }
+        result => panic!("expected horizon request error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_request_transaction_failed_without_operations() {
+    let body = json::parse(include_str!("./fixtures/horizon_error_tx_bad_seq.json"))
+        .unwrap()
+        .dump();
+    let response = json_response("400 Bad Request", "X-Request-Id: req-400\r\n", &body);
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    match client.request(api::ledgers::all()).await {
+        Err(err @ Error::HorizonRequestError(_)) => {
+            let horizon_error = err.horizon_error().unwrap();
+            assert!(horizon_error.is_transaction_failed());
+            assert!(horizon_error.result_codes().is_some());
+            assert_eq!(Some("req-400"), err.request_id());
+        }
+        result => panic!("expected horizon request error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_request_account_not_found() {
+    let body = r#"{
+        "type": "https://stellar.org/horizon-errors/not_found",
+        "title": "Resource Missing",
+        "status": 404,
+        "detail": "The resource at the url requested was not found."
+    }"#;
+    let (host, _requests) = serve(json_response("404 Not Found", "", body)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let public_key =
+        PublicKey::from_account_id("GA73S4WXZG7EONFCIFDSZ6VOJKFC2PMV5574YDJC4V4UBDGPAYN4SPAC")
+            .unwrap();
+    let err = client
+        .request(api::accounts::single(&public_key))
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+    assert_eq!("Resource Missing", err.horizon_error().unwrap().title);
+}
+
+#[tokio::test]
+async fn test_request_deserialization_error() {
+    let (host, _requests) = serve(json_response("200 OK", "", "{\"invalid\": true}")).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    match client.request(api::ledgers::all()).await {
+        Err(Error::Deserialization { body_snippet, .. }) => {
+            assert_eq!("{\"invalid\": true}", body_snippet);
+        }
+        result => panic!("expected deserialization error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_request_with_strict_deserialization() {
+    let ledger = ledger_with_unknown_field_json();
+    let response = json_response("200 OK", "Connection: close\r\n", &ledger);
+    let (host, _requests) = serve_many(vec![response.into_bytes()]).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    assert_eq!(DeserializationMode::Lenient, client.deserialization_mode());
+    let req = api::ledgers::SingleLedgerRequest::new(7);
+    assert!(client.request(req.clone()).await.is_ok());
+
+    let client = client.with_deserialization_mode(DeserializationMode::Strict);
+    assert_eq!(DeserializationMode::Strict, client.deserialization_mode());
+    match client.request(req).await {
+        Err(Error::Deserialization { source, .. }) => {
+            assert_eq!("unknown field `new_field`", source.to_string());
+        }
+        result => panic!("expected deserialization error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_stream_with_strict_deserialization() {
+    let events = [
+        message("1-1", &ledger_json()),
+        message("1-2", &ledger_with_unknown_field_json()),
+    ]
+    .concat();
+    let (host, _requests) = serve(event_stream_response(&events)).await;
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_deserialization_mode(DeserializationMode::Strict);
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+    match stream.next().await {
+        Some(Err(Error::Deserialization { .. })) => {}
+        result => panic!("expected deserialization error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_shared_client_across_tasks() {
+    let ledger = ledger_json();
+    let response = json_response("200 OK", "Connection: close\r\n", &ledger);
+    let (host, _requests) = serve_many(vec![response.into_bytes()]).await;
+    let client = Arc::new(HorizonHttpClient::new_from_str(&host).unwrap());
+    let tasks: Vec<_> = (0..4)
+        .map(|sequence| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                let req = api::ledgers::SingleLedgerRequest::new(sequence);
+                client.request(req).await.map(|(_, ledger)| ledger)
+            })
+        })
+        .collect();
+    for task in tasks {
+        let ledger = task.await.unwrap().unwrap();
+        assert!(!ledger.hash.is_empty());
+    }
+
+    let (host, _requests) = serve(event_stream_response(&message("1-1", &ledger_json()))).await;
+    let client = Arc::new(HorizonHttpClient::new_from_str(&host).unwrap());
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    let task = tokio::spawn(async move { stream.next().await });
+    assert!(task.await.unwrap().unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_request_with_path_prefix() {
+    let ledgers = include_str!("./fixtures/all_ledgers.json");
+    let (host, mut requests) = serve(json_response("200 OK", "", ledgers)).await;
+    let client = HorizonHttpClient::new_from_str(&format!("{}/horizon", host)).unwrap();
+    client.request(api::ledgers::all()).await.unwrap();
+    let request = requests.recv().await.unwrap();
+    assert!(request.starts_with("get /horizon/ledgers?"));
+}
+
+#[tokio::test]
+async fn test_request_without_client_headers() {
+    let ledgers = include_str!("./fixtures/all_ledgers.json");
+    let (host, mut requests) = serve(json_response("200 OK", "", ledgers)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    assert!(client.send_client_headers());
+    client.request(api::ledgers::all()).await.unwrap();
+    let request = requests.recv().await.unwrap();
+    assert!(request.contains("x-client-name: "));
+    assert!(request.contains("x-client-version: "));
+
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_send_client_headers(false);
+    assert!(!client.send_client_headers());
+    client.request(api::ledgers::all()).await.unwrap();
+    let request = requests.recv().await.unwrap();
+    assert!(!request.contains("x-client-name"));
+    assert!(!request.contains("x-client-version"));
+}
+
+#[tokio::test]
+async fn test_request_and_stream_with_headers() {
+    let ledgers = include_str!("./fixtures/all_ledgers.json");
+    let (host, mut requests) = serve(json_response("200 OK", "", ledgers)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let req = api::ledgers::all()
+        .with_header("X-Request-Id", "req-1")
+        .with_header("X-Client-Name", "my-app")
+        .with_header("x-request-id", "req-2")
+        .with_limit(2);
+    assert_eq!(&Some(2), req.limit());
+    client.request(req).await.unwrap();
+    let request = requests.recv().await.unwrap();
+    assert!(request.starts_with("get /ledgers?limit=2 "));
+    assert!(request.contains("x-request-id: req-2\r\n"));
+    assert!(!request.contains("req-1"));
+    assert!(request.contains("x-client-name: my-app\r\n"));
+    assert_eq!(1, request.matches("x-client-name").count());
+
+    let (host, mut requests) = serve(event_stream_response(&message("1-1", &ledger_json()))).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let req = api::ledgers::all().with_header("X-Request-Id", "stream-1");
+    let mut stream = client.stream(req).unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+    let request = requests.recv().await.unwrap();
+    assert!(request.contains("x-request-id: stream-1\r\n"));
+    assert!(request.contains("accept: text/event-stream\r\n"));
+}
+
+#[tokio::test]
+async fn test_stream_without_client_headers() {
+    let (host, mut requests) = serve(event_stream_response(&message("1-1", &ledger_json()))).await;
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_send_client_headers(false);
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+    let request = requests.recv().await.unwrap();
+    assert!(!request.contains("x-client-name"));
+    assert!(!request.contains("x-client-version"));
+}
+
+#[tokio::test]
+async fn test_request_single_resources() {
+    let ledger = ledger_json();
+    let (host, mut requests) = serve(json_response("200 OK", "", &ledger)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let (_, ledger) = client
+        .request(api::ledgers::SingleLedgerRequest::new(7))
+        .await
+        .unwrap();
+    assert!(!ledger.hash.is_empty());
+    let request = requests.recv().await.unwrap();
+    assert!(request.starts_with("get /ledgers/7 "));
+
+    let account = include_str!("./fixtures/account.json");
+    let (host, mut requests) = serve(json_response("200 OK", "", account)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let public_key =
+        PublicKey::from_account_id("GA73S4WXZG7EONFCIFDSZ6VOJKFC2PMV5574YDJC4V4UBDGPAYN4SPAC")
+            .unwrap();
+    let (_, account) = client
+        .request(api::accounts::SingleAccountRequest::new(&public_key))
+        .await
+        .unwrap();
+    assert!(!account.balances.is_empty());
+    let request = requests.recv().await.unwrap();
+    assert!(request
+        .starts_with("get /accounts/ga73s4wxzg7eonfcifdsz6vojkfc2pmv5574ydjc4v4ubdgpayn4spac "));
+}
+
+#[tokio::test]
+async fn test_request_on_response_hook() {
+    let (host, _requests) = serve(json_response("503 Service Unavailable", "", "{}")).await;
+    let responses = Arc::new(Mutex::new(Vec::new()));
+    let hook_responses = responses.clone();
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_on_response(move |uri, status, _elapsed| {
+            hook_responses
+                .lock()
+                .unwrap()
+                .push((uri.path().to_string(), status.as_u16()));
+        });
+    assert!(client.request(api::ledgers::all()).await.is_err());
+    assert_eq!(
+        vec![("/ledgers".to_string(), 503)],
+        *responses.lock().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_rate_limited() {
+    let response = json_response("429 Too Many Requests", "Retry-After: 10\r\n", "{}");
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    match stream.next().await {
+        Some(Err(
+            err @ Error::RateLimited {
+                reset: None,
+                retry_after: Some(_),
+                ..
+            },
+        )) => {
+            assert_eq!(Some(Duration::from_secs(10)), err.retry_after());
+        }
+        result => panic!("expected rate limited error, got {:?}", result),
+    }
+}
+
+fn invalid_utf8_response() -> Vec<u8> {
+    let mut response = event_stream_response("data: ").into_bytes();
+    response.extend_from_slice(&[0xff, 0xfe, b'\n', b'\n']);
+    response
+}
+
+#[tokio::test]
+async fn test_stream_fail_fast_on_decoder_error() {
+    let (host, _requests) = serve_many(vec![invalid_utf8_response()]).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    assert_eq!(StreamErrorPolicy::FailFast, client.stream_error_policy());
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    match stream.next().await {
+        Some(Err(Error::SSEDecoderError(message))) => assert!(message.contains("UTF-8")),
+        result => panic!("expected sse decoder error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_stream_reconnects_on_decoder_error() {
+    let valid = event_stream_response(&message("1-1", &ledger_json())).into_bytes();
+    let (host, mut requests) = serve_many(vec![invalid_utf8_response(), valid]).await;
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_stream_error_policy(StreamErrorPolicy::Reconnect);
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+    assert!(requests.recv().await.is_some());
+    assert!(requests.recv().await.is_some());
+}
+
+#[tokio::test]
+async fn test_stream_skips_invalid_event() {
+    let body = format!(
+        "{}{}",
+        message("1-1", "{\"invalid\": true}"),
+        message("1-2", &ledger_json())
+    );
+    let (host, _requests) = serve(event_stream_response(&body)).await;
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_stream_error_policy(StreamErrorPolicy::Skip);
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_stream_fail_fast_on_invalid_event() {
+    let body = message("1-1", "{\"invalid\": true}");
+    let (host, _requests) = serve(event_stream_response(&body)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let mut stream = client.stream(api::ledgers::all()).unwrap();
+    match stream.next().await {
+        Some(Err(Error::Deserialization { body_snippet, .. })) => {
+            assert_eq!("{\"invalid\": true}", body_snippet);
+        }
+        result => panic!("expected deserialization error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_request_with_response_cache() {
+    let ledger = ledger_json();
+    let (host, mut requests) = serve_many(vec![
+        json_response("200 OK", "ETag: \"v1\"\r\nConnection: close\r\n", &ledger).into_bytes(),
+        json_response("304 Not Modified", "Connection: close\r\n", "").into_bytes(),
+    ])
+    .await;
+    let client = HorizonHttpClient::new_from_str(&host)
+        .unwrap()
+        .with_response_cache(MemoryResponseCache::new());
+    let (_, first) = client.request(api::ledgers::single(7)).await.unwrap();
+    assert!(!requests.recv().await.unwrap().contains("if-none-match"));
+    let (_, second) = client.request(api::ledgers::single(7)).await.unwrap();
+    assert!(requests
+        .recv()
+        .await
+        .unwrap()
+        .contains("if-none-match: \"v1\"\r\n"));
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn test_capabilities_are_cached() {
+    let root = json::parse(include_str!("./fixtures/root.json"))
+        .unwrap()
+        .dump();
+    let response = json_response("200 OK", "Connection: close\r\n", &root);
+    let (host, mut requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let capabilities = client.capabilities().await.unwrap();
+    assert_eq!(13, capabilities.protocol_version());
+    assert_eq!(capabilities, client.capabilities().await.unwrap());
+    assert!(requests.recv().await.unwrap().starts_with("get / "));
+    assert!(requests.try_recv().is_err());
+
+    client.refresh_capabilities().await.unwrap();
+    assert!(requests.recv().await.unwrap().starts_with("get / "));
+}
+
+#[tokio::test]
+async fn test_request_not_modified_without_cache() {
+    let response = json_response("304 Not Modified", "", "");
+    let (host, _requests) = serve(response).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    match client.request(api::ledgers::single(7)).await {
+        Err(Error::UnexpectedStatus { status: 304, .. }) => {}
+        result => panic!("expected unexpected status error, got {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_stream_close() {
+    let (host, _requests) = serve(event_stream_response(&message("1-1", &ledger_json()))).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let (mut stream, handle) = closeable(client.stream(api::ledgers::all()).unwrap());
+    assert!(stream.next().await.unwrap().is_ok());
+    let closer = handle.clone();
+    tokio::spawn(async move {
+        tokio::time::delay_for(Duration::from_millis(50)).await;
+        closer.close();
+    });
+    assert!(stream.next().await.is_none());
+    assert!(handle.is_closed());
+}
+
+#[tokio::test]
+async fn test_order_book_request_and_stream() {
+    let book = json::parse(include_str!("./fixtures/book_summary.json"))
+        .unwrap()
+        .dump();
+    let summary: stellar_horizon::resources::OrderBookSummary =
+        serde_json::from_str(&book).unwrap();
+    let request =
+        api::aggregations::OrderBookRequest::from_resource_assets(&summary.base, &summary.counter)
+            .unwrap()
+            .with_limit(10);
+
+    let (host, mut requests) = serve(json_response("200 OK", "", &book)).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let (_, snapshot) = client.request(request.clone()).await.unwrap();
+    assert_eq!(summary, snapshot);
+    let sent = requests.recv().await.unwrap();
+    assert!(sent.starts_with("get /order_book?"));
+    assert!(sent.contains("selling_asset_type="));
+    assert!(sent.contains("buying_asset_type="));
+    assert!(sent.contains("limit=10"));
+
+    let (host, _requests) = serve(event_stream_response(&message("1", &book))).await;
+    let client = HorizonHttpClient::new_from_str(&host).unwrap();
+    let mut stream = client.stream(request).unwrap();
+    assert_eq!(summary, stream.next().await.unwrap().unwrap());
+}